@@ -0,0 +1,81 @@
+//! Evaluación de alertas sobre un snapshot `Metric`, independiente del frontend.
+//!
+//! La usan el panel de alertas de la GUI y la interfaz de terminal.
+//!
+//! - `Umbrales`: Límites de uso de CPU y memoria, en porcentaje.
+//! - `Alerta`: Una condición activa con su mensaje.
+//! - `evaluar`: Alertas activas de un snapshot.
+
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::metrics::Metric;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Umbrales {
+    pub cpu: f64,
+    pub memoria: f64,
+}
+
+impl Default for Umbrales {
+    fn default() -> Self {
+        Umbrales { cpu: 80.0, memoria: 80.0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TipoAlerta {
+    Cpu,
+    Memoria,
+    ProcesoSospechoso,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alerta {
+    pub tipo: TipoAlerta,
+    pub mensaje: String,
+}
+
+impl fmt::Display for Alerta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ALERTA: {}", self.mensaje)
+    }
+}
+
+/// Alertas activas del snapshot según los umbrales.
+pub fn evaluar(metric: &Metric, umbrales: &Umbrales) -> Vec<Alerta> {
+    let mut alertas = Vec::new();
+    let valores = metric.valores();
+
+    if let Some(&uso) = valores.get("cpu.uso")
+        && uso > umbrales.cpu
+    {
+        alertas.push(Alerta { tipo: TipoAlerta::Cpu, mensaje: format!("CPU usada {:.1}% excede el limite", uso) });
+    }
+
+    if let Some(&uso) = valores.get("memoria.uso")
+        && uso > umbrales.memoria
+    {
+        alertas.push(Alerta { tipo: TipoAlerta::Memoria, mensaje: format!("Memoria usada {:.1}% excede el limite", uso) });
+    }
+
+    // Procesos con capacidades peligrosas fuera del host
+    if let Some(procesos) = metric.procesos() {
+        for proceso in procesos.get_procesos_sospechosos() {
+            if let Some(seguridad) = proceso.get_seguridad() {
+                let capacidades = match seguridad.get_capacidades_efectivas() {
+                    Some(_) => seguridad.get_capacidades_peligrosas().join(", "),
+                    None => "capacidades ilegibles".to_string(),
+                };
+                alertas.push(Alerta {
+                    tipo: TipoAlerta::ProcesoSospechoso,
+                    mensaje: format!(
+                        "proceso {} ({}) tiene {} fuera de los namespaces del host",
+                        proceso.get_nombre(), proceso.get_pid(), capacidades
+                    ),
+                });
+            }
+        }
+    }
+
+    alertas
+}
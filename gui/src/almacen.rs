@@ -0,0 +1,375 @@
+//! Almacenamiento persistente de las series numéricas de `Metric`.
+//!
+//! Formato en disco: un directorio por día (UTC) y, dentro, un archivo por
+//! serie (`<dir>/AAAA-MM-DD/<serie>.bin`). Cada archivo es de solo anexado y
+//! contiene registros de 16 bytes: la marca en ms (`i64`) y el valor (`f64`),
+//! ambos en little endian. Guardar cada serie por separado (columnar) permite
+//! leer solo la serie consultada y descartar días completos por rango.
+//! Una fuente que no se volvió a muestrear desde el snapshot anterior (según
+//! `Metric::marcas`) no se vuelve a escribir. Un registro incompleto al final
+//! de un archivo (escritura interrumpida) se ignora al leer y se descarta antes
+//! de volver a anexar.
+//!
+//! - `Almacen::abrir`: Abre (o crea) el almacén en un directorio.
+//! - `Almacen::escribir`: Anexa las series de las fuentes nuevas de un snapshot.
+//! - `Almacen::consultar`: Puntos de una serie en un rango de tiempo.
+//! - `Almacen::consultar_promedios`: Igual, promediados por intervalos.
+//! - `Almacen::nombres`: Series que existen en disco.
+//! - `Almacen::podar`: Borra los días más viejos que la retención.
+//! - `directorio_datos`: `$XDG_DATA_HOME/sistema_defensa`.
+//! - `directorio_por_defecto`: `series` dentro de `directorio_datos`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use chrono::{DateTime, NaiveDate};
+use crate::metrics::Metric;
+
+/// Días que se conservan en disco si la configuración no dice otra cosa.
+pub const RETENCION_POR_DEFECTO: Duration = Duration::from_secs(90 * 86_400);
+
+const TAMANO_REGISTRO: usize = 16;
+const EXTENSION: &str = "bin";
+
+pub struct Almacen {
+    dir: PathBuf,
+    // escritores abiertos del día en curso, por serie
+    dia_actual: Option<NaiveDate>,
+    escritores: HashMap<String, BufWriter<File>>,
+    // último día en que se podó, para hacerlo una sola vez por día
+    podado: Option<NaiveDate>,
+    // marca de la última lectura escrita de cada fuente
+    marcas_escritas: BTreeMap<String, i64>,
+}
+
+impl Almacen {
+    /// Abre el almacén en `dir`, creando el directorio si no existe.
+    pub fn abrir(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Almacen { dir, dia_actual: None, escritores: HashMap::new(), podado: None, marcas_escritas: BTreeMap::new() })
+    }
+
+    pub fn get_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Anexa, con la marca del snapshot, las series numéricas de las fuentes
+    /// cuya lectura es más nueva que la última escrita de esa fuente.
+    pub fn escribir(&mut self, metric: &Metric) -> io::Result<()> {
+        let Some(dia) = dia_de(metric.marca) else { return Ok(()) };
+        let nuevas: Vec<(&String, i64)> = metric
+            .lecturas
+            .keys()
+            .filter_map(|fuente| Some((fuente, *metric.marcas.get(fuente)?)))
+            .filter(|(fuente, marca)| self.marcas_escritas.get(*fuente).is_none_or(|escrita| marca > escrita))
+            .collect();
+        if nuevas.is_empty() {
+            return Ok(());
+        }
+        if self.dia_actual != Some(dia) {
+            // cambio de día: se cierran los archivos del segmento anterior
+            self.escritores.clear();
+            fs::create_dir_all(self.dir.join(dia.to_string()))?;
+            self.dia_actual = Some(dia);
+        }
+        for (fuente, marca) in nuevas {
+            for (nombre, valor) in Metric::valores_de(&metric.lecturas[fuente]) {
+                let escritor = match self.escritores.get_mut(&nombre) {
+                    Some(escritor) => escritor,
+                    None => {
+                        let archivo = abrir_segmento(&self.ruta_segmento(dia, &nombre))?;
+                        self.escritores.entry(nombre).or_insert(BufWriter::new(archivo))
+                    }
+                };
+                escritor.write_all(&metric.marca.to_le_bytes())?;
+                escritor.write_all(&valor.to_le_bytes())?;
+            }
+            self.marcas_escritas.insert(fuente.clone(), marca);
+        }
+        // se vacía en cada snapshot para no perder datos si la aplicación termina
+        for escritor in self.escritores.values_mut() {
+            escritor.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Devuelve los puntos `(marca en ms, valor)` de una serie con `desde <= marca <= hasta`.
+    pub fn consultar(&self, nombre: &str, desde: i64, hasta: i64) -> io::Result<Vec<(i64, f64)>> {
+        let (Some(primer_dia), Some(ultimo_dia)) = (dia_de(desde), dia_de(hasta)) else {
+            return Ok(Vec::new());
+        };
+        let mut puntos = Vec::new();
+        for dia in self.dias()?.into_iter().filter(|d| *d >= primer_dia && *d <= ultimo_dia) {
+            let bytes = match fs::read(self.ruta_segmento(dia, nombre)) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            // un registro incompleto al final (escritura interrumpida) se ignora
+            for registro in bytes.chunks_exact(TAMANO_REGISTRO) {
+                let marca = i64::from_le_bytes(registro[..8].try_into().unwrap_or_default());
+                let valor = f64::from_le_bytes(registro[8..].try_into().unwrap_or_default());
+                if marca >= desde && marca <= hasta {
+                    puntos.push((marca, valor));
+                }
+            }
+        }
+        Ok(puntos)
+    }
+
+    /// Como `consultar`, pero promediando los puntos en intervalos de `resolucion` ms.
+    ///
+    /// Cada punto devuelto lleva la marca de inicio de su intervalo.
+    pub fn consultar_promedios(&self, nombre: &str, desde: i64, hasta: i64, resolucion: i64) -> io::Result<Vec<(i64, f64)>> {
+        let resolucion = resolucion.max(1);
+        let mut intervalos: BTreeMap<i64, (f64, u32)> = BTreeMap::new();
+        for (marca, valor) in self.consultar(nombre, desde, hasta)? {
+            let suma = intervalos.entry(marca - marca.rem_euclid(resolucion)).or_insert((0.0, 0));
+            suma.0 += valor;
+            suma.1 += 1;
+        }
+        Ok(intervalos.into_iter().map(|(marca, (suma, n))| (marca, suma / n as f64)).collect())
+    }
+
+    /// Nombres de las series que tienen al menos un segmento en disco.
+    pub fn nombres(&self) -> io::Result<Vec<String>> {
+        let mut nombres = BTreeSet::new();
+        for dia in self.dias()? {
+            for entrada in fs::read_dir(self.dir.join(dia.to_string()))? {
+                let ruta = entrada?.path();
+                if ruta.extension().is_some_and(|e| e == EXTENSION)
+                    && let Some(nombre) = ruta.file_stem()
+                {
+                    nombres.insert(nombre.to_string_lossy().into_owned());
+                }
+            }
+        }
+        Ok(nombres.into_iter().collect())
+    }
+
+    /// Borra los directorios de los días que quedaron enteros antes de
+    /// `marca - retencion`. Solo recorre el disco la primera vez que se llama
+    /// en cada día de `marca`; las demás llamadas de ese día no hacen nada.
+    ///
+    /// # Retorno
+    /// Cuántos días se borraron.
+    pub fn podar(&mut self, marca: i64, retencion: Duration) -> io::Result<usize> {
+        let hoy = dia_de(marca);
+        if hoy.is_none() || self.podado == hoy {
+            return Ok(0);
+        }
+        let limite = marca.saturating_sub(i64::try_from(retencion.as_millis()).unwrap_or(i64::MAX));
+        let Some(primer_dia) = dia_de(limite) else { return Ok(0) };
+        let viejos: Vec<NaiveDate> = self.dias()?.into_iter().filter(|d| *d < primer_dia).collect();
+        for dia in &viejos {
+            fs::remove_dir_all(self.dir.join(dia.to_string()))?;
+        }
+        self.podado = hoy;
+        Ok(viejos.len())
+    }
+
+    // Días con segmentos en disco, en orden
+    fn dias(&self) -> io::Result<Vec<NaiveDate>> {
+        let mut dias: Vec<NaiveDate> = fs::read_dir(&self.dir)?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.file_name().to_str()?.parse().ok())
+            .collect();
+        dias.sort();
+        Ok(dias)
+    }
+
+    fn ruta_segmento(&self, dia: NaiveDate, nombre: &str) -> PathBuf {
+        // el nombre de la serie se usa como nombre de archivo
+        let nombre: String = nombre
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-' { c } else { '_' })
+            .collect();
+        self.dir.join(dia.to_string()).join(format!("{}.{}", nombre, EXTENSION))
+    }
+}
+
+// Abre un segmento para anexar; si una escritura interrumpida dejó un registro
+// incompleto al final se descarta, para que los siguientes queden alineados
+fn abrir_segmento(ruta: &Path) -> io::Result<File> {
+    let archivo = OpenOptions::new().create(true).append(true).open(ruta)?;
+    let largo = archivo.metadata()?.len();
+    let sobrante = largo % TAMANO_REGISTRO as u64;
+    if sobrante != 0 {
+        archivo.set_len(largo - sobrante)?;
+    }
+    Ok(archivo)
+}
+
+fn dia_de(marca: i64) -> Option<NaiveDate> {
+    DateTime::from_timestamp_millis(marca).map(|fecha| fecha.date_naive())
+}
+
+/// Directorio de datos de la aplicación: `$XDG_DATA_HOME/sistema_defensa`, o
+/// `~/.local/share/sistema_defensa` si la variable no está definida.
+pub fn directorio_datos() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_DATA_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".local/share"),
+    };
+    Some(base.join("sistema_defensa"))
+}
+
+/// Directorio por defecto del almacén de series.
+pub fn directorio_por_defecto() -> Option<PathBuf> {
+    Some(directorio_datos()?.join("series"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // 2024-03-10 00:00:00 UTC
+    const DIA: i64 = 1_710_028_800_000;
+    const MINUTO: i64 = 60_000;
+
+    // Directorio temporal propio de cada prueba, vacío al empezar
+    fn directorio_prueba(nombre: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sistema_defensa-almacen-{}-{}", nombre, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    // Snapshot publicado en `marca` con una lectura de CPU y una de disco, cada una con su marca
+    fn snapshot(marca: i64, cpu: (i64, f32), disco: (i64, f64)) -> Metric {
+        serde_json::from_value(json!({
+            "id": "prueba",
+            "marca": marca,
+            "lecturas": {
+                "cpu": { "tipo": "cpu", "datos": { "brand": "x", "cantidad_nucleos": 1, "frecuencia": 0, "uso_nucleos": [cpu.1] } },
+                "disco": { "tipo": "disco", "datos": {
+                    "cantidad_discos": 0, "espacio_total": 100.0, "espacio_libre": 100.0 - disco.1, "espacio_usado": disco.1, "discos": []
+                } }
+            },
+            "marcas": { "cpu": cpu.0, "disco": disco.0 },
+            "errores": {}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn escribir_y_consultar() {
+        let dir = directorio_prueba("consultar");
+        let mut almacen = Almacen::abrir(&dir).unwrap();
+        for (i, uso) in [10.0, 20.0, 30.0].into_iter().enumerate() {
+            let marca = DIA + i as i64 * 1000;
+            almacen.escribir(&snapshot(marca, (marca, uso), (marca, 50.0 + uso as f64))).unwrap();
+        }
+        assert_eq!(almacen.consultar("cpu.uso", DIA, DIA + 2000).unwrap(), vec![(DIA, 10.0), (DIA + 1000, 20.0), (DIA + 2000, 30.0)]);
+        assert_eq!(almacen.consultar("disco.uso", DIA + 500, DIA + 1500).unwrap(), vec![(DIA + 1000, 70.0)]);
+        assert!(almacen.consultar("red.enviados_mb", DIA, DIA + 2000).unwrap().is_empty());
+        assert_eq!(almacen.nombres().unwrap(), vec!["cpu.uso".to_string(), "disco.uso".to_string()]);
+
+        // otro `Almacen` sobre el mismo directorio lee lo mismo
+        assert_eq!(Almacen::abrir(&dir).unwrap().consultar("cpu.uso", DIA, DIA + 2000).unwrap().len(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn fuentes_no_remuestreadas_no_se_vuelven_a_escribir() {
+        let dir = directorio_prueba("remuestreo");
+        let mut almacen = Almacen::abrir(&dir).unwrap();
+        // el disco se muestreó una sola vez; la CPU en cada snapshot
+        for i in 0..5 {
+            let marca = DIA + i * 1000;
+            almacen.escribir(&snapshot(marca, (marca, 10.0), (DIA, 60.0))).unwrap();
+        }
+        assert_eq!(almacen.consultar("cpu.uso", DIA, DIA + MINUTO).unwrap().len(), 5);
+        assert_eq!(almacen.consultar("disco.uso", DIA, DIA + MINUTO).unwrap(), vec![(DIA, 60.0)]);
+        // un snapshot sin ninguna fuente nueva no escribe nada
+        almacen.escribir(&snapshot(DIA + 9000, (DIA + 4000, 10.0), (DIA, 60.0))).unwrap();
+        assert_eq!(almacen.consultar("cpu.uso", DIA, DIA + MINUTO).unwrap().len(), 5);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn promedios_por_intervalo() {
+        let dir = directorio_prueba("promedios");
+        let mut almacen = Almacen::abrir(&dir).unwrap();
+        for (desplazamiento, uso) in [(0, 10.0), (30_000, 20.0), (59_999, 30.0), (MINUTO, 40.0), (3 * MINUTO + 1, 50.0)] {
+            let marca = DIA + desplazamiento;
+            almacen.escribir(&snapshot(marca, (marca, uso), (DIA, 0.0))).unwrap();
+        }
+        assert_eq!(
+            almacen.consultar_promedios("cpu.uso", DIA, DIA + 4 * MINUTO, MINUTO).unwrap(),
+            vec![(DIA, 20.0), (DIA + MINUTO, 40.0), (DIA + 3 * MINUTO, 50.0)]
+        );
+        // el rango recorta antes de promediar
+        assert_eq!(almacen.consultar_promedios("cpu.uso", DIA + 30_000, DIA + MINUTO, MINUTO).unwrap(), vec![(DIA, 25.0), (DIA + MINUTO, 40.0)]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cambio_de_dia_abre_un_segmento_nuevo() {
+        let dir = directorio_prueba("dia");
+        let mut almacen = Almacen::abrir(&dir).unwrap();
+        let (antes, despues) = (DIA - 1000, DIA + 1000);
+        almacen.escribir(&snapshot(antes, (antes, 10.0), (antes, 50.0))).unwrap();
+        almacen.escribir(&snapshot(despues, (despues, 20.0), (antes, 50.0))).unwrap();
+
+        assert!(dir.join("2024-03-09").join("cpu.uso.bin").is_file());
+        assert!(dir.join("2024-03-10").join("cpu.uso.bin").is_file());
+        assert!(!dir.join("2024-03-10").join("disco.uso.bin").exists());
+        assert_eq!(almacen.consultar("cpu.uso", antes, despues).unwrap(), vec![(antes, 10.0), (despues, 20.0)]);
+        assert_eq!(almacen.consultar("cpu.uso", DIA, despues).unwrap(), vec![(despues, 20.0)]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn podar_borra_solo_los_dias_vencidos() {
+        let dir = directorio_prueba("podar");
+        let mut almacen = Almacen::abrir(&dir).unwrap();
+        for dias in 0..5 {
+            let marca = DIA + dias * 86_400_000 + 12 * 60 * MINUTO;
+            almacen.escribir(&snapshot(marca, (marca, 10.0), (marca, 50.0))).unwrap();
+        }
+        fs::create_dir(dir.join("notas")).unwrap();
+
+        // el 2024-03-14 a mediodía con 2 días de retención el límite cae el 12 a mediodía:
+        // se borran el 10 y el 11, que quedaron enteros antes del límite
+        let ahora = DIA + 4 * 86_400_000 + 12 * 60 * MINUTO;
+        assert_eq!(almacen.podar(ahora, Duration::from_secs(2 * 86_400)).unwrap(), 2);
+        let mut restantes: Vec<String> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect();
+        restantes.sort();
+        assert_eq!(restantes, vec!["2024-03-12", "2024-03-13", "2024-03-14", "notas"]);
+
+        // una sola pasada por día
+        assert_eq!(almacen.podar(ahora + 1000, Duration::ZERO).unwrap(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn registro_incompleto_al_final_se_ignora() {
+        let dir = directorio_prueba("incompleto");
+        let mut almacen = Almacen::abrir(&dir).unwrap();
+        for i in 0..2 {
+            let marca = DIA + i * 1000;
+            almacen.escribir(&snapshot(marca, (marca, 10.0 + i as f32), (DIA, 0.0))).unwrap();
+        }
+        // una escritura interrumpida deja medio registro
+        let ruta = dir.join("2024-03-10").join("cpu.uso.bin");
+        let mut archivo = OpenOptions::new().append(true).open(&ruta).unwrap();
+        archivo.write_all(&(DIA + 2000).to_le_bytes()[..7]).unwrap();
+        assert_eq!(fs::metadata(&ruta).unwrap().len(), 2 * TAMANO_REGISTRO as u64 + 7);
+
+        assert_eq!(almacen.consultar("cpu.uso", DIA, DIA + MINUTO).unwrap(), vec![(DIA, 10.0), (DIA + 1000, 11.0)]);
+
+        // al reabrir, el medio registro se descarta antes de anexar
+        let mut almacen = Almacen::abrir(&dir).unwrap();
+        almacen.escribir(&snapshot(DIA + 3000, (DIA + 3000, 13.0), (DIA, 0.0))).unwrap();
+        assert_eq!(fs::metadata(&ruta).unwrap().len(), 3 * TAMANO_REGISTRO as u64);
+        assert_eq!(
+            almacen.consultar("cpu.uso", DIA, DIA + MINUTO).unwrap(),
+            vec![(DIA, 10.0), (DIA + 1000, 11.0), (DIA + 3000, 13.0)]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
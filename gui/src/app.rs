@@ -1,23 +1,71 @@
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use eframe::egui;
 
-use crate::{metrics, views};
+use crate::{alertas, daemon, exportar, flota, grabacion, metrics, views};
+use crate::config::{Alertas, Configuracion, Porcentaje, Vigilante};
+use exportar::Formato;
+use grabacion::Reproduccion;
 use views::panel_metricas::PanelMetricas;
 use views::panel_alertas::PanelAlertas;
 use views::panel_graficas::PanelGraficas;
+use views::panel_procesos::PanelProcesos;
+use views::panel_arbol_procesos::PanelArbolProcesos;
+use views::panel_cgroups::PanelCgroups;
+use views::panel_flota::PanelFlota;
+use views::panel_configuracion::{AccionConfiguracion, PanelConfiguracion};
 
 use metrics::Metrics;
 
 pub struct SistemaDefensaApp {
     pub monitoring: bool,
     pub metrics: Arc<Mutex<Metrics>>,
+    pub configuracion: Configuracion,
+    // lo que hay en el archivo (o los valores por defecto si no existe)
+    pub configuracion_archivo: Configuracion,
+    // archivo de configuración que se vigila para recargarla
+    pub vigilante: Option<Vigilante>,
+    // última recarga rechazada; mientras tanto sigue `configuracion`
+    pub error_configuracion: Option<String>,
     pub logs: Vec<String>,
     pub vista_actual: Vista,
     pub panel_graficas: PanelGraficas,
+    pub panel_procesos: PanelProcesos,
+    pub panel_arbol_procesos: PanelArbolProcesos,
+    pub panel_cgroups: PanelCgroups,
+    pub panel_flota: PanelFlota,
+    pub panel_configuracion: PanelConfiguracion,
+    // equipo de la flota que muestran los paneles en lugar del local
+    pub equipo_remoto: Option<flota::Equipo>,
+    // conexión con el daemon en curso (ver `Metrics::conectar_daemon`)
+    pub conexion_daemon: Option<metrics::ConexionDaemon>,
     pub cpu_alert_threshold: f64,
     pub mem_alert_threshold: f64,
+    // modo reproducción: los paneles leen de la grabación en lugar del monitoreo
+    pub reproduccion: Option<Reproduccion>,
+    pub ruta_reproduccion: String,
+    pub mostrar_reproduccion: bool,
+    pub error_grabacion: Option<String>,
+    pub mostrar_exportar: bool,
+    pub formato_exportar: Formato,
+    pub alcance_exportar: usize,
+    pub ruta_exportar: String,
+    pub mensaje_exportar: Option<String>,
 }
 
+/// Clave con la que se guardan los umbrales de alerta entre sesiones (ver `eframe::App::save`).
+/// Solo se usan si no hay archivo de configuración: si lo hay, manda el archivo.
+const CLAVE_ALERTAS: &str = "alertas";
+
+/// Alcances de la exportación: nombre y minutos hacia atrás (`None` = solo el snapshot actual).
+const ALCANCES_EXPORTAR: [(&str, Option<i64>); 4] = [
+    ("Snapshot actual", None),
+    ("Últimos 5 min", Some(5)),
+    ("Última hora", Some(60)),
+    ("Todo lo que hay en memoria", Some(i64::MAX / 60_000)),
+];
+
 enum Monitoreado {
     Activado,
     Desactivado,
@@ -26,25 +74,124 @@ enum Vista {
     PanelMetricas,
     PanelAlertas,
     PanelGraficas,
+    PanelProcesos,
+    PanelArbolProcesos,
+    PanelCgroups,
+    PanelFlota,
+    PanelConfiguracion,
 }
 
 impl Default for SistemaDefensaApp {
     fn default() -> Self {
+        Self::con_configuracion(Configuracion::default(), None)
+    }
+}
+
+impl SistemaDefensaApp {
+    /// Crea la aplicación con los umbrales, las fuentes, la retención y la
+    /// zona horaria de `configuracion`; si cambia el archivo `ruta`, se recarga.
+    pub fn con_configuracion(configuracion: Configuracion, ruta: Option<PathBuf>) -> Self {
+        let umbrales = configuracion.umbrales();
         Self {
             monitoring: false,
-            metrics: Arc::new(Mutex::new(Metrics::new())),
+            metrics: Arc::new(Mutex::new(Metrics::con_configuracion(&configuracion))),
+            configuracion_archivo: configuracion.clone(),
+            configuracion,
+            vigilante: ruta.map(Vigilante::new),
+            error_configuracion: None,
             logs: Vec::new(),
             vista_actual: Vista::PanelMetricas,
             panel_graficas: PanelGraficas::new(),
-            cpu_alert_threshold: 80.0,
-            mem_alert_threshold: 80.0,
+            panel_procesos: PanelProcesos::new(),
+            panel_arbol_procesos: PanelArbolProcesos::new(),
+            panel_cgroups: PanelCgroups::new(),
+            panel_flota: PanelFlota::new(),
+            panel_configuracion: PanelConfiguracion::new(),
+            equipo_remoto: None,
+            conexion_daemon: None,
+            cpu_alert_threshold: umbrales.cpu,
+            mem_alert_threshold: umbrales.memoria,
+            reproduccion: None,
+            ruta_reproduccion: String::new(),
+            mostrar_reproduccion: false,
+            error_grabacion: None,
+            mostrar_exportar: false,
+            formato_exportar: Formato::Json,
+            alcance_exportar: 0,
+            ruta_exportar: String::new(),
+            mensaje_exportar: None,
             // panel_graficas: PanelGraficas::new(),
         }
     }
+
+    /// Como `con_configuracion`; si no hay archivo de configuración, conserva
+    /// los umbrales de alerta que el operador dejó en la sesión anterior (quedan
+    /// como cambios sin guardar). Con archivo, sus umbrales son los que valen.
+    pub fn new(cc: &eframe::CreationContext<'_>, configuracion: Configuracion, ruta: Option<PathBuf>) -> Self {
+        let mut app = Self::con_configuracion(configuracion, ruta);
+        if app.vigilante.is_none()
+            && let Some(alertas) = cc.storage.and_then(|s| eframe::get_value::<Alertas>(s, CLAVE_ALERTAS))
+            && alertas != app.configuracion.alertas
+        {
+            let mut nueva = app.configuracion.clone();
+            nueva.alertas = alertas;
+            app.aplicar_configuracion(nueva);
+        }
+        app
+    }
 }
 
 impl eframe::App for SistemaDefensaApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // con archivo de configuración los umbrales se guardan ahí (panel de configuración)
+        if self.vigilante.is_none() {
+            eframe::set_value(storage, CLAVE_ALERTAS, &self.configuracion.alertas);
+        }
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // recoger los snapshots que el hilo de monitoreo haya publicado
+        if let Ok(mut metrics) = self.metrics.try_lock() {
+            metrics.recibir();
+        }
+        if let Some(conexion) = &self.conexion_daemon {
+            match conexion.revisar() {
+                Some(Ok(historico)) => {
+                    self.metrics.lock().unwrap().usar_daemon(historico);
+                    self.monitoring = true;
+                    self.conexion_daemon = None;
+                }
+                Some(Err(e)) => {
+                    self.logs.push(format!("No se pudo conectar al daemon en {}: {}", conexion.get_ruta().display(), e));
+                    self.conexion_daemon = None;
+                }
+                None => ctx.request_repaint_after(Duration::from_millis(100)),
+            }
+        }
+        if self.monitoring || self.panel_flota.escuchando() {
+            ctx.request_repaint_after(Duration::from_millis(500));
+        }
+        if let Some(vigilante) = &mut self.vigilante {
+            match vigilante.revisar() {
+                Some(Ok(nueva)) => {
+                    self.logs.push(format!("Configuración recargada de {}", vigilante.get_ruta().display()));
+                    self.configuracion_archivo = nueva.clone();
+                    self.aplicar_configuracion(nueva);
+                    self.error_configuracion = None;
+                }
+                Some(Err(e)) => self.error_configuracion = Some(e.to_string()),
+                None => {}
+            }
+            // para notar los cambios del archivo aunque no haya otra actividad
+            ctx.request_repaint_after(Duration::from_secs(2));
+        }
+        if let Some(reproduccion) = &mut self.reproduccion {
+            reproduccion.avanzar();
+            if reproduccion.reproduciendo() {
+                ctx.request_repaint_after(Duration::from_millis(100));
+            }
+        }
+
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.label("Sistema de Defensa");
@@ -61,17 +208,69 @@ impl eframe::App for SistemaDefensaApp {
                     self.vista_actual = Vista::PanelGraficas;
                     ctx.request_repaint();
                 }
+                if ui.button("Procesos").clicked() {
+                    self.vista_actual = Vista::PanelProcesos;
+                    ctx.request_repaint();
+                }
+                if ui.button("Árbol de procesos").clicked() {
+                    self.vista_actual = Vista::PanelArbolProcesos;
+                    ctx.request_repaint();
+                }
+                if ui.button("Contenedores").clicked() {
+                    self.vista_actual = Vista::PanelCgroups;
+                    ctx.request_repaint();
+                }
+                if ui.button("Flota").clicked() {
+                    self.vista_actual = Vista::PanelFlota;
+                    ctx.request_repaint();
+                }
+                if ui.button("Configuración").clicked() {
+                    self.vista_actual = Vista::PanelConfiguracion;
+                    ctx.request_repaint();
+                }
                 if ui.button(if self.monitoring { "Parar Monitoreo" } else { "Iniciar Monitoreo" }).clicked() {
                     if !self.monitoring {
                         self.monitoring = true;
-                        let metrics_clone = Arc::clone(&self.metrics);
-                        self.metrics.lock().unwrap().start_monitoring(metrics_clone);
+                        // el monitoreo local reemplaza una conexión con el daemon a medio hacer
+                        self.conexion_daemon = None;
+                        self.metrics.lock().unwrap().start_monitoring_con(self.configuracion.registro());
                     } else {
                         self.monitoring = false;
                         self.metrics.lock().unwrap().stop_monitoring();
                     }
                     ctx.request_repaint();
                 }
+                if self.conexion_daemon.is_some() {
+                    ui.label("Conectando al daemon…");
+                } else if !self.monitoring && ui.button("Conectar al daemon").clicked() {
+                    // el daemon recolecta; "Parar Monitoreo" desconecta
+                    if let Some(ruta) = daemon::ruta_socket_por_defecto() {
+                        self.conexion_daemon = Some(self.metrics.lock().unwrap().conectar_daemon(&ruta));
+                    }
+                }
+                if self.reproduccion.is_none() {
+                    let grabando = self.metrics.lock().map(|m| m.get_grabacion().is_some()).unwrap_or(false);
+                    if ui.button(if grabando { "Detener grabación" } else { "Grabar" }).clicked() {
+                        let mut metrics = self.metrics.lock().unwrap();
+                        if grabando {
+                            metrics.detener_grabacion();
+                        } else {
+                            let ruta = grabacion::ruta_nueva_grabacion();
+                            self.error_grabacion = metrics
+                                .iniciar_grabacion(ruta.clone())
+                                .err()
+                                .map(|e| format!("No se pudo crear {}: {}", ruta.display(), e));
+                        }
+                    }
+                }
+                if ui.button("Reproducir grabación").clicked() {
+                    self.mostrar_reproduccion = !self.mostrar_reproduccion;
+                }
+                if ui.button("Exportar").clicked() {
+                    self.mostrar_exportar = !self.mostrar_exportar;
+                    self.ruta_exportar = exportar::ruta_nueva_exportacion(self.formato_exportar).display().to_string();
+                    self.mensaje_exportar = None;
+                }
                 if ui.button("Salir").clicked() {
                     ctx.request_repaint();
                     std::process::exit(0);
@@ -79,22 +278,264 @@ impl eframe::App for SistemaDefensaApp {
             });
         });
 
+        let grabando = self.metrics.lock().is_ok_and(|m| m.get_grabacion().is_some());
+        if self.mostrar_reproduccion || self.reproduccion.is_some() || self.error_grabacion.is_some() || grabando {
+            egui::TopBottomPanel::bottom("reproduccion").show(ctx, |ui| {
+                self.mostrar_controles_reproduccion(ui);
+            });
+        }
+
+        if let Some(error) = &self.error_configuracion {
+            let mut cerrar = false;
+            egui::TopBottomPanel::top("error_configuracion").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::RED, format!("Configuración rechazada, sigue la anterior: {}", error));
+                    cerrar = ui.button("Cerrar").clicked();
+                });
+            });
+            if cerrar {
+                self.error_configuracion = None;
+            }
+        }
+
+        if let Some(equipo) = &self.equipo_remoto {
+            let mut volver = false;
+            egui::TopBottomPanel::top("equipo_remoto").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Viendo el equipo {} ({})", equipo.get_nombre(), equipo.get_direccion()));
+                    volver = ui.button("Volver al equipo local").clicked();
+                });
+            });
+            if volver {
+                self.equipo_remoto = None;
+            }
+        }
+
+        // en modo reproducción todos los paneles leen del histórico reproducido,
+        // y al elegir un equipo de la flota, del histórico de ese equipo
+        let metrics = match (&self.reproduccion, &self.equipo_remoto) {
+            (Some(reproduccion), _) => Arc::clone(&reproduccion.metrics),
+            (None, Some(equipo)) => Arc::clone(equipo.get_metrics()),
+            (None, None) => Arc::clone(&self.metrics),
+        };
+
+        if self.mostrar_exportar {
+            let mut abierta = true;
+            egui::Window::new("Exportar").open(&mut abierta).show(ctx, |ui| {
+                self.mostrar_exportar_ventana(ui, &metrics);
+            });
+            self.mostrar_exportar = abierta;
+        }
+
         match self.vista_actual {
             Vista::PanelMetricas => {
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    PanelMetricas::show(ui, &self.metrics);
+                    PanelMetricas::show(ui, &metrics, self.configuracion.interfaz.unidades);
                 });
             }
             Vista::PanelAlertas => {
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    PanelAlertas::show(ui, &self.logs, &mut self.cpu_alert_threshold, &mut self.mem_alert_threshold, &self.metrics);
+                    PanelAlertas::show(ui, &self.logs, &mut self.cpu_alert_threshold, &mut self.mem_alert_threshold, &metrics);
                 });
+                // los deslizadores forman parte de la configuración
+                let alertas = Alertas { cpu: Porcentaje(self.cpu_alert_threshold), memoria: Porcentaje(self.mem_alert_threshold) };
+                if alertas != self.configuracion.alertas {
+                    let mut nueva = self.configuracion.clone();
+                    nueva.alertas = alertas;
+                    self.aplicar_configuracion(nueva);
+                }
             }
             Vista::PanelGraficas => {
                 egui::CentralPanel::default().show(ctx, |ui| {
-                    self.panel_graficas.show(ui, &self.metrics);
+                    self.panel_graficas.show(ui, &metrics);
+                });
+            }
+            Vista::PanelProcesos => {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    self.panel_procesos.show(ui, &metrics, self.configuracion.interfaz.unidades);
+                });
+            }
+            Vista::PanelArbolProcesos => {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    self.panel_arbol_procesos.show(ui, &metrics);
+                });
+            }
+            Vista::PanelCgroups => {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    self.panel_cgroups.show(ui, &metrics);
+                });
+            }
+            Vista::PanelFlota => {
+                let umbrales = alertas::Umbrales { cpu: self.cpu_alert_threshold, memoria: self.mem_alert_threshold };
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    if let Some(equipo) = self.panel_flota.show(ui, &umbrales) {
+                        self.equipo_remoto = Some(equipo);
+                        self.vista_actual = Vista::PanelMetricas;
+                    }
+                });
+            }
+            Vista::PanelConfiguracion => {
+                let ruta = self.vigilante.as_ref().map(|v| v.get_ruta().to_path_buf());
+                let sin_guardar = self.configuracion != self.configuracion_archivo;
+                let accion = egui::CentralPanel::default()
+                    .show(ctx, |ui| self.panel_configuracion.show(ui, &self.configuracion, ruta.as_deref(), sin_guardar))
+                    .inner;
+                match accion {
+                    Some(AccionConfiguracion::Aplicar(nueva)) => {
+                        self.aplicar_configuracion(nueva);
+                        self.panel_configuracion.informar(Ok("Configuración aplicada (sin guardar).".to_string()));
+                    }
+                    Some(AccionConfiguracion::Guardar(nueva)) => {
+                        let resultado = self.guardar_configuracion(&nueva);
+                        // el archivo rechazado quedó reemplazado por uno válido
+                        if resultado.is_ok() {
+                            self.error_configuracion = None;
+                        }
+                        self.aplicar_configuracion(nueva);
+                        self.panel_configuracion.informar(resultado);
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+}
+
+impl SistemaDefensaApp {
+    /// Aplica una configuración nueva (del archivo o de la GUI) sin detener el
+    /// monitoreo ni vaciar el histórico.
+    ///
+    /// No toca `error_configuracion`: ese aviso es sobre el archivo y solo se
+    /// quita cuando el archivo vuelve a ser válido.
+    pub fn aplicar_configuracion(&mut self, nueva: Configuracion) {
+        self.metrics.lock().unwrap().aplicar_configuracion(&self.configuracion, &nueva);
+        let umbrales = nueva.umbrales();
+        self.cpu_alert_threshold = umbrales.cpu;
+        self.mem_alert_threshold = umbrales.memoria;
+        self.configuracion = nueva;
+    }
+
+    // Escribe `configuracion` en el archivo vigilado, sin que cuente como un cambio externo
+    fn guardar_configuracion(&mut self, configuracion: &Configuracion) -> Result<String, String> {
+        let Some(vigilante) = &mut self.vigilante else {
+            return Err("No hay ruta para el archivo de configuración.".to_string());
+        };
+        let ruta = vigilante.get_ruta().to_path_buf();
+        configuracion
+            .guardar(&ruta)
+            .map_err(|e| format!("No se pudo guardar en {}: {} (los cambios se aplicaron igual)", ruta.display(), e))?;
+        vigilante.sincronizar();
+        self.configuracion_archivo = configuracion.clone();
+        Ok(format!("Configuración guardada en {}.", ruta.display()))
+    }
+
+    // Barra inferior: estado de la grabación y controles de reproducción
+    fn mostrar_controles_reproduccion(&mut self, ui: &mut egui::Ui) {
+        if let Some(error) = &self.error_grabacion {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        if let Ok(metrics) = self.metrics.lock()
+            && let Some(grabacion) = metrics.get_grabacion()
+        {
+            match grabacion.get_error() {
+                Some(error) => ui.colored_label(
+                    egui::Color32::RED,
+                    format!("Se dejó de grabar en {} ({} snapshots): {}", grabacion.get_ruta().display(), grabacion.get_cantidad(), error),
+                ),
+                None => ui.label(format!("Grabando en {} ({} snapshots)", grabacion.get_ruta().display(), grabacion.get_cantidad())),
+            };
+        }
+
+        let Some(reproduccion) = &mut self.reproduccion else {
+            if self.mostrar_reproduccion {
+                ui.horizontal(|ui| {
+                    ui.label("Archivo:");
+                    ui.text_edit_singleline(&mut self.ruta_reproduccion);
+                    if ui.button("Abrir").clicked() {
+                        match Reproduccion::abrir(self.ruta_reproduccion.trim()) {
+                            Ok(reproduccion) => {
+                                self.reproduccion = Some(reproduccion);
+                                self.error_grabacion = None;
+                            }
+                            Err(e) => self.error_grabacion = Some(format!("No se pudo abrir la grabación: {}", e)),
+                        }
+                    }
                 });
             }
+            return;
+        };
+
+        let mut salir = false;
+        ui.horizontal(|ui| {
+            ui.label(format!("Reproduciendo {} ({} snapshots)", reproduccion.get_ruta().display(), reproduccion.get_cantidad()));
+            if ui.button(if reproduccion.reproduciendo() { "Pausa" } else { "Reproducir" }).clicked() {
+                reproduccion.alternar();
+            }
+            let mut velocidad = reproduccion.get_velocidad();
+            if ui.add(egui::Slider::new(&mut velocidad, 0.25..=32.0).logarithmic(true).suffix("x")).changed() {
+                reproduccion.set_velocidad(velocidad);
+            }
+            if ui.button("Salir de la reproducción").clicked() {
+                salir = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            let inicio = reproduccion.get_inicio();
+            let mut segundos = (reproduccion.get_tiempo() - inicio) as f64 / 1000.0;
+            let duracion = (reproduccion.get_fin() - inicio) as f64 / 1000.0;
+            ui.spacing_mut().slider_width = (ui.available_width() - 120.0).max(100.0);
+            let respuesta = ui.add(egui::Slider::new(&mut segundos, 0.0..=duracion).suffix(" s"));
+            if respuesta.changed() {
+                reproduccion.ir_a(inicio + (segundos * 1000.0) as i64);
+            }
+        });
+        if salir {
+            self.reproduccion = None;
+            self.mostrar_reproduccion = false;
         }
     }
-}
\ No newline at end of file
+
+    // Ventana de exportación del snapshot actual o de un rango del histórico
+    fn mostrar_exportar_ventana(&mut self, ui: &mut egui::Ui, metrics: &Arc<Mutex<Metrics>>) {
+        ui.horizontal(|ui| {
+            ui.label("Formato:");
+            for formato in Formato::TODOS {
+                if ui.selectable_value(&mut self.formato_exportar, formato, formato.nombre()).changed() {
+                    self.ruta_exportar = exportar::ruta_nueva_exportacion(formato).display().to_string();
+                }
+            }
+        });
+        egui::ComboBox::from_label("Alcance")
+            .selected_text(ALCANCES_EXPORTAR[self.alcance_exportar].0)
+            .show_ui(ui, |ui| {
+                for (i, (nombre, _)) in ALCANCES_EXPORTAR.iter().enumerate() {
+                    ui.selectable_value(&mut self.alcance_exportar, i, *nombre);
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.label(if self.formato_exportar == Formato::Csv { "Directorio:" } else { "Archivo:" });
+            ui.text_edit_singleline(&mut self.ruta_exportar);
+        });
+        if ui.button("Exportar").clicked() {
+            let Ok(metrics) = metrics.lock() else { return };
+            let metricas: Vec<&metrics::Metric> = match (ALCANCES_EXPORTAR[self.alcance_exportar].1, metrics.latest()) {
+                (_, None) => Vec::new(),
+                (None, Some(ultimo)) => vec![ultimo],
+                (Some(minutos), Some(ultimo)) => metrics.rango(ultimo.marca.saturating_sub(minutos * 60_000), ultimo.marca).collect(),
+            };
+            let ruta = std::path::PathBuf::from(self.ruta_exportar.trim());
+            self.mensaje_exportar = Some(if metricas.is_empty() {
+                "No hay snapshots para exportar.".to_string()
+            } else {
+                match exportar::exportar(&metricas, self.formato_exportar, &ruta) {
+                    Ok(()) => format!("Se exportaron {} snapshots a {}", metricas.len(), ruta.display()),
+                    Err(e) => format!("No se pudo exportar: {}", e),
+                }
+            });
+        }
+        if let Some(mensaje) = &self.mensaje_exportar {
+            ui.label(mensaje);
+        }
+        ui.small("Los archivos JSON y NDJSON se pueden abrir con \"Reproducir grabación\".");
+    }
+}
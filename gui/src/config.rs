@@ -1 +1,808 @@
-//TODO: implementar panel para configuracion de alertas
\ No newline at end of file
+//! Archivo de configuración en TOML.
+//!
+//! Se busca, en orden, en la ruta indicada con `--config`, en
+//! `$XDG_CONFIG_HOME/sistema_defensa/config.toml` (o `~/.config/...`) y en
+//! `/etc/sistema_defensa/config.toml`; si no hay ninguno se usan los valores
+//! por defecto. Toda sección y todo campo son opcionales, y un campo
+//! desconocido es un error (para no ignorar en silencio una errata).
+//!
+//! ```toml
+//! [alertas]                    # porcentaje de uso que dispara una alerta
+//! cpu = 80.0
+//! memoria = 80.0
+//!
+//! [monitoreo]
+//! zona_horaria = "America/Mexico_City"   # nombre de la base de datos IANA
+//!
+//! # fuentes activas y cada cuánto se muestrean; sin esta tabla se activan
+//! # todas, y si está, las que no aparecen quedan desactivadas
+//! [monitoreo.recolectores]
+//! cpu = "1s"
+//! memoria = "1s"
+//! red = "1s"
+//! disco = "30s"
+//! procesos = "5s"
+//! cgroups = "5s"
+//! inventario = "1h"
+//!
+//! [retencion]                  # histórico en memoria
+//! completa = "1h"
+//! procesos = "10m"
+//! niveles = [
+//!     { resolucion = "1m", duracion = "1d" },
+//!     { resolucion = "15m", duracion = "30d" },
+//! ]
+//!
+//! [almacen]                    # series persistidas en disco
+//! activo = true
+//! directorio = "/var/lib/sistema_defensa/series"   # por defecto, $XDG_DATA_HOME/sistema_defensa/series
+//! retencion = "90d"            # los días más viejos se borran
+//!
+//! [interfaz]
+//! unidades = "MB"              # tamaños de memoria en "MB" o "GiB"
+//! ```
+//!
+//! Las duraciones son un entero con unidad: `ms`, `s`, `m`, `h` o `d`.
+//!
+//! La GUI y el daemon vigilan el archivo con `Vigilante` y aplican los cambios
+//! sin reiniciar el monitoreo (ver `Metrics::aplicar_configuracion`); si el
+//! archivo nuevo no es válido se informa el error y sigue la configuración anterior.
+//! La vista "Configuración" de la GUI edita estos valores y los guarda con `guardar`.
+//!
+//! - `Configuracion`: El archivo completo, con `cargar`, `buscar`, `desde_texto` y `guardar`.
+//! - `Alertas` / `Monitoreo` / `Recolectores` / `ConfigRetencion` / `ConfigAlmacen` / `Interfaz`: Sus secciones.
+//! - `Porcentaje` / `Duracion` / `Zona` / `Unidades`: Valores que se validan al leerse.
+//! - `ErrorConfig`: Error de lectura, o de formato con su línea y columna.
+//! - `Vigilante`: Detecta cambios en el archivo y lo vuelve a leer.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use instrumentos::recolector::{Registro, INTEGRADOS};
+use crate::alertas::Umbrales;
+use crate::almacen;
+use crate::metrics::Retencion;
+
+/// Nombre del archivo dentro de los directorios de configuración.
+pub const NOMBRE_ARCHIVO: &str = "config.toml";
+/// Cada cuánto `Vigilante` mira la fecha de modificación del archivo.
+const INTERVALO_REVISION: Duration = Duration::from_secs(2);
+
+/// Configuración completa; cada sección cae en sus valores por defecto.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Configuracion {
+    pub alertas: Alertas,
+    pub monitoreo: Monitoreo,
+    pub retencion: ConfigRetencion,
+    pub almacen: ConfigAlmacen,
+    pub interfaz: Interfaz,
+}
+
+/// Umbrales de alerta, en porcentaje de uso.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Alertas {
+    pub cpu: Porcentaje,
+    pub memoria: Porcentaje,
+}
+
+impl Default for Alertas {
+    fn default() -> Self {
+        let umbrales = Umbrales::default();
+        Alertas { cpu: Porcentaje(umbrales.cpu), memoria: Porcentaje(umbrales.memoria) }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Monitoreo {
+    pub zona_horaria: Zona,
+    pub recolectores: Recolectores,
+}
+
+/// Intervalo de cada fuente integrada; `None` la desactiva.
+///
+/// A diferencia de las demás secciones, un campo ausente no toma el valor por
+/// defecto sino que desactiva la fuente; el valor por defecto de la tabla
+/// entera (cuando no aparece en el archivo) las activa todas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Recolectores {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<Duracion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memoria: Option<Duracion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub red: Option<Duracion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disco: Option<Duracion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub procesos: Option<Duracion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cgroups: Option<Duracion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inventario: Option<Duracion>,
+}
+
+impl Default for Recolectores {
+    /// Todas las fuentes integradas con sus intervalos por defecto.
+    fn default() -> Self {
+        let mut recolectores = Recolectores {
+            cpu: None,
+            memoria: None,
+            red: None,
+            disco: None,
+            procesos: None,
+            cgroups: None,
+            inventario: None,
+        };
+        for (nombre, intervalo) in INTEGRADOS {
+            if let Some(campo) = recolectores.campo_mut(nombre) {
+                *campo = Some(Duracion(intervalo));
+            }
+        }
+        recolectores
+    }
+}
+
+impl Recolectores {
+    /// Campo de la fuente integrada `nombre`; `None` si no es una de `INTEGRADOS`.
+    pub fn campo_mut(&mut self, nombre: &str) -> Option<&mut Option<Duracion>> {
+        match nombre {
+            "cpu" => Some(&mut self.cpu),
+            "memoria" => Some(&mut self.memoria),
+            "red" => Some(&mut self.red),
+            "disco" => Some(&mut self.disco),
+            "procesos" => Some(&mut self.procesos),
+            "cgroups" => Some(&mut self.cgroups),
+            "inventario" => Some(&mut self.inventario),
+            _ => None,
+        }
+    }
+
+    /// Intervalo de la fuente integrada `nombre`; `None` si está desactivada.
+    pub fn campo(&self, nombre: &str) -> Option<Duracion> {
+        match nombre {
+            "cpu" => self.cpu,
+            "memoria" => self.memoria,
+            "red" => self.red,
+            "disco" => self.disco,
+            "procesos" => self.procesos,
+            "cgroups" => self.cgroups,
+            "inventario" => self.inventario,
+            _ => None,
+        }
+    }
+
+    /// Fuentes activas con su intervalo, en el orden de `INTEGRADOS`.
+    pub fn activos(&self) -> Vec<(&'static str, Duration)> {
+        INTEGRADOS.iter().filter_map(|(nombre, _)| Some((*nombre, self.campo(nombre)?.0))).collect()
+    }
+}
+
+/// Política de retención tal como se escribe en el archivo (ver `Retencion`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RetencionArchivo")]
+pub struct ConfigRetencion {
+    pub completa: Duracion,
+    pub procesos: Duracion,
+    pub niveles: Vec<ConfigNivel>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigNivel {
+    pub resolucion: Duracion,
+    pub duracion: Duracion,
+}
+
+impl Default for ConfigRetencion {
+    fn default() -> Self {
+        let retencion = Retencion::default();
+        ConfigRetencion {
+            completa: Duracion(retencion.completa),
+            procesos: Duracion(retencion.procesos),
+            niveles: retencion
+                .niveles
+                .iter()
+                .map(|&(resolucion, duracion)| ConfigNivel { resolucion: Duracion(resolucion), duracion: Duracion(duracion) })
+                .collect(),
+        }
+    }
+}
+
+// Forma sin validar de `ConfigRetencion`: las reglas que relacionan campos se
+// comprueban en `try_from`, y el error queda en la línea de la tabla.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RetencionArchivo {
+    #[serde(default = "por_defecto_completa")]
+    completa: Duracion,
+    #[serde(default = "por_defecto_procesos")]
+    procesos: Duracion,
+    #[serde(default = "por_defecto_niveles")]
+    niveles: Vec<ConfigNivel>,
+}
+
+fn por_defecto_completa() -> Duracion {
+    ConfigRetencion::default().completa
+}
+
+fn por_defecto_procesos() -> Duracion {
+    ConfigRetencion::default().procesos
+}
+
+fn por_defecto_niveles() -> Vec<ConfigNivel> {
+    ConfigRetencion::default().niveles
+}
+
+impl TryFrom<RetencionArchivo> for ConfigRetencion {
+    type Error = String;
+
+    fn try_from(archivo: RetencionArchivo) -> Result<Self, String> {
+        let retencion = ConfigRetencion { completa: archivo.completa, procesos: archivo.procesos, niveles: archivo.niveles };
+        retencion.validar()?;
+        Ok(retencion)
+    }
+}
+
+impl ConfigRetencion {
+    /// Comprueba las reglas que relacionan campos (las mismas que al leer el archivo).
+    pub fn validar(&self) -> Result<(), String> {
+        if self.procesos > self.completa {
+            return Err(format!(
+                "retencion.procesos ({}) no puede ser mayor que retencion.completa ({})",
+                self.procesos, self.completa
+            ));
+        }
+        for nivel in &self.niveles {
+            if nivel.resolucion >= nivel.duracion {
+                return Err(format!(
+                    "en cada nivel la resolución ({}) debe ser menor que la duración ({})",
+                    nivel.resolucion, nivel.duracion
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn retencion(&self) -> Retencion {
+        Retencion {
+            completa: self.completa.0,
+            procesos: self.procesos.0,
+            niveles: self.niveles.iter().map(|n| (n.resolucion.0, n.duracion.0)).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigAlmacen {
+    pub activo: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directorio: Option<PathBuf>,
+    pub retencion: Duracion,
+}
+
+impl Default for ConfigAlmacen {
+    fn default() -> Self {
+        ConfigAlmacen { activo: true, directorio: None, retencion: Duracion(almacen::RETENCION_POR_DEFECTO) }
+    }
+}
+
+impl ConfigAlmacen {
+    /// Directorio efectivo del almacén; `None` si está desactivado.
+    pub fn directorio(&self) -> Option<PathBuf> {
+        if !self.activo {
+            return None;
+        }
+        self.directorio.clone().or_else(almacen::directorio_por_defecto)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Interfaz {
+    pub unidades: Unidades,
+}
+
+/// Unidades en que la GUI muestra los tamaños de memoria.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unidades {
+    #[default]
+    #[serde(rename = "MB")]
+    Mb,
+    #[serde(rename = "GiB")]
+    Gib,
+}
+
+impl Unidades {
+    pub const TODAS: [Unidades; 2] = [Unidades::Mb, Unidades::Gib];
+
+    pub fn nombre(self) -> &'static str {
+        match self {
+            Unidades::Mb => "MB",
+            Unidades::Gib => "GiB",
+        }
+    }
+
+    /// Valor sin unidad de un tamaño que los instrumentos reportan en MB (de 1024 KiB).
+    pub fn valor(self, mb: u64) -> String {
+        match self {
+            Unidades::Mb => mb.to_string(),
+            Unidades::Gib => format!("{:.2}", mb as f64 / 1024.0),
+        }
+    }
+
+    /// Como `valor`, con la unidad.
+    pub fn formatear(self, mb: u64) -> String {
+        format!("{} {}", self.valor(mb), self.nombre())
+    }
+}
+
+/// Porcentaje entre 0 y 100.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize)]
+#[serde(transparent)]
+pub struct Porcentaje(pub f64);
+
+impl<'de> Deserialize<'de> for Porcentaje {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let valor = f64::deserialize(deserializer)?;
+        if !(0.0..=100.0).contains(&valor) {
+            return Err(serde::de::Error::custom(format!("{} no es un porcentaje entre 0 y 100", valor)));
+        }
+        Ok(Porcentaje(valor))
+    }
+}
+
+/// Duración mayor que cero; en el archivo, `"500ms"`, `"5s"`, `"10m"`, `"1h"` o `"30d"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duracion(pub Duration);
+
+/// Unidades de `Duracion`, de la mayor a la menor, en milisegundos.
+const UNIDADES: [(&str, u64); 5] = [("d", 86_400_000), ("h", 3_600_000), ("m", 60_000), ("s", 1000), ("ms", 1)];
+
+impl FromStr for Duracion {
+    type Err = String;
+
+    fn from_str(texto: &str) -> Result<Self, String> {
+        let texto = texto.trim();
+        let corte = texto.find(|c: char| !c.is_ascii_digit()).unwrap_or(texto.len());
+        let (numero, unidad) = texto.split_at(corte);
+        let formato = || format!("'{}' no es una duración (se espera algo como \"5s\", \"10m\", \"1h\" o \"30d\")", texto);
+        let numero: u64 = numero.parse().map_err(|_| formato())?;
+        let (_, factor) = UNIDADES.iter().find(|(nombre, _)| *nombre == unidad.trim()).ok_or_else(formato)?;
+        let ms = numero.checked_mul(*factor).ok_or_else(formato)?;
+        if ms == 0 {
+            return Err(format!("'{}': la duración debe ser mayor que cero", texto));
+        }
+        Ok(Duracion(Duration::from_millis(ms)))
+    }
+}
+
+impl fmt::Display for Duracion {
+    /// Con la mayor unidad que la expresa sin decimales.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = self.0.as_millis() as u64;
+        let (nombre, factor) = UNIDADES.iter().find(|(_, factor)| ms.is_multiple_of(*factor)).unwrap_or(&("ms", 1));
+        write!(f, "{}{}", ms / factor, nombre)
+    }
+}
+
+impl Serialize for Duracion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Duracion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Zona horaria de la base de datos IANA, usada para el `id` de los snapshots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zona(pub Tz);
+
+impl Default for Zona {
+    fn default() -> Self {
+        Zona(chrono_tz::America::Mexico_City)
+    }
+}
+
+impl Serialize for Zona {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.name())
+    }
+}
+
+impl FromStr for Zona {
+    type Err = String;
+
+    fn from_str(nombre: &str) -> Result<Self, String> {
+        nombre
+            .trim()
+            .parse()
+            .map(Zona)
+            .map_err(|_| format!("zona horaria desconocida: '{}' (p. ej. \"America/Mexico_City\" o \"UTC\")", nombre.trim()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Zona {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Error al leer la configuración.
+#[derive(Debug)]
+pub enum ErrorConfig {
+    /// No se pudo leer el archivo.
+    Lectura { ruta: PathBuf, fuente: io::Error },
+    /// El archivo no es TOML válido o no cumple el esquema.
+    Formato { ruta: Option<PathBuf>, linea: Option<usize>, columna: Option<usize>, mensaje: String },
+}
+
+impl fmt::Display for ErrorConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorConfig::Lectura { ruta, fuente } => write!(f, "{}: {}", ruta.display(), fuente),
+            ErrorConfig::Formato { ruta, linea, columna, mensaje } => {
+                if let Some(ruta) = ruta {
+                    write!(f, "{}:", ruta.display())?;
+                }
+                if let Some(linea) = linea {
+                    write!(f, "{}:", linea)?;
+                }
+                if let Some(columna) = columna {
+                    write!(f, "{}:", columna)?;
+                }
+                write!(f, " {}", mensaje)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ErrorConfig {}
+
+impl Configuracion {
+    /// Interpreta el texto de un archivo de configuración.
+    pub fn desde_texto(texto: &str) -> Result<Self, ErrorConfig> {
+        toml::from_str(texto).map_err(|e| {
+            // línea y columna desde 1, a partir del byte donde empieza el error
+            let posicion = e.span().map(|rango| {
+                let antes = &texto[..rango.start.min(texto.len())];
+                let linea = antes.matches('\n').count() + 1;
+                let columna = antes.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+                (linea, columna)
+            });
+            ErrorConfig::Formato {
+                ruta: None,
+                linea: posicion.map(|p| p.0),
+                columna: posicion.map(|p| p.1),
+                mensaje: e.message().trim_end().to_string(),
+            }
+        })
+    }
+
+    /// Texto TOML de la configuración completa, con un encabezado comentado.
+    pub fn a_texto(&self) -> String {
+        let cuerpo = toml::to_string_pretty(self).expect("la configuración siempre se puede serializar");
+        format!("# Configuración de sistema_defensa; los campos que falten toman su valor por defecto.\n\n{}", cuerpo)
+    }
+
+    /// Escribe la configuración en `ruta`, creando el directorio si hace falta.
+    ///
+    /// Se escribe en un archivo temporal que después se renombra, para que
+    /// quien vigile el archivo nunca lea uno a medio escribir.
+    pub fn guardar(&self, ruta: &Path) -> io::Result<()> {
+        if let Some(padre) = ruta.parent() {
+            fs::create_dir_all(padre)?;
+        }
+        let temporal = ruta.with_extension("toml.tmp");
+        fs::write(&temporal, self.a_texto())?;
+        fs::rename(&temporal, ruta)
+    }
+
+    /// Lee y valida el archivo `ruta`.
+    pub fn cargar(ruta: &Path) -> Result<Self, ErrorConfig> {
+        let texto = fs::read_to_string(ruta).map_err(|fuente| ErrorConfig::Lectura { ruta: ruta.to_path_buf(), fuente })?;
+        Self::desde_texto(&texto).map_err(|error| match error {
+            ErrorConfig::Formato { linea, columna, mensaje, .. } => {
+                ErrorConfig::Formato { ruta: Some(ruta.to_path_buf()), linea, columna, mensaje }
+            }
+            otro => otro,
+        })
+    }
+
+    /// Carga `explicita` si se indicó (tiene que existir); si no, el primer
+    /// archivo que exista de `rutas_busqueda`, o los valores por defecto.
+    ///
+    /// # Retorno
+    /// La configuración y la ruta de la que se leyó, si hubo una.
+    pub fn buscar(explicita: Option<&Path>) -> Result<(Self, Option<PathBuf>), ErrorConfig> {
+        Self::buscar_en(explicita, rutas_busqueda())
+    }
+
+    // `buscar` con las rutas candidatas dadas, en orden
+    fn buscar_en(explicita: Option<&Path>, rutas: Vec<PathBuf>) -> Result<(Self, Option<PathBuf>), ErrorConfig> {
+        if let Some(ruta) = explicita {
+            return Ok((Self::cargar(ruta)?, Some(ruta.to_path_buf())));
+        }
+        match rutas.into_iter().find(|ruta| ruta.is_file()) {
+            Some(ruta) => Ok((Self::cargar(&ruta)?, Some(ruta))),
+            None => Ok((Self::default(), None)),
+        }
+    }
+
+    pub fn umbrales(&self) -> Umbrales {
+        Umbrales { cpu: self.alertas.cpu.0, memoria: self.alertas.memoria.0 }
+    }
+
+    pub fn zona(&self) -> Tz {
+        self.monitoreo.zona_horaria.0
+    }
+
+    /// Registro con las fuentes activas y sus intervalos.
+    pub fn registro(&self) -> Registro {
+        Registro::integrados(&self.monitoreo.recolectores.activos())
+    }
+}
+
+/// Archivo de configuración del usuario: `$XDG_CONFIG_HOME/sistema_defensa/config.toml`,
+/// o `~/.config/sistema_defensa/config.toml` si la variable no está definida.
+pub fn ruta_usuario() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("sistema_defensa").join(NOMBRE_ARCHIVO))
+}
+
+/// Rutas en las que se busca la configuración cuando no se indica una, en orden.
+pub fn rutas_busqueda() -> Vec<PathBuf> {
+    let mut rutas: Vec<PathBuf> = ruta_usuario().into_iter().collect();
+    rutas.push(Path::new("/etc/sistema_defensa").join(NOMBRE_ARCHIVO));
+    rutas
+}
+
+/// Vigila un archivo de configuración por su fecha de modificación.
+///
+/// No necesita hilo propio: quien lo usa llama a `revisar` en su bucle, y el
+/// archivo se consulta como mucho cada `INTERVALO_REVISION`.
+pub struct Vigilante {
+    ruta: PathBuf,
+    modificado: Option<SystemTime>,
+    revisado: Instant,
+}
+
+impl Vigilante {
+    /// Empieza a vigilar `ruta`; el contenido actual cuenta como ya leído.
+    /// El archivo puede no existir todavía.
+    pub fn new(ruta: PathBuf) -> Self {
+        let modificado = Self::fecha(&ruta);
+        Vigilante { ruta, modificado, revisado: Instant::now() }
+    }
+
+    pub fn get_ruta(&self) -> &Path {
+        &self.ruta
+    }
+
+    /// Toma el contenido actual del archivo como ya leído (p. ej. tras guardarlo).
+    pub fn sincronizar(&mut self) {
+        self.modificado = Self::fecha(&self.ruta);
+    }
+
+    /// # Retorno
+    /// `None` si el archivo no cambió desde la última revisión; si cambió, el
+    /// resultado de volver a leerlo. Que el archivo desaparezca cuenta como error.
+    pub fn revisar(&mut self) -> Option<Result<Configuracion, ErrorConfig>> {
+        if self.revisado.elapsed() < INTERVALO_REVISION {
+            return None;
+        }
+        self.revisado = Instant::now();
+        let modificado = Self::fecha(&self.ruta);
+        if modificado == self.modificado {
+            return None;
+        }
+        self.modificado = modificado;
+        Some(Configuracion::cargar(&self.ruta))
+    }
+
+    fn fecha(ruta: &Path) -> Option<SystemTime> {
+        fs::metadata(ruta).and_then(|m| m.modified()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duracion(texto: &str) -> Result<Duration, String> {
+        texto.parse::<Duracion>().map(|d| d.0)
+    }
+
+    // Directorio temporal propio de cada prueba, vacío al empezar
+    fn directorio_prueba(nombre: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sistema_defensa-config-{}-{}", nombre, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn duracion_acepta_cada_unidad() {
+        assert_eq!(duracion("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(duracion("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(duracion("10m"), Ok(Duration::from_secs(600)));
+        assert_eq!(duracion("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(duracion("30d"), Ok(Duration::from_secs(30 * 86_400)));
+        assert_eq!(duracion(" 2h "), Ok(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn duracion_rechaza_textos_invalidos() {
+        for texto in ["", "5", "s", "5x", "1.5h", "-1s", "5 sec", "99999999999999999999s"] {
+            assert!(duracion(texto).is_err(), "se aceptó {:?}", texto);
+        }
+        // no cabe en u64 al pasar a milisegundos
+        assert!(duracion(&format!("{}d", u64::MAX / 1000)).is_err());
+    }
+
+    #[test]
+    fn duracion_rechaza_cero() {
+        let error = duracion("0s").unwrap_err();
+        assert!(error.contains("mayor que cero"), "{}", error);
+    }
+
+    #[test]
+    fn duracion_se_escribe_con_la_mayor_unidad_exacta() {
+        assert_eq!(Duracion(Duration::from_secs(7200)).to_string(), "2h");
+        assert_eq!(Duracion(Duration::from_secs(90 * 60)).to_string(), "90m");
+        assert_eq!(Duracion(Duration::from_millis(1500)).to_string(), "1500ms");
+        for texto in ["500ms", "5s", "10m", "1h", "30d"] {
+            assert_eq!(texto.parse::<Duracion>().unwrap().to_string(), texto);
+        }
+    }
+
+    #[test]
+    fn campo_de_fuente_desconocida_no_existe() {
+        let mut recolectores = Recolectores::default();
+        assert!(recolectores.campo_mut("gpu").is_none());
+        assert_eq!(recolectores.campo("gpu"), None);
+        *recolectores.campo_mut("disco").unwrap() = None;
+        assert_eq!(recolectores.campo("disco"), None);
+    }
+
+    #[test]
+    fn texto_vacio_da_la_configuracion_por_defecto() {
+        assert_eq!(Configuracion::desde_texto("").unwrap(), Configuracion::default());
+    }
+
+    #[test]
+    fn desde_texto_lee_las_secciones() {
+        let texto = "[alertas]\ncpu = 90.0\n\n[monitoreo.recolectores]\ncpu = \"2s\"\ndisco = \"1m\"\n";
+        let configuracion = Configuracion::desde_texto(texto).unwrap();
+        assert_eq!(configuracion.alertas.cpu, Porcentaje(90.0));
+        assert_eq!(configuracion.alertas.memoria, Alertas::default().memoria);
+        // las fuentes que no aparecen en la tabla quedan desactivadas
+        assert_eq!(
+            configuracion.monitoreo.recolectores.activos(),
+            vec![("cpu", Duration::from_secs(2)), ("disco", Duration::from_secs(60))]
+        );
+    }
+
+    #[test]
+    fn desde_texto_vuelve_a_leer_lo_que_escribe_a_texto() {
+        let mut configuracion = Configuracion::default();
+        configuracion.alertas.cpu = Porcentaje(55.0);
+        configuracion.retencion.completa = Duracion(Duration::from_secs(2 * 3600));
+        assert_eq!(Configuracion::desde_texto(&configuracion.a_texto()).unwrap(), configuracion);
+    }
+
+    #[test]
+    fn error_de_formato_indica_linea_y_columna() {
+        let texto = "[alertas]\ncpu = 80.0\nmemria = 70.0\n";
+        match Configuracion::desde_texto(texto) {
+            Err(ErrorConfig::Formato { linea, columna, mensaje, .. }) => {
+                assert_eq!((linea, columna), (Some(3), Some(1)));
+                assert!(mensaje.contains("memria"), "{}", mensaje);
+            }
+            otro => panic!("se esperaba un error de formato: {:?}", otro),
+        }
+
+        let texto = "[monitoreo.recolectores]\ncpu = \"5x\"\n";
+        match Configuracion::desde_texto(texto) {
+            Err(ErrorConfig::Formato { linea, columna, .. }) => assert_eq!((linea, columna), (Some(2), Some(7))),
+            otro => panic!("se esperaba un error de formato: {:?}", otro),
+        }
+    }
+
+    #[test]
+    fn retencion_por_defecto_es_valida() {
+        assert_eq!(ConfigRetencion::default().validar(), Ok(()));
+    }
+
+    #[test]
+    fn retencion_rechaza_procesos_mayor_que_completa() {
+        let retencion = ConfigRetencion {
+            completa: Duracion(Duration::from_secs(600)),
+            procesos: Duracion(Duration::from_secs(3600)),
+            ..ConfigRetencion::default()
+        };
+        assert!(retencion.validar().unwrap_err().contains("retencion.procesos"));
+
+        // al leer el archivo el error queda en la línea de la tabla
+        let texto = "[retencion]\ncompleta = \"10m\"\nprocesos = \"1h\"\n";
+        match Configuracion::desde_texto(texto) {
+            Err(ErrorConfig::Formato { linea, mensaje, .. }) => {
+                assert_eq!(linea, Some(1));
+                assert!(mensaje.contains("retencion.procesos"), "{}", mensaje);
+            }
+            otro => panic!("se esperaba un error de formato: {:?}", otro),
+        }
+    }
+
+    #[test]
+    fn retencion_rechaza_niveles_con_resolucion_no_menor_que_la_duracion() {
+        let nivel = |resolucion: u64, duracion: u64| ConfigNivel {
+            resolucion: Duracion(Duration::from_secs(resolucion)),
+            duracion: Duracion(Duration::from_secs(duracion)),
+        };
+        let mut retencion = ConfigRetencion { niveles: vec![nivel(60, 3600), nivel(3600, 3600)], ..ConfigRetencion::default() };
+        assert!(retencion.validar().is_err());
+        retencion.niveles[1] = nivel(7200, 3600);
+        assert!(retencion.validar().is_err());
+        retencion.niveles[1] = nivel(900, 86_400);
+        assert_eq!(retencion.validar(), Ok(()));
+    }
+
+    #[test]
+    fn buscar_usa_la_primera_ruta_que_existe() {
+        let dir = directorio_prueba("orden");
+        let (primera, segunda, tercera) = (dir.join("no_existe.toml"), dir.join("segunda.toml"), dir.join("tercera.toml"));
+        fs::write(&segunda, "[alertas]\ncpu = 20.0\n").unwrap();
+        fs::write(&tercera, "[alertas]\ncpu = 30.0\n").unwrap();
+
+        let (configuracion, ruta) = Configuracion::buscar_en(None, vec![primera, segunda.clone(), tercera.clone()]).unwrap();
+        assert_eq!(ruta, Some(segunda));
+        assert_eq!(configuracion.alertas.cpu, Porcentaje(20.0));
+
+        // la ruta explícita gana sobre las candidatas
+        let (configuracion, ruta) = Configuracion::buscar_en(Some(&tercera), vec![dir.join("segunda.toml")]).unwrap();
+        assert_eq!(ruta, Some(tercera));
+        assert_eq!(configuracion.alertas.cpu, Porcentaje(30.0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn buscar_sin_archivos_da_la_configuracion_por_defecto() {
+        let dir = directorio_prueba("vacio");
+        let (configuracion, ruta) = Configuracion::buscar_en(None, vec![dir.join("a.toml"), dir.join("b.toml")]).unwrap();
+        assert_eq!((configuracion, ruta), (Configuracion::default(), None));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn buscar_con_ruta_explicita_inexistente_es_error() {
+        let dir = directorio_prueba("explicita");
+        fs::write(dir.join("candidata.toml"), "").unwrap();
+        let resultado = Configuracion::buscar_en(Some(&dir.join("falta.toml")), vec![dir.join("candidata.toml")]);
+        assert!(matches!(resultado, Err(ErrorConfig::Lectura { .. })));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn la_configuracion_del_sistema_se_busca_al_final() {
+        let rutas = rutas_busqueda();
+        assert_eq!(rutas.last(), Some(&PathBuf::from("/etc/sistema_defensa/config.toml")));
+        assert_eq!(rutas.first(), ruta_usuario().as_ref().or(rutas.last()));
+    }
+}
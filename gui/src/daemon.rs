@@ -0,0 +1,440 @@
+//! Modo daemon: el monitoreo corre sin ventana y atiende clientes por un
+//! socket Unix local.
+//!
+//! El daemon recolecta con `Metrics` (y por lo tanto escribe en el almacén
+//! persistente), evalúa las alertas en cada snapshot y anota en la salida de
+//! errores las que se activan o se resuelven.
+//!
+//! Protocolo: líneas JSON. El cliente envía una `Peticion` por línea y el
+//! daemon responde con una `Respuesta` por línea, en el mismo orden. Tras
+//! `Suscribir` la conexión queda solo para recibir: el daemon envía un
+//! `Respuesta::Snapshot` por cada snapshot nuevo hasta que el cliente cierra.
+//!
+//! ```text
+//! → {"comando":"snapshot"}
+//! ← {"tipo":"snapshot","datos":{"id":...,"marca":...,"lecturas":{...},...}}
+//! → {"comando":"historial","serie":"cpu.uso","desde":1792300000000,"hasta":1792303600000}
+//! ← {"tipo":"historial","datos":[[1792300001000,12.5],...]}
+//! ```
+//!
+//! Opcionalmente sirve además la API HTTP de `http` sobre el mismo histórico
+//! y envía cada snapshot a las salidas de `salidas` (InfluxDB, Graphite).
+//! Si el archivo de configuración cambia, el daemon lo vuelve a leer y aplica
+//! umbrales, fuentes, zona horaria y retención sin reiniciar el monitoreo.
+//!
+//! - `ejecutar`: Corre el daemon hasta recibir `Detener`.
+//! - `OpcionesDaemon`: Configuración (umbrales, fuentes, retención), API HTTP (dirección, TLS y tokens) y salidas.
+//! - `Cliente`: Conexión de un cliente (GUI, TUI o CLI) al daemon.
+//! - `ruta_socket_por_defecto`: `$XDG_RUNTIME_DIR/sistema_defensa.sock`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use crate::alertas::{self, Alerta, TipoAlerta};
+use crate::almacen;
+use crate::config::{Configuracion, Vigilante};
+use crate::http::ServidorHttp;
+use crate::metrics::{Metric, Metrics};
+use crate::salidas::{self, Destino, Formato, Salida};
+use crate::seguridad::{OpcionesSeguridad, Seguridad};
+
+/// Cada cuánto el daemon revisa conexiones nuevas y snapshots publicados.
+const ESPERA: Duration = Duration::from_millis(200);
+/// Un suscriptor que no lee en este tiempo se desconecta.
+const TIEMPO_ESCRITURA: Duration = Duration::from_secs(2);
+/// Snapshots pendientes por suscriptor; si se llena, el suscriptor se desconecta.
+const COLA_SUSCRIPTOR: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "comando", rename_all = "snake_case")]
+pub enum Peticion {
+    Estado,
+    Snapshot,
+    /// Puntos `(marca en ms, valor)` de una serie de `Metric::valores`.
+    Historial { serie: String, desde: i64, hasta: i64 },
+    /// Puntos de resolución completa en memoria de todas las series, con `desde <= marca <= hasta`.
+    Puntos { desde: i64, hasta: i64 },
+    Series,
+    Alertas,
+    Suscribir,
+    Detener,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "tipo", content = "datos", rename_all = "snake_case")]
+pub enum Respuesta {
+    Estado(EstadoDaemon),
+    Snapshot(Option<Metric>),
+    Historial(Vec<(i64, f64)>),
+    Puntos(BTreeMap<String, Vec<(i64, f64)>>),
+    Series(Vec<String>),
+    Alertas(Vec<Alerta>),
+    Suscrito,
+    Detenido,
+    Error(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstadoDaemon {
+    pub pid: u32,
+    pub inicio: i64, // ms desde la época UNIX
+    pub snapshots: usize,
+    pub suscriptores: usize,
+    pub almacen: Option<PathBuf>,
+}
+
+/// Opciones del daemon.
+///
+/// - `configuracion`: Umbrales de las alertas que se anotan y se sirven, fuentes
+///   con sus intervalos, zona horaria, retención y almacén (ver `config`).
+/// - `ruta_configuracion`: Archivo que se vigila para recargar `configuracion`; `None` no vigila.
+/// - `http`: Dirección de la API HTTP (por ejemplo `127.0.0.1:9100`); `None` la desactiva.
+/// - `seguridad`: TLS y tokens de la API HTTP.
+/// - `salidas`: Destinos a los que se envía cada snapshot y en qué formato.
+#[derive(Debug, Clone, Default)]
+pub struct OpcionesDaemon {
+    pub configuracion: Configuracion,
+    pub ruta_configuracion: Option<PathBuf>,
+    pub http: Option<String>,
+    pub seguridad: OpcionesSeguridad,
+    pub salidas: Vec<(Formato, Destino)>,
+}
+
+// Estado compartido entre el bucle principal y los hilos de los clientes
+struct Compartido {
+    metrics: Arc<Mutex<Metrics>>,
+    alertas: Mutex<Vec<Alerta>>,
+    // cola de cada suscriptor; su propio hilo la vacía en el socket
+    suscriptores: Mutex<Vec<SyncSender<Arc<Vec<u8>>>>>,
+    detener: AtomicBool,
+    inicio: i64,
+}
+
+/// Ruta por defecto del socket: `$XDG_RUNTIME_DIR/sistema_defensa.sock`, o
+/// `daemon.sock` dentro de `almacen::directorio_datos` si no hay directorio de ejecución.
+pub fn ruta_socket_por_defecto() -> Option<PathBuf> {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|v| !v.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir).join("sistema_defensa.sock")),
+        None => Some(almacen::directorio_datos()?.join("daemon.sock")),
+    }
+}
+
+/// Corre el daemon en `ruta` hasta que un cliente envíe `Detener`.
+///
+/// Si el socket existe pero nadie lo atiende (un daemon anterior terminó sin
+/// limpiar), se reemplaza; si hay un daemon vivo, devuelve `AddrInUse`.
+pub fn ejecutar(ruta: &Path, opciones: &OpcionesDaemon) -> io::Result<()> {
+    if ruta.exists() {
+        if UnixStream::connect(ruta).is_ok() {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, format!("ya hay un daemon en {}", ruta.display())));
+        }
+        fs::remove_file(ruta)?;
+    }
+    if let Some(padre) = ruta.parent() {
+        fs::DirBuilder::new().recursive(true).mode(0o700).create(padre)?;
+    }
+    let listener = escuchar(ruta)?;
+    listener.set_nonblocking(true)?;
+
+    let mut metrics = Metrics::con_configuracion(&opciones.configuracion);
+    metrics.start_monitoring_con(opciones.configuracion.registro());
+    let metrics = Arc::new(Mutex::new(metrics));
+    let umbrales = Arc::new(Mutex::new(opciones.configuracion.umbrales()));
+    let mut configuracion = opciones.configuracion.clone();
+    let mut vigilante = opciones.ruta_configuracion.clone().map(Vigilante::new);
+    // se detiene al salir de esta función
+    let _http = match &opciones.http {
+        Some(direccion) => {
+            let seguridad = Seguridad::cargar(&opciones.seguridad)?;
+            let esquema = if seguridad.get_tls() { "https" } else { "http" };
+            let sin_tokens = !seguridad.get_tokens().activos();
+            let servidor = ServidorHttp::iniciar(direccion, Arc::clone(&metrics), Arc::clone(&umbrales), seguridad)?;
+            if sin_tokens {
+                eprintln!("Aviso: la API HTTP no pide token (sin archivo de tokens); las acciones quedan deshabilitadas");
+            }
+            eprintln!("API HTTP en {}://{}", esquema, servidor.get_direccion());
+            Some(servidor)
+        }
+        None => None,
+    };
+    // al soltar el emisor el hilo de salidas hace un último envío y termina
+    let envio = (!opciones.salidas.is_empty()).then(|| {
+        salidas::iniciar(opciones.salidas.iter().map(|(formato, destino)| Salida::new(*formato, destino.clone())).collect())
+    });
+    let compartido = Arc::new(Compartido {
+        metrics,
+        alertas: Mutex::new(Vec::new()),
+        suscriptores: Mutex::new(Vec::new()),
+        detener: AtomicBool::new(false),
+        inicio: Utc::now().timestamp_millis(),
+    });
+    eprintln!("Daemon escuchando en {}", ruta.display());
+
+    let mut activas: BTreeSet<String> = BTreeSet::new();
+    let resultado = loop {
+        if compartido.detener.load(Ordering::Relaxed) {
+            break Ok(());
+        }
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let compartido = Arc::clone(&compartido);
+                    thread::spawn(move || atender(stream, &compartido));
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => eprintln!("Error al aceptar una conexión: {}", e),
+            }
+        }
+        if let Some(vigilante) = &mut vigilante {
+            match vigilante.revisar() {
+                Some(Ok(nueva)) => {
+                    if let Ok(mut metrics) = compartido.metrics.lock() {
+                        metrics.aplicar_configuracion(&configuracion, &nueva);
+                    }
+                    *umbrales.lock().unwrap() = nueva.umbrales();
+                    configuracion = nueva;
+                    eprintln!("Configuración recargada de {}", vigilante.get_ruta().display());
+                }
+                Some(Err(e)) => eprintln!("Configuración inválida, se mantiene la anterior: {}", e),
+                None => {}
+            }
+        }
+        let nuevo = {
+            let Ok(mut metrics) = compartido.metrics.lock() else { break Err(io::Error::other("estado envenenado")) };
+            if metrics.recibir() { metrics.latest().cloned() } else { None }
+        };
+        if let Some(metric) = nuevo {
+            let alertas = alertas::evaluar(&metric, &umbrales.lock().unwrap());
+            anotar_alertas(&metric, &alertas, &mut activas);
+            if let Ok(mut guardadas) = compartido.alertas.lock() {
+                *guardadas = alertas;
+            }
+            difundir(&compartido, &metric);
+            if let Some(envio) = &envio {
+                let _ = envio.send(metric);
+            }
+        }
+        thread::sleep(ESPERA);
+    };
+
+    if let Ok(mut metrics) = compartido.metrics.lock() {
+        metrics.stop_monitoring();
+    }
+    let _ = fs::remove_file(ruta);
+    resultado
+}
+
+// Crea el socket en `ruta` de modo que solo el usuario que corre el daemon pueda
+// conectarse: se enlaza dentro de un directorio privado (0700), se restringe a
+// 0600 y recién entonces se mueve a `ruta`, así nadie alcanza a conectarse antes
+fn escuchar(ruta: &Path) -> io::Result<UnixListener> {
+    let padre = ruta.parent().unwrap_or(Path::new("."));
+    let privado = padre.join(format!(".sistema_defensa.{}", std::process::id()));
+    let _ = fs::remove_dir_all(&privado); // resto de un daemon anterior con el mismo pid
+    fs::DirBuilder::new().mode(0o700).create(&privado)?;
+    let temporal = privado.join("sock");
+    let resultado = UnixListener::bind(&temporal).and_then(|listener| {
+        fs::set_permissions(&temporal, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&temporal, ruta)?;
+        Ok(listener)
+    });
+    let _ = fs::remove_file(&temporal);
+    let _ = fs::remove_dir(&privado);
+    resultado
+}
+
+// Anota las alertas que se activan y las que se resuelven desde el snapshot anterior
+fn anotar_alertas(metric: &Metric, alertas: &[Alerta], activas: &mut BTreeSet<String>) {
+    // CPU y memoria se identifican por tipo: su mensaje cambia con el valor
+    let clave = |a: &Alerta| match a.tipo {
+        TipoAlerta::ProcesoSospechoso => a.mensaje.clone(),
+        tipo => format!("{:?}", tipo),
+    };
+    let nuevas: BTreeSet<String> = alertas.iter().map(clave).collect();
+    for alerta in alertas.iter().filter(|a| !activas.contains(&clave(a))) {
+        eprintln!("[{}] {}", metric.id, alerta);
+    }
+    for resuelta in activas.difference(&nuevas) {
+        eprintln!("[{}] Alerta resuelta: {}", metric.id, resuelta);
+    }
+    *activas = nuevas;
+}
+
+// Encola el snapshot para cada suscriptor sin esperar a que se escriba; los que
+// se desconectaron o tienen la cola llena se descartan
+fn difundir(compartido: &Compartido, metric: &Metric) {
+    if compartido.suscriptores.lock().map_or(true, |s| s.is_empty()) {
+        return;
+    }
+    let Ok(mut linea) = serde_json::to_vec(&Respuesta::Snapshot(Some(metric.clone()))) else { return };
+    linea.push(b'\n');
+    let linea = Arc::new(linea);
+    let Ok(mut suscriptores) = compartido.suscriptores.lock() else { return };
+    suscriptores.retain(|cola| match cola.try_send(Arc::clone(&linea)) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) => false,
+    });
+}
+
+// Atiende las peticiones de un cliente hasta que cierra la conexión o se suscribe
+fn atender(stream: UnixStream, compartido: &Compartido) {
+    // el socket aceptado puede heredar el modo no bloqueante del listener
+    if stream.set_nonblocking(false).is_err() {
+        return;
+    }
+    let Ok(lectura) = stream.try_clone() else { return };
+    let mut escritor = stream;
+    for linea in BufReader::new(lectura).lines() {
+        let Ok(linea) = linea else { return };
+        if linea.trim().is_empty() {
+            continue;
+        }
+        let peticion = serde_json::from_str::<Peticion>(&linea);
+        let respuesta = match &peticion {
+            Ok(peticion) => responder(peticion, compartido),
+            Err(e) => Respuesta::Error(format!("petición inválida: {}", e)),
+        };
+        if escribir_linea(&mut escritor, &respuesta).is_err() {
+            return;
+        }
+        match peticion {
+            Ok(Peticion::Suscribir) => {
+                if escritor.set_write_timeout(Some(TIEMPO_ESCRITURA)).is_err() {
+                    return;
+                }
+                let (cola, pendientes) = mpsc::sync_channel::<Arc<Vec<u8>>>(COLA_SUSCRIPTOR);
+                match compartido.suscriptores.lock() {
+                    Ok(mut suscriptores) => suscriptores.push(cola),
+                    Err(_) => return,
+                }
+                // este hilo pasa a escribir los snapshots; termina cuando difundir
+                // descarta la cola o el cliente deja de leer
+                for linea in pendientes {
+                    if escritor.write_all(&linea).is_err() {
+                        return;
+                    }
+                }
+                return;
+            }
+            Ok(Peticion::Detener) => return,
+            _ => {}
+        }
+    }
+}
+
+fn responder(peticion: &Peticion, compartido: &Compartido) -> Respuesta {
+    let Ok(metrics) = compartido.metrics.lock() else {
+        return Respuesta::Error("estado envenenado".to_string());
+    };
+    match peticion {
+        Peticion::Estado => Respuesta::Estado(EstadoDaemon {
+            pid: std::process::id(),
+            inicio: compartido.inicio,
+            snapshots: metrics.vector.len(),
+            suscriptores: compartido.suscriptores.lock().map_or(0, |s| s.len()),
+            almacen: metrics.get_almacen().cloned(),
+        }),
+        Peticion::Snapshot => Respuesta::Snapshot(metrics.latest().cloned()),
+        Peticion::Historial { serie, desde, hasta } => {
+            let consulta = metrics.historial(serie, *desde, *hasta);
+            drop(metrics);
+            Respuesta::Historial(consulta.completar())
+        }
+        Peticion::Puntos { desde, hasta } => Respuesta::Puntos(metrics.puntos(*desde, *hasta)),
+        Peticion::Series => Respuesta::Series(metrics.nombres_series()),
+        Peticion::Alertas => Respuesta::Alertas(compartido.alertas.lock().map(|a| a.clone()).unwrap_or_default()),
+        Peticion::Suscribir => Respuesta::Suscrito,
+        Peticion::Detener => {
+            compartido.detener.store(true, Ordering::Relaxed);
+            Respuesta::Detenido
+        }
+    }
+}
+
+fn escribir_linea<T: Serialize>(escritor: &mut impl Write, valor: &T) -> io::Result<()> {
+    let mut linea = serde_json::to_vec(valor)?;
+    linea.push(b'\n');
+    escritor.write_all(&linea)?;
+    escritor.flush()
+}
+
+/// Conexión de un cliente al daemon.
+pub struct Cliente {
+    escritor: UnixStream,
+    lector: BufReader<UnixStream>,
+}
+
+impl Cliente {
+    pub fn conectar(ruta: &Path) -> io::Result<Self> {
+        let escritor = UnixStream::connect(ruta)?;
+        let lector = BufReader::new(escritor.try_clone()?);
+        Ok(Cliente { escritor, lector })
+    }
+
+    /// Envía una petición y espera su respuesta.
+    ///
+    /// `Respuesta::Error` se devuelve como `Ok`; los errores de E/S y una
+    /// conexión cerrada por el daemon son `Err`.
+    pub fn pedir(&mut self, peticion: &Peticion) -> io::Result<Respuesta> {
+        escribir_linea(&mut self.escritor, peticion)?;
+        self.leer_respuesta()
+    }
+
+    /// Último snapshot del daemon (`None` si todavía no recolectó ninguno).
+    pub fn snapshot(&mut self) -> io::Result<Option<Metric>> {
+        match self.pedir(&Peticion::Snapshot)? {
+            Respuesta::Snapshot(metric) => Ok(metric),
+            otra => Err(respuesta_inesperada(&otra)),
+        }
+    }
+
+    /// Puntos de resolución completa de todas las series que el daemon tiene en
+    /// memoria entre `desde` y `hasta` (ms), sin los snapshots completos.
+    pub fn puntos(&mut self, desde: i64, hasta: i64) -> io::Result<BTreeMap<String, Vec<(i64, f64)>>> {
+        match self.pedir(&Peticion::Puntos { desde, hasta })? {
+            Respuesta::Puntos(puntos) => Ok(puntos),
+            otra => Err(respuesta_inesperada(&otra)),
+        }
+    }
+
+    /// Se suscribe y devuelve los snapshots a medida que el daemon los publica.
+    ///
+    /// El iterador termina cuando el daemon cierra la conexión.
+    pub fn suscribir(mut self) -> io::Result<impl Iterator<Item = io::Result<Metric>>> {
+        match self.pedir(&Peticion::Suscribir)? {
+            Respuesta::Suscrito => {}
+            otra => return Err(respuesta_inesperada(&otra)),
+        }
+        Ok(std::iter::from_fn(move || match self.leer_respuesta() {
+            Ok(Respuesta::Snapshot(Some(metric))) => Some(Ok(metric)),
+            Ok(otra) => Some(Err(respuesta_inesperada(&otra))),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            Err(e) => Some(Err(e)),
+        }))
+    }
+
+    fn leer_respuesta(&mut self) -> io::Result<Respuesta> {
+        let mut linea = String::new();
+        if self.lector.read_line(&mut linea)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "el daemon cerró la conexión"));
+        }
+        serde_json::from_str(&linea).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+fn respuesta_inesperada(respuesta: &Respuesta) -> io::Error {
+    match respuesta {
+        Respuesta::Error(mensaje) => io::Error::other(mensaje.clone()),
+        otra => io::Error::new(io::ErrorKind::InvalidData, format!("respuesta inesperada: {:?}", otra)),
+    }
+}
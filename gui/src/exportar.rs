@@ -0,0 +1,214 @@
+//! Exportación e importación de snapshots `Metric`.
+//!
+//! - `Json`: Un arreglo JSON con los snapshots completos.
+//! - `Ndjson`: Un snapshot completo por línea.
+//! - `Csv`: Un archivo por familia de métricas dentro de un directorio. Cada
+//!   lectura se aplana: los objetos anidados se convierten en columnas
+//!   `campo.subcampo` y las listas de objetos (discos, interfaces, procesos...)
+//!   van a su propia familia `fuente.campo` con una fila por elemento. Una
+//!   lectura solo se escribe en el primer snapshot en que aparece.
+//!
+//! JSON y NDJSON se pueden volver a abrir con `importar` (y por lo tanto en
+//! modo reproducción); CSV es solo para análisis externo.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use serde_json::Value;
+use crate::almacen;
+use crate::grabacion;
+use crate::metrics::Metric;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Formato {
+    Json,
+    Ndjson,
+    Csv,
+}
+
+impl Formato {
+    pub const TODOS: [Formato; 3] = [Formato::Json, Formato::Ndjson, Formato::Csv];
+
+    pub fn nombre(&self) -> &'static str {
+        match self {
+            Formato::Json => "JSON",
+            Formato::Ndjson => "NDJSON",
+            Formato::Csv => "CSV",
+        }
+    }
+
+    /// Extensión del archivo; CSV se exporta a un directorio y no lleva extensión.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Formato::Json => ".json",
+            Formato::Ndjson => ".ndjson",
+            Formato::Csv => "",
+        }
+    }
+}
+
+/// Ruta por defecto para una exportación nueva en el formato indicado.
+pub fn ruta_nueva_exportacion(formato: Formato) -> PathBuf {
+    let nombre = format!("metricas-{}{}", Local::now().format("%Y%m%d-%H%M%S"), formato.extension());
+    almacen::directorio_datos()
+        .map(|dir| dir.join("exportaciones"))
+        .unwrap_or_default()
+        .join(nombre)
+}
+
+/// Exporta los snapshots en el formato indicado.
+///
+/// Para `Csv` la ruta es un directorio (se crea si no existe).
+pub fn exportar(metricas: &[&Metric], formato: Formato, ruta: &Path) -> io::Result<()> {
+    if formato == Formato::Csv {
+        return exportar_csv(metricas, ruta);
+    }
+    if let Some(padre) = ruta.parent() {
+        fs::create_dir_all(padre)?;
+    }
+    let mut escritor = BufWriter::new(File::create(ruta)?);
+    match formato {
+        Formato::Json => serde_json::to_writer_pretty(&mut escritor, metricas)?,
+        _ => {
+            for metric in metricas {
+                serde_json::to_writer(&mut escritor, metric)?;
+                escritor.write_all(b"\n")?;
+            }
+        }
+    }
+    escritor.flush()
+}
+
+/// Lee snapshots exportados en JSON (arreglo o un solo objeto) o NDJSON.
+///
+/// También acepta las grabaciones de `grabacion`, que son NDJSON.
+pub fn importar(ruta: &Path) -> io::Result<Vec<Metric>> {
+    let contenido = fs::read_to_string(ruta)?;
+    let invalido = |e: serde_json::Error| io::Error::new(io::ErrorKind::InvalidData, e);
+    if contenido.trim_start().starts_with('[') {
+        return serde_json::from_str(&contenido).map_err(invalido);
+    }
+    // un solo objeto en todo el archivo es la exportación del snapshot actual
+    match serde_json::from_str::<Metric>(&contenido) {
+        Ok(metric) => Ok(vec![metric]),
+        Err(_) => grabacion::leer_grabacion(ruta),
+    }
+}
+
+/// Filas de una familia: nombres de columna en orden de aparición y valores por fila.
+#[derive(Default)]
+struct Tabla {
+    columnas: Vec<String>,
+    filas: Vec<BTreeMap<String, String>>,
+}
+
+impl Tabla {
+    fn agregar(&mut self, fila: BTreeMap<String, String>) {
+        for columna in fila.keys() {
+            if !self.columnas.contains(columna) {
+                self.columnas.push(columna.clone());
+            }
+        }
+        self.filas.push(fila);
+    }
+}
+
+fn exportar_csv(metricas: &[&Metric], dir: &Path) -> io::Result<()> {
+    let mut familias: BTreeMap<String, Tabla> = BTreeMap::new();
+    let mut exportadas: BTreeMap<&str, i64> = BTreeMap::new();
+    for metric in metricas {
+        for (nombre, lectura) in &metric.lecturas {
+            // una lectura que no se volvió a muestrear se repite en los snapshots siguientes
+            let marca = metric.marcas.get(nombre).copied().unwrap_or(metric.marca);
+            if exportadas.insert(nombre, marca) == Some(marca) {
+                continue;
+            }
+            let valor = serde_json::to_value(lectura.as_ref())?;
+            // las lecturas se serializan como {"tipo": ..., "datos": ...}
+            let datos = valor.get("datos").cloned().unwrap_or(Value::Null);
+            let mut base = BTreeMap::new();
+            base.insert("marca".to_string(), metric.marca.to_string());
+            base.insert("id".to_string(), metric.id.clone());
+            aplanar_familia(&mut familias, nombre, base, &datos);
+        }
+    }
+
+    fs::create_dir_all(dir)?;
+    for (familia, tabla) in familias {
+        let mut escritor = BufWriter::new(File::create(dir.join(format!("{}.csv", familia)))?);
+        // marca e id siempre primero
+        let mut columnas = vec!["marca".to_string(), "id".to_string()];
+        columnas.extend(tabla.columnas.into_iter().filter(|c| c != "marca" && c != "id"));
+        let encabezado: Vec<String> = columnas.iter().map(|c| campo_csv(c)).collect();
+        writeln!(escritor, "{}", encabezado.join(","))?;
+        for fila in &tabla.filas {
+            let celdas: Vec<String> = columnas
+                .iter()
+                .map(|c| fila.get(c).map(|v| campo_csv(v)).unwrap_or_default())
+                .collect();
+            writeln!(escritor, "{}", celdas.join(","))?;
+        }
+        escritor.flush()?;
+    }
+    Ok(())
+}
+
+// Agrega una fila a la familia `nombre` y una familia hija por cada lista de objetos
+fn aplanar_familia(familias: &mut BTreeMap<String, Tabla>, nombre: &str, mut fila: BTreeMap<String, String>, datos: &Value) {
+    let mut hijas: Vec<(String, &Vec<Value>)> = Vec::new();
+    aplanar_valor(&mut fila, &mut hijas, "", datos);
+    let base: BTreeMap<String, String> = [("marca", &fila["marca"]), ("id", &fila["id"])]
+        .into_iter()
+        .map(|(c, v)| (c.to_string(), v.clone()))
+        .collect();
+    familias.entry(nombre.to_string()).or_default().agregar(fila);
+    for (campo, elementos) in hijas {
+        let familia = if campo.is_empty() { format!("{}.elementos", nombre) } else { format!("{}.{}", nombre, campo) };
+        for (indice, elemento) in elementos.iter().enumerate() {
+            let mut fila_hija = base.clone();
+            fila_hija.insert("indice".to_string(), indice.to_string());
+            aplanar_familia(familias, &familia, fila_hija, elemento);
+        }
+    }
+}
+
+fn aplanar_valor<'a>(fila: &mut BTreeMap<String, String>, hijas: &mut Vec<(String, &'a Vec<Value>)>, prefijo: &str, valor: &'a Value) {
+    let clave = |campo: &str| if prefijo.is_empty() { campo.to_string() } else { format!("{}.{}", prefijo, campo) };
+    let columna = if prefijo.is_empty() { "valor".to_string() } else { prefijo.to_string() };
+    match valor {
+        Value::Object(campos) => {
+            for (campo, v) in campos {
+                aplanar_valor(fila, hijas, &clave(campo), v);
+            }
+        }
+        Value::Array(elementos) if elementos.iter().any(Value::is_object) => {
+            hijas.push((prefijo.to_string(), elementos));
+        }
+        Value::Array(elementos) => {
+            let textos: Vec<String> = elementos.iter().map(texto).collect();
+            fila.insert(columna, textos.join(";"));
+        }
+        otro => {
+            fila.insert(columna, texto(otro));
+        }
+    }
+}
+
+fn texto(valor: &Value) -> String {
+    match valor {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        otro => otro.to_string(),
+    }
+}
+
+// Entrecomilla el campo si contiene separadores, comillas o saltos de línea
+fn campo_csv(valor: &str) -> String {
+    if valor.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", valor.replace('"', "\"\""))
+    } else {
+        valor.to_string()
+    }
+}
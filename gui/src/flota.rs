@@ -0,0 +1,362 @@
+//! Modo flota: agentes livianos envían sus snapshots por TCP a una instancia
+//! central, que guarda un `Metrics` por equipo para verlo con los paneles de
+//! siempre.
+//!
+//! Protocolo: líneas JSON de `MensajeAgente`. El agente se presenta con
+//! `Hola` y luego envía un `Snapshot` por cada snapshot nuevo; el servidor no
+//! responde. Como en las grabaciones, cada `Snapshot` solo trae las lecturas
+//! que cambiaron desde el anterior de la misma conexión (el primero va
+//! completo); `marcas` siempre va completo y el servidor completa el resto con
+//! el snapshot anterior. Si la conexión se corta, el agente reintenta con
+//! espera creciente (los snapshots tomados mientras tanto no se reenvían).
+//!
+//! La conexión puede ir por TLS y el servidor puede exigir en `Hola` un token
+//! con alcance de agente (ver `seguridad`).
+//!
+//! ```text
+//! → {"tipo":"hola","datos":{"equipo":"web-01","token":"5a4b3c..."}}
+//! → {"tipo":"snapshot","datos":{"id":...,"marca":...,"lecturas":{...},...}}
+//! ```
+//!
+//! - `ejecutar_agente`: Recolecta y envía al servidor hasta que se corte el proceso.
+//! - `ServidorFlota`: Acepta agentes y mantiene la lista de equipos.
+//! - `Equipo`: Un equipo conocido, con su histórico y su `Salud`.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use crate::config::{ConfigRetencion, Configuracion, Duracion};
+use crate::metrics::{Metric, Metrics, Retencion};
+use crate::seguridad::{self, Alcance, Flujo, OpcionesCliente, Seguridad};
+
+/// Cada cuánto el agente revisa snapshots nuevos y el servidor conexiones nuevas.
+const ESPERA: Duration = Duration::from_millis(200);
+/// Espera máxima del agente entre reintentos de conexión.
+const ESPERA_MAXIMA: Duration = Duration::from_secs(30);
+/// Tiempo máximo para escribir un snapshot; cada cuánto un lector revisa si debe terminar.
+const TIEMPO_CONEXION: Duration = Duration::from_secs(5);
+const TIEMPO_LECTURA: Duration = Duration::from_secs(1);
+/// Tamaño máximo de una línea (un snapshot con todos sus procesos).
+const TAMANO_MAXIMO_LINEA: u64 = 16 * 1024 * 1024;
+/// Sin snapshots por más de esto (ms), un equipo conectado se considera atrasado.
+const LIMITE_ATRASO: i64 = 10_000;
+/// Cuánto conserva el servidor los procesos y cgroups de cada equipo; el
+/// resto del histórico sigue la retención por defecto.
+pub const RETENCION_PROCESOS_FLOTA: Duration = Duration::from_secs(2 * 60);
+/// Silencio mínimo tras el cual el servidor corta a un agente; si el agente
+/// envía con menos frecuencia, el límite es tres veces su intervalo.
+const SILENCIO_MAXIMO: Duration = Duration::from_secs(30);
+
+/// Mensaje de un agente al servidor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "tipo", content = "datos", rename_all = "snake_case")]
+pub enum MensajeAgente {
+    Hola {
+        equipo: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    Snapshot(Metric),
+}
+
+/// Estado de un equipo según su conexión y su último snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Salud {
+    Activo,
+    Atrasado,
+    Desconectado,
+}
+
+impl Salud {
+    pub fn nombre(self) -> &'static str {
+        match self {
+            Salud::Activo => "Activo",
+            Salud::Atrasado => "Atrasado",
+            Salud::Desconectado => "Desconectado",
+        }
+    }
+}
+
+/// Un equipo que se presentó alguna vez al servidor.
+///
+/// `metrics` se comparte con los paneles; no escribe en el almacén local y
+/// solo conserva los procesos por `RETENCION_PROCESOS_FLOTA`.
+#[derive(Clone)]
+pub struct Equipo {
+    nombre: String,
+    direccion: SocketAddr,
+    conectado: bool,
+    ultima_vez: i64, // ms desde la época UNIX del último mensaje
+    metrics: Arc<Mutex<Metrics>>,
+}
+
+impl Equipo {
+    pub fn get_nombre(&self) -> &str {
+        &self.nombre
+    }
+
+    pub fn get_direccion(&self) -> SocketAddr {
+        self.direccion
+    }
+
+    pub fn get_conectado(&self) -> bool {
+        self.conectado
+    }
+
+    pub fn get_ultima_vez(&self) -> i64 {
+        self.ultima_vez
+    }
+
+    pub fn get_metrics(&self) -> &Arc<Mutex<Metrics>> {
+        &self.metrics
+    }
+
+    /// Salud a la hora `ahora` (ms desde la época UNIX).
+    pub fn salud(&self, ahora: i64) -> Salud {
+        if !self.conectado {
+            Salud::Desconectado
+        } else if ahora - self.ultima_vez > LIMITE_ATRASO {
+            Salud::Atrasado
+        } else {
+            Salud::Activo
+        }
+    }
+}
+
+/// Corre el agente: recolecta con las fuentes e intervalos de `configuracion`
+/// y envía cada snapshot a `servidor` (`host:puerto`), con TLS y token según `cliente`.
+///
+/// `nombre` identifica al equipo en la flota; por defecto, el nombre de host
+/// del inventario. No vuelve: si el servidor no responde, reintenta.
+pub fn ejecutar_agente(servidor: &str, nombre: Option<&str>, cliente: &OpcionesCliente, configuracion: &Configuracion) -> ! {
+    // el agente solo reenvía: sin almacén y con el histórico mínimo
+    let mut configuracion = configuracion.clone();
+    configuracion.retencion = ConfigRetencion {
+        completa: Duracion(Duration::from_secs(60)),
+        procesos: Duracion(Duration::from_secs(60)),
+        niveles: Vec::new(),
+    };
+    configuracion.almacen.activo = false;
+    let mut metrics = Metrics::con_configuracion(&configuracion);
+    metrics.start_monitoring_con(configuracion.registro());
+
+    let mut conexion: Option<(Flujo, Instant)> = None;
+    // marcas del último snapshot enviado por la conexión actual
+    let mut marcas_enviadas: BTreeMap<String, i64> = BTreeMap::new();
+    let mut espera = Duration::from_secs(1);
+    loop {
+        thread::sleep(ESPERA);
+        if !metrics.recibir() {
+            continue;
+        }
+        let Some(metric) = metrics.latest() else { continue };
+        if conexion.is_none() {
+            let equipo = nombre
+                .map(str::to_string)
+                .or_else(|| metric.inventario().map(|i| i.get_nombre_equipo().to_string()))
+                .unwrap_or_else(|| "desconocido".to_string());
+            match conectar_agente(servidor, equipo, cliente) {
+                Ok(stream) => {
+                    eprintln!("Conectado a {}", servidor);
+                    conexion = Some((stream, Instant::now()));
+                    // el servidor completa con el snapshot anterior de esta conexión: el primero va entero
+                    marcas_enviadas.clear();
+                }
+                Err(e) => {
+                    eprintln!("No se pudo conectar a {} (reintento en {} s): {}", servidor, espera.as_secs(), e);
+                    thread::sleep(espera);
+                    espera = (espera * 2).min(ESPERA_MAXIMA);
+                    continue;
+                }
+            }
+        }
+        let Some((stream, desde)) = &mut conexion else { continue };
+        match enviar(stream, &MensajeAgente::Snapshot(metric.cambios_desde(&marcas_enviadas))) {
+            Ok(()) => marcas_enviadas = metric.marcas.clone(),
+            Err(e) => {
+                eprintln!("Conexión con {} interrumpida: {}", servidor, e);
+                // si el servidor corta enseguida (por ejemplo, nombre repetido) se espera más cada vez
+                if desde.elapsed() < ESPERA_MAXIMA {
+                    thread::sleep(espera);
+                    espera = (espera * 2).min(ESPERA_MAXIMA);
+                } else {
+                    espera = Duration::from_secs(1);
+                }
+                conexion = None;
+            }
+        }
+    }
+}
+
+fn conectar_agente(servidor: &str, equipo: String, cliente: &OpcionesCliente) -> io::Result<Flujo> {
+    let mut flujo = seguridad::conectar(servidor, cliente)?;
+    flujo.get_tcp().set_write_timeout(Some(TIEMPO_CONEXION))?;
+    enviar(&mut flujo, &MensajeAgente::Hola { equipo, token: cliente.token.clone() })?;
+    Ok(flujo)
+}
+
+fn enviar(escritor: &mut impl Write, mensaje: &MensajeAgente) -> io::Result<()> {
+    let mut linea = serde_json::to_vec(mensaje)?;
+    linea.push(b'\n');
+    escritor.write_all(&linea)?;
+    escritor.flush()
+}
+
+type Equipos = Arc<Mutex<BTreeMap<String, Equipo>>>;
+
+/// Servidor de la flota en segundo plano; se detiene al soltarlo.
+///
+/// Los equipos se identifican por el nombre con que se presentan: un equipo
+/// que se reconecta sigue con su histórico, y un segundo agente con el nombre
+/// de uno conectado se rechaza. Un agente que deja de enviar (por ejemplo,
+/// con la conexión medio abierta) se corta tras `SILENCIO_MAXIMO`, o tres
+/// veces su intervalo si es mayor, y libera su nombre. Con tokens configurados, el agente debe
+/// presentar uno con alcance de agente; sin ellos solo se escucha en loopback, salvo
+/// que `Seguridad` permita explícitamente el acceso sin autenticación.
+pub struct ServidorFlota {
+    direccion: SocketAddr,
+    detener: Arc<AtomicBool>,
+    equipos: Equipos,
+}
+
+impl ServidorFlota {
+    /// Escucha agentes en `direccion` (por ejemplo `0.0.0.0:9200`).
+    ///
+    /// Falla si `seguridad` no permite escuchar ahí (ver `Seguridad::permitir_escucha`).
+    pub fn iniciar(direccion: &str, seguridad: Seguridad) -> io::Result<Self> {
+        let listener = TcpListener::bind(direccion)?;
+        let direccion = listener.local_addr()?;
+        seguridad.permitir_escucha(&direccion)?;
+        listener.set_nonblocking(true)?;
+        let detener = Arc::new(AtomicBool::new(false));
+        let equipos: Equipos = Arc::new(Mutex::new(BTreeMap::new()));
+        let (detener_hilo, equipos_hilo) = (Arc::clone(&detener), Arc::clone(&equipos));
+        thread::spawn(move || {
+            while !detener_hilo.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, origen)) => {
+                        let (detener, equipos) = (Arc::clone(&detener_hilo), Arc::clone(&equipos_hilo));
+                        let seguridad = seguridad.clone();
+                        thread::spawn(move || {
+                            if let Err(e) = atender(stream, origen, &seguridad, &equipos, &detener) {
+                                eprintln!("Agente {} desconectado: {}", origen, e);
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(ESPERA),
+                    Err(e) => eprintln!("Error al aceptar un agente: {}", e),
+                }
+            }
+        });
+        Ok(ServidorFlota { direccion, detener, equipos })
+    }
+
+    /// Dirección en la que escucha (con el puerto real si se pidió el 0).
+    pub fn get_direccion(&self) -> SocketAddr {
+        self.direccion
+    }
+
+    /// Copia de la lista de equipos, ordenada por nombre.
+    pub fn equipos(&self) -> Vec<Equipo> {
+        self.equipos.lock().map(|e| e.values().cloned().collect()).unwrap_or_default()
+    }
+}
+
+impl Drop for ServidorFlota {
+    fn drop(&mut self) {
+        self.detener.store(true, Ordering::Relaxed);
+    }
+}
+
+// Lee los mensajes de un agente hasta que corta o el servidor se detiene
+fn atender(stream: TcpStream, origen: SocketAddr, seguridad: &Seguridad, equipos: &Equipos, detener: &AtomicBool) -> io::Result<()> {
+    // el socket aceptado puede heredar el modo no bloqueante del listener
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(TIEMPO_LECTURA))?;
+    let mut lector = BufReader::new(seguridad.aceptar(stream)?);
+    let mut linea = Vec::new();
+    let mut nombre: Option<String> = None;
+    // último snapshot de esta conexión, para completar los siguientes (ver `Metric::completar_con`)
+    let mut anterior: Option<Metric> = None;
+    // para cortar una conexión medio abierta, que de otro modo retiene el nombre del equipo
+    let mut ultimo_mensaje = Instant::now();
+    let mut intervalo = Duration::ZERO;
+    let resultado = loop {
+        if detener.load(Ordering::Relaxed) {
+            break Ok(());
+        }
+        let restante = TAMANO_MAXIMO_LINEA.saturating_sub(linea.len() as u64);
+        match (&mut lector).take(restante).read_until(b'\n', &mut linea) {
+            Ok(0) if linea.is_empty() => break Ok(()),
+            Ok(_) if linea.last() != Some(&b'\n') => {
+                if linea.len() as u64 >= TAMANO_MAXIMO_LINEA {
+                    break Err(io::Error::new(io::ErrorKind::InvalidData, "línea demasiado larga"));
+                }
+                break Ok(()); // cortó a mitad de línea
+            }
+            Ok(_) => {}
+            // una lectura parcial queda en `linea` y se completa en la siguiente vuelta
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                let limite = (intervalo * 3).max(SILENCIO_MAXIMO);
+                if ultimo_mensaje.elapsed() > limite {
+                    break Err(io::Error::new(io::ErrorKind::TimedOut, format!("sin mensajes en {} s", limite.as_secs())));
+                }
+                continue;
+            }
+            Err(e) => break Err(e),
+        }
+        let mensaje = serde_json::from_slice::<MensajeAgente>(&linea);
+        linea.clear();
+        let Ok(mut equipos) = equipos.lock() else { break Err(io::Error::other("estado envenenado")) };
+        let ahora = Utc::now().timestamp_millis();
+        match (mensaje, &nombre) {
+            (Ok(MensajeAgente::Hola { equipo, token }), None) => {
+                if let Err(denegado) = seguridad.get_tokens().autorizar(token.as_deref(), Alcance::Agente) {
+                    break Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{} rechazado: {}", equipo, denegado)));
+                }
+                if equipos.get(&equipo).is_some_and(|e| e.conectado) {
+                    break Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("ya hay un agente conectado como {}", equipo)));
+                }
+                let entrada = equipos.entry(equipo.clone()).or_insert_with(|| {
+                    let mut metrics = Metrics::con_retencion(Retencion { procesos: RETENCION_PROCESOS_FLOTA, ..Retencion::default() });
+                    metrics.set_almacen(None);
+                    Equipo { nombre: equipo.clone(), direccion: origen, conectado: true, ultima_vez: ahora, metrics: Arc::new(Mutex::new(metrics)) }
+                });
+                entrada.direccion = origen;
+                entrada.conectado = true;
+                entrada.ultima_vez = ahora;
+                eprintln!("Agente {} conectado desde {}", equipo, origen);
+                ultimo_mensaje = Instant::now();
+                nombre = Some(equipo);
+            }
+            (Ok(MensajeAgente::Snapshot(mut metric)), Some(nombre)) => {
+                intervalo = ultimo_mensaje.elapsed();
+                ultimo_mensaje = Instant::now();
+                if let Some(anterior) = &anterior {
+                    metric.completar_con(anterior);
+                }
+                if let Some(equipo) = equipos.get_mut(nombre) {
+                    equipo.ultima_vez = ahora;
+                    if let Ok(mut metrics) = equipo.metrics.lock() {
+                        metrics.agregar(metric.clone());
+                    }
+                }
+                anterior = Some(metric);
+            }
+            (Ok(_), _) => break Err(io::Error::new(io::ErrorKind::InvalidData, "el agente debe presentarse una sola vez con hola")),
+            (Err(e), _) => break Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    };
+    if let (Some(nombre), Ok(mut equipos)) = (nombre, equipos.lock())
+        && let Some(equipo) = equipos.get_mut(&nombre)
+    {
+        equipo.conectado = false;
+    }
+    resultado
+}
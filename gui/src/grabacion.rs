@@ -0,0 +1,270 @@
+//! Grabación y reproducción del flujo de snapshots `Metric`.
+//!
+//! Una grabación es un archivo NDJSON: una línea JSON por snapshot. Para no
+//! repetir las lecturas pesadas (procesos, cgroups) en cada línea, cada línea
+//! solo incluye las lecturas que cambiaron desde la anterior; `marcas` siempre
+//! va completo y al leer se combinan las líneas para reconstruir cada snapshot.
+//!
+//! - `Grabador`: Anexa snapshots a un archivo.
+//! - `Grabacion`: Grabación en curso; un hilo propio escribe con un `Grabador`
+//!   para que quien publica los snapshots (la GUI) no espere al disco.
+//! - `Reproduccion`: Carga una grabación y la va aplicando sobre un `Metrics`
+//!   propio, con reproducción/pausa, velocidad y salto a un instante.
+//! - `ruta_nueva_grabacion`: Ruta por defecto para una grabación nueva.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::Local;
+use crate::almacen;
+use crate::exportar;
+use crate::metrics::{Metric, Metrics};
+
+pub struct Grabador {
+    ruta: PathBuf,
+    escritor: BufWriter<File>,
+    marcas_escritas: BTreeMap<String, i64>,
+    cantidad: usize,
+}
+
+impl Grabador {
+    /// Crea (o trunca) el archivo de grabación.
+    pub fn crear(ruta: impl Into<PathBuf>) -> io::Result<Self> {
+        let ruta = ruta.into();
+        if let Some(padre) = ruta.parent() {
+            fs::create_dir_all(padre)?;
+        }
+        let escritor = BufWriter::new(File::create(&ruta)?);
+        Ok(Grabador { ruta, escritor, marcas_escritas: BTreeMap::new(), cantidad: 0 })
+    }
+
+    pub fn get_ruta(&self) -> &Path {
+        &self.ruta
+    }
+
+    pub fn get_cantidad(&self) -> usize {
+        self.cantidad
+    }
+
+    /// Anexa un snapshot con solo las lecturas nuevas desde el anterior.
+    pub fn grabar(&mut self, metric: &Metric) -> io::Result<()> {
+        let linea = metric.cambios_desde(&self.marcas_escritas);
+        serde_json::to_writer(&mut self.escritor, &linea)?;
+        self.escritor.write_all(b"\n")?;
+        self.escritor.flush()?;
+        self.marcas_escritas = metric.marcas.clone();
+        self.cantidad += 1;
+        Ok(())
+    }
+}
+
+/// Grabación en curso en segundo plano.
+///
+/// Si una escritura falla el hilo termina y el motivo queda en `get_error`;
+/// los snapshots siguientes se descartan. Al soltarla el hilo termina.
+pub struct Grabacion {
+    ruta: PathBuf,
+    emisor: Sender<Metric>,
+    cantidad: Arc<AtomicUsize>,
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl Grabacion {
+    /// Crea (o trunca) el archivo y arranca el hilo que escribe en él.
+    pub fn iniciar(ruta: impl Into<PathBuf>) -> io::Result<Self> {
+        let mut grabador = Grabador::crear(ruta)?;
+        let ruta = grabador.get_ruta().to_path_buf();
+        let (emisor, receptor) = mpsc::channel::<Metric>();
+        let cantidad = Arc::new(AtomicUsize::new(0));
+        let error = Arc::new(Mutex::new(None));
+        let (cantidad_hilo, error_hilo) = (Arc::clone(&cantidad), Arc::clone(&error));
+        thread::spawn(move || {
+            for metric in receptor {
+                if let Err(e) = grabador.grabar(&metric) {
+                    if let Ok(mut error) = error_hilo.lock() {
+                        *error = Some(e.to_string());
+                    }
+                    return;
+                }
+                cantidad_hilo.store(grabador.get_cantidad(), Ordering::Relaxed);
+            }
+        });
+        Ok(Grabacion { ruta, emisor, cantidad, error })
+    }
+
+    pub fn get_ruta(&self) -> &Path {
+        &self.ruta
+    }
+
+    /// Snapshots ya escritos en el archivo.
+    pub fn get_cantidad(&self) -> usize {
+        self.cantidad.load(Ordering::Relaxed)
+    }
+
+    /// Motivo por el que se dejó de grabar, si una escritura falló.
+    pub fn get_error(&self) -> Option<String> {
+        self.error.lock().ok()?.clone()
+    }
+
+    /// Encola un snapshot para el hilo de escritura sin esperar al disco.
+    pub fn enviar(&self, metric: &Metric) {
+        let _ = self.emisor.send(metric.clone());
+    }
+}
+
+/// Lee una grabación y reconstruye los snapshots completos, en orden.
+///
+/// Una línea final incompleta (grabación interrumpida) se descarta; cualquier
+/// otra línea inválida es un error con su número de línea.
+pub fn leer_grabacion(ruta: &Path) -> io::Result<Vec<Metric>> {
+    let lineas: Vec<String> = BufReader::new(File::open(ruta)?).lines().collect::<io::Result<_>>()?;
+    let mut metricas: Vec<Metric> = Vec::new();
+    for (i, linea) in lineas.iter().enumerate() {
+        if linea.trim().is_empty() {
+            continue;
+        }
+        let mut metric: Metric = match serde_json::from_str(linea) {
+            Ok(metric) => metric,
+            Err(_) if i + 1 == lineas.len() => break,
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("línea {}: {}", i + 1, e))),
+        };
+        if let Some(anterior) = metricas.last() {
+            // las lecturas que no vienen en la línea son las del snapshot anterior
+            metric.completar_con(anterior);
+        }
+        metricas.push(metric);
+    }
+    Ok(metricas)
+}
+
+/// Ruta por defecto para una grabación nueva, con la fecha y hora en el nombre.
+pub fn ruta_nueva_grabacion() -> PathBuf {
+    let nombre = format!("sesion-{}.ndjson", Local::now().format("%Y%m%d-%H%M%S"));
+    almacen::directorio_datos()
+        .map(|dir| dir.join("grabaciones"))
+        .unwrap_or_default()
+        .join(nombre)
+}
+
+pub struct Reproduccion {
+    ruta: PathBuf,
+    metricas: Vec<Metric>,
+    aplicadas: usize, // snapshots ya agregados a `metrics`
+    tiempo: i64,      // instante reproducido, en ms
+    reproduciendo: bool,
+    velocidad: f64,
+    ultimo_avance: Option<Instant>,
+    pub metrics: Arc<Mutex<Metrics>>,
+}
+
+impl Reproduccion {
+    /// Carga una grabación (o una exportación JSON/NDJSON) y se posiciona en
+    /// su primer snapshot, en pausa.
+    pub fn abrir(ruta: impl Into<PathBuf>) -> io::Result<Self> {
+        let ruta = ruta.into();
+        let mut metricas = exportar::importar(&ruta)?;
+        metricas.sort_by_key(|m| m.marca);
+        let Some(primera) = metricas.first() else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "la grabación está vacía"));
+        };
+        let tiempo = primera.marca;
+        let mut reproduccion = Reproduccion {
+            ruta,
+            metricas,
+            aplicadas: 0,
+            tiempo,
+            reproduciendo: false,
+            velocidad: 1.0,
+            ultimo_avance: None,
+            metrics: Arc::new(Mutex::new(Self::metrics_vacio())),
+        };
+        reproduccion.aplicar();
+        Ok(reproduccion)
+    }
+
+    // Histórico sin almacén: lo reproducido no debe mezclarse con lo persistido
+    fn metrics_vacio() -> Metrics {
+        let mut metrics = Metrics::new();
+        metrics.set_almacen(None);
+        metrics
+    }
+
+    pub fn get_ruta(&self) -> &Path {
+        &self.ruta
+    }
+    pub fn get_inicio(&self) -> i64 {
+        self.metricas.first().map_or(0, |m| m.marca)
+    }
+    pub fn get_fin(&self) -> i64 {
+        self.metricas.last().map_or(0, |m| m.marca)
+    }
+    pub fn get_tiempo(&self) -> i64 {
+        self.tiempo
+    }
+    pub fn get_cantidad(&self) -> usize {
+        self.metricas.len()
+    }
+    pub fn reproduciendo(&self) -> bool {
+        self.reproduciendo
+    }
+    pub fn get_velocidad(&self) -> f64 {
+        self.velocidad
+    }
+    pub fn set_velocidad(&mut self, velocidad: f64) {
+        self.velocidad = velocidad.max(0.0);
+    }
+
+    /// Alterna entre reproducción y pausa. Al llegar al final vuelve a empezar.
+    pub fn alternar(&mut self) {
+        if !self.reproduciendo && self.tiempo >= self.get_fin() {
+            self.ir_a(self.get_inicio());
+        }
+        self.reproduciendo = !self.reproduciendo;
+        self.ultimo_avance = None;
+    }
+
+    /// Avanza el tiempo reproducido según el tiempo real transcurrido y la velocidad.
+    ///
+    /// Se llama en cada frame; no hace nada si está en pausa.
+    pub fn avanzar(&mut self) {
+        if !self.reproduciendo {
+            return;
+        }
+        let ahora = Instant::now();
+        let transcurrido = self.ultimo_avance.map_or(Duration::ZERO, |antes| ahora - antes);
+        self.ultimo_avance = Some(ahora);
+        self.tiempo += (transcurrido.as_secs_f64() * 1000.0 * self.velocidad) as i64;
+        if self.tiempo >= self.get_fin() {
+            self.tiempo = self.get_fin();
+            self.reproduciendo = false;
+        }
+        self.aplicar();
+    }
+
+    /// Salta al instante indicado (ms), acotado al rango de la grabación.
+    pub fn ir_a(&mut self, marca: i64) {
+        self.tiempo = marca.clamp(self.get_inicio(), self.get_fin());
+        self.aplicar();
+    }
+
+    // Lleva `metrics` hasta el último snapshot con marca <= tiempo
+    fn aplicar(&mut self) {
+        let objetivo = self.metricas.partition_point(|m| m.marca <= self.tiempo);
+        let Ok(mut metrics) = self.metrics.lock() else { return };
+        if objetivo < self.aplicadas {
+            // hacia atrás: se reconstruye el histórico desde el principio
+            *metrics = Self::metrics_vacio();
+            self.aplicadas = 0;
+        }
+        for metric in &self.metricas[self.aplicadas..objetivo] {
+            metrics.agregar(metric.clone());
+        }
+        self.aplicadas = objetivo;
+    }
+}
@@ -0,0 +1,378 @@
+//! API HTTP sobre un `Metrics` compartido.
+//!
+//! Servidor HTTP/1.1 mínimo sobre `TcpListener`: un hilo por conexión y una
+//! petición por conexión (`Connection: close`). Las consultas son `GET`; la
+//! única acción es el `POST` de señales a procesos.
+//!
+//! Con `Seguridad` el servidor puede usar TLS (y TLS mutuo) y pedir un token
+//! en `Authorization: Bearer ...`: las consultas requieren alcance de lectura
+//! y las acciones, de control. Sin tokens configurados las consultas quedan
+//! abiertas y las acciones deshabilitadas, y solo se acepta escuchar en
+//! loopback (ver `Seguridad::permitir_escucha`).
+//!
+//! - `GET /api/snapshot`: Último `Metric` completo.
+//! - `GET /api/cpu`: Último `CPUInfo`.
+//! - `GET /api/procesos?sort=cpu|memoria|pid&limit=N`: Procesos ordenados
+//!   (por defecto por CPU, sin límite).
+//! - `GET /api/history?metric=cpu.uso&from=MS&to=MS`: Puntos de una serie de
+//!   `Metric::valores`; `from` y `to` en ms desde la época UNIX (por defecto,
+//!   la última hora).
+//! - `GET /api/alerts`: Alertas activas según los umbrales del servidor.
+//! - `GET /metrics`: Último snapshot en el formato de Prometheus (ver `prometheus`).
+//! - `POST /api/procesos/senal?pid=N&senal=terminar|matar`: Envía `SIGTERM` (por
+//!   defecto) o `SIGKILL` al proceso. Requiere alcance de control.
+//!
+//! Las respuestas son JSON (salvo `/metrics`); los errores son `{"error": "..."}` con el código
+//! HTTP correspondiente.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use instrumentos::error::ErrorInstrumentos;
+use instrumentos::procesos::{self, Senal};
+use crate::alertas::{self, Umbrales};
+use crate::metrics::Metrics;
+use crate::prometheus;
+use crate::seguridad::{Alcance, Denegado, Seguridad};
+
+/// Tamaño máximo de la línea de petición más los encabezados.
+const TAMANO_MAXIMO_ENCABEZADOS: usize = 16 * 1024;
+/// Tiempo máximo para recibir la petición o enviar la respuesta.
+const TIEMPO_CONEXION: Duration = Duration::from_secs(5);
+/// Cada cuánto el hilo del servidor revisa si debe terminar.
+const ESPERA: Duration = Duration::from_millis(100);
+
+/// Servidor en segundo plano; se detiene al soltarlo.
+pub struct ServidorHttp {
+    direccion: SocketAddr,
+    detener: Arc<AtomicBool>,
+}
+
+impl ServidorHttp {
+    /// Escucha en `direccion` (por ejemplo `127.0.0.1:9100`) y atiende en un hilo propio.
+    ///
+    /// `umbrales` es compartido para que una recarga de la configuración aplique
+    /// a las peticiones siguientes. Falla si `seguridad` no permite escuchar en
+    /// `direccion` (sin tokens fuera de loopback).
+    pub fn iniciar(direccion: &str, metrics: Arc<Mutex<Metrics>>, umbrales: Arc<Mutex<Umbrales>>, seguridad: Seguridad) -> io::Result<Self> {
+        let listener = TcpListener::bind(direccion)?;
+        let direccion = listener.local_addr()?;
+        seguridad.permitir_escucha(&direccion)?;
+        listener.set_nonblocking(true)?;
+        let detener = Arc::new(AtomicBool::new(false));
+        let contexto = Arc::new(Contexto { metrics, umbrales, seguridad });
+        let detener_hilo = Arc::clone(&detener);
+        thread::spawn(move || {
+            while !detener_hilo.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let contexto = Arc::clone(&contexto);
+                        thread::spawn(move || {
+                            if let Err(e) = atender(stream, &contexto) {
+                                // un cliente que corta la conexión no es un error del servidor
+                                if e.kind() != io::ErrorKind::BrokenPipe && e.kind() != io::ErrorKind::ConnectionReset {
+                                    eprintln!("Error en una conexión HTTP: {}", e);
+                                }
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(ESPERA),
+                    Err(e) => eprintln!("Error al aceptar una conexión HTTP: {}", e),
+                }
+            }
+        });
+        Ok(ServidorHttp { direccion, detener })
+    }
+
+    /// Dirección en la que escucha (con el puerto real si se pidió el 0).
+    pub fn get_direccion(&self) -> SocketAddr {
+        self.direccion
+    }
+}
+
+impl Drop for ServidorHttp {
+    fn drop(&mut self) {
+        self.detener.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Contexto {
+    metrics: Arc<Mutex<Metrics>>,
+    umbrales: Arc<Mutex<Umbrales>>,
+    seguridad: Seguridad,
+}
+
+struct PeticionHttp {
+    metodo: String,
+    ruta: String,
+    consulta: BTreeMap<String, String>,
+    encabezados: BTreeMap<String, String>, // nombres en minúsculas
+}
+
+struct RespuestaHttp {
+    estado: u16,
+    tipo: &'static str,
+    cuerpo: Vec<u8>,
+}
+
+impl RespuestaHttp {
+    fn json<T: Serialize + ?Sized>(valor: &T) -> Self {
+        match serde_json::to_vec(valor) {
+            Ok(cuerpo) => RespuestaHttp { estado: 200, tipo: "application/json", cuerpo },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    fn error(estado: u16, mensaje: &str) -> Self {
+        let cuerpo = serde_json::json!({ "error": mensaje }).to_string().into_bytes();
+        RespuestaHttp { estado, tipo: "application/json", cuerpo }
+    }
+}
+
+fn atender(stream: TcpStream, contexto: &Contexto) -> io::Result<()> {
+    // el socket aceptado puede heredar el modo no bloqueante del listener
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(TIEMPO_CONEXION))?;
+    stream.set_write_timeout(Some(TIEMPO_CONEXION))?;
+    let mut flujo = contexto.seguridad.aceptar(stream)?;
+    let respuesta = match leer_peticion(&mut flujo) {
+        Ok(peticion) => responder(&peticion, contexto),
+        // si falló el handshake TLS no hay canal por el que responder
+        Err(e) if e.kind() == io::ErrorKind::InvalidData && !flujo.negociando() => RespuestaHttp::error(400, &e.to_string()),
+        Err(e) => return Err(e),
+    };
+    escribir_respuesta(&mut flujo, &respuesta)?;
+    flujo.cerrar()
+}
+
+fn leer_peticion(stream: impl Read) -> io::Result<PeticionHttp> {
+    let invalida = |mensaje: &str| io::Error::new(io::ErrorKind::InvalidData, mensaje.to_string());
+    let mut lector = BufReader::new(stream.take(TAMANO_MAXIMO_ENCABEZADOS as u64));
+    let mut linea = String::new();
+    lector.read_line(&mut linea)?;
+    let mut partes = linea.split_whitespace();
+    let (Some(metodo), Some(objetivo), Some(_version)) = (partes.next(), partes.next(), partes.next()) else {
+        return Err(invalida("línea de petición inválida"));
+    };
+    let (metodo, objetivo) = (metodo.to_string(), objetivo.to_string());
+    // el cuerpo, si lo hay, se ignora
+    let mut encabezados = BTreeMap::new();
+    loop {
+        linea.clear();
+        if lector.read_line(&mut linea)? == 0 {
+            return Err(invalida("encabezados incompletos"));
+        }
+        if linea.trim_end().is_empty() {
+            break;
+        }
+        if let Some((nombre, valor)) = linea.split_once(':') {
+            encabezados.insert(nombre.trim().to_ascii_lowercase(), valor.trim().to_string());
+        }
+    }
+    let (ruta, consulta) = objetivo.split_once('?').unwrap_or((&objetivo, ""));
+    let consulta = consulta
+        .split('&')
+        .filter(|par| !par.is_empty())
+        .map(|par| {
+            let (clave, valor) = par.split_once('=').unwrap_or((par, ""));
+            (decodificar(clave), decodificar(valor))
+        })
+        .collect();
+    Ok(PeticionHttp { metodo, ruta: decodificar(ruta), consulta, encabezados })
+}
+
+// Decodifica `%XX` y `+` de una URL; las secuencias inválidas se dejan como están
+fn decodificar(texto: &str) -> String {
+    let bytes = texto.as_bytes();
+    let mut salida = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => salida.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let digito = |b: u8| (b as char).to_digit(16);
+                match (digito(bytes[i + 1]), digito(bytes[i + 2])) {
+                    (Some(alto), Some(bajo)) => {
+                        salida.push((alto * 16 + bajo) as u8);
+                        i += 2;
+                    }
+                    _ => salida.push(b'%'),
+                }
+            }
+            byte => salida.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&salida).into_owned()
+}
+
+fn escribir_respuesta(escritor: &mut impl Write, respuesta: &RespuestaHttp) -> io::Result<()> {
+    write!(
+        escritor,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n",
+        respuesta.estado,
+        razon(respuesta.estado),
+        respuesta.tipo,
+        respuesta.cuerpo.len(),
+        if respuesta.estado == 401 { "WWW-Authenticate: Bearer\r\n" } else { "" }
+    )?;
+    escritor.write_all(&respuesta.cuerpo)?;
+    escritor.flush()
+}
+
+fn razon(estado: u16) -> &'static str {
+    match estado {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+fn responder(peticion: &PeticionHttp, contexto: &Contexto) -> RespuestaHttp {
+    let requerido = match (peticion.metodo.as_str(), peticion.ruta.as_str()) {
+        ("POST", "/api/procesos/senal") => Alcance::Control,
+        (_, "/api/procesos/senal") => return RespuestaHttp::error(405, "usar POST"),
+        ("GET", _) => Alcance::Lectura,
+        _ => return RespuestaHttp::error(405, "solo se admite GET"),
+    };
+    let token = peticion.encabezados.get("authorization").and_then(|v| v.strip_prefix("Bearer ")).map(str::trim);
+    let cliente = match contexto.seguridad.get_tokens().autorizar(token, requerido) {
+        Ok(cliente) => cliente,
+        Err(denegado @ Denegado::NoAutenticado) => return RespuestaHttp::error(401, &denegado.to_string()),
+        Err(denegado) => return RespuestaHttp::error(403, &denegado.to_string()),
+    };
+    if requerido == Alcance::Control {
+        return senal(peticion, cliente);
+    }
+    let Ok(metrics) = contexto.metrics.lock() else {
+        return RespuestaHttp::error(500, "estado envenenado");
+    };
+    if peticion.ruta == "/api/history" {
+        return historial(peticion, metrics);
+    }
+    let rutas = ["/api/snapshot", "/api/cpu", "/api/procesos", "/api/alerts", "/metrics"];
+    if !rutas.contains(&peticion.ruta.as_str()) {
+        return RespuestaHttp::error(404, "ruta desconocida");
+    }
+    let Some(metric) = metrics.latest() else {
+        return RespuestaHttp::error(503, "todavía no hay snapshots");
+    };
+    match peticion.ruta.as_str() {
+        "/api/snapshot" => RespuestaHttp::json(metric),
+        "/metrics" => RespuestaHttp { estado: 200, tipo: prometheus::CONTENT_TYPE, cuerpo: prometheus::exponer(metric).into_bytes() },
+        "/api/cpu" => match metric.cpu() {
+            Some(cpu) => RespuestaHttp::json(cpu),
+            None => RespuestaHttp::error(503, "sin lectura de CPU"),
+        },
+        "/api/procesos" => {
+            let Some(procesos) = metric.procesos() else {
+                return RespuestaHttp::error(503, "sin lectura de procesos");
+            };
+            let limite = match peticion.consulta.get("limit").map(|l| l.parse::<usize>()) {
+                None => usize::MAX,
+                Some(Ok(limite)) => limite,
+                Some(Err(_)) => return RespuestaHttp::error(400, "limit debe ser un entero"),
+            };
+            let mut ordenados: Vec<_> = procesos.get_procesos().iter().collect();
+            match peticion.consulta.get("sort").map(String::as_str).unwrap_or("cpu") {
+                "cpu" => ordenados.sort_by(|a, b| b.get_uso_cpu().total_cmp(&a.get_uso_cpu())),
+                "memoria" => ordenados.sort_by_key(|p| std::cmp::Reverse(p.get_uso_memoria())),
+                "pid" => ordenados.sort_by_key(|p| p.get_pid()),
+                _ => return RespuestaHttp::error(400, "sort debe ser cpu, memoria o pid"),
+            }
+            ordenados.truncate(limite);
+            RespuestaHttp::json(&ordenados)
+        }
+        _ => {
+            let umbrales = *contexto.umbrales.lock().unwrap();
+            RespuestaHttp::json(&alertas::evaluar(metric, &umbrales))
+        }
+    }
+}
+
+fn historial(peticion: &PeticionHttp, metrics: MutexGuard<'_, Metrics>) -> RespuestaHttp {
+    let Some(serie) = peticion.consulta.get("metric") else {
+        return RespuestaHttp::error(400, "falta el parámetro metric");
+    };
+    let entero = |nombre: &str, defecto: i64| match peticion.consulta.get(nombre) {
+        None => Ok(defecto),
+        Some(valor) => valor.parse::<i64>().map_err(|_| format!("{} debe ser un entero (ms)", nombre)),
+    };
+    let ahora = Utc::now().timestamp_millis();
+    let (desde, hasta) = match (entero("from", ahora - 3_600_000), entero("to", ahora)) {
+        (Ok(desde), Ok(hasta)) => (desde, hasta),
+        (Err(e), _) | (_, Err(e)) => return RespuestaHttp::error(400, &e),
+    };
+    // fuera del rango de fechas de chrono el almacén no tiene días que consultar
+    if DateTime::from_timestamp_millis(desde).is_none() || DateTime::from_timestamp_millis(hasta).is_none() {
+        return RespuestaHttp::error(400, "from y to deben ser fechas válidas (ms desde 1970)");
+    }
+    if desde > hasta {
+        return RespuestaHttp::error(400, "from no puede ser posterior a to");
+    }
+    let consulta = metrics.historial(serie, desde, hasta);
+    drop(metrics);
+    #[derive(Serialize)]
+    struct Historial<'a> {
+        metric: &'a str,
+        from: i64,
+        to: i64,
+        puntos: Vec<(i64, f64)>,
+    }
+    RespuestaHttp::json(&Historial { metric: serie, from: desde, to: hasta, puntos: consulta.completar() })
+}
+
+fn senal(peticion: &PeticionHttp, cliente: &str) -> RespuestaHttp {
+    let Some(Ok(pid)) = peticion.consulta.get("pid").map(|p| p.parse::<i32>()) else {
+        return RespuestaHttp::error(400, "pid debe ser un entero");
+    };
+    let nombre = peticion.consulta.get("senal").map(String::as_str).unwrap_or("terminar");
+    let senal = match nombre {
+        "terminar" => Senal::Terminar,
+        "matar" => Senal::Matar,
+        _ => return RespuestaHttp::error(400, "senal debe ser terminar o matar"),
+    };
+    // queda registro de quién terminó qué proceso
+    eprintln!("El cliente {} envió la señal {} al proceso {}", cliente, nombre, pid);
+    match procesos::enviar_senal(pid, senal) {
+        Ok(()) => RespuestaHttp::json(&serde_json::json!({ "pid": pid, "senal": nombre })),
+        Err(e @ ErrorInstrumentos::SinDatos(_)) => RespuestaHttp::error(404, &e.to_string()),
+        Err(e) => RespuestaHttp::error(500, &e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seguridad::OpcionesSeguridad;
+
+    fn iniciar(direccion: &str, opciones: &OpcionesSeguridad) -> io::Result<ServidorHttp> {
+        let seguridad = Seguridad::cargar(opciones).unwrap();
+        ServidorHttp::iniciar(direccion, Arc::new(Mutex::new(Metrics::new())), Arc::new(Mutex::new(Umbrales::default())), seguridad)
+    }
+
+    #[test]
+    fn sin_tokens_rechaza_escuchar_fuera_de_loopback() {
+        let error = iniciar("0.0.0.0:0", &OpcionesSeguridad::default()).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(iniciar("127.0.0.1:0", &OpcionesSeguridad::default()).is_ok());
+    }
+
+    #[test]
+    fn sin_autenticacion_explicita_permite_escuchar_fuera_de_loopback() {
+        let opciones = OpcionesSeguridad { sin_autenticacion: true, ..OpcionesSeguridad::default() };
+        let servidor = iniciar("0.0.0.0:0", &opciones).unwrap();
+        assert!(servidor.get_direccion().ip().is_unspecified());
+    }
+}
@@ -1,11 +1,26 @@
+pub mod alertas;
+pub mod almacen;
 pub mod app;
 pub mod metrics;
 pub mod plots;
+pub mod prometheus;
 pub mod config;
+pub mod daemon;
+pub mod exportar;
+pub mod flota;
+pub mod grabacion;
+pub mod http;
 pub mod log;
+pub mod salidas;
+pub mod seguridad;
 mod views;
 
 pub use app::SistemaDefensaApp;
 pub use views::panel_metricas::PanelMetricas;
 pub use views::panel_alertas::PanelAlertas;
 pub use views::panel_graficas::PanelGraficas;
+pub use views::panel_procesos::PanelProcesos;
+pub use views::panel_arbol_procesos::PanelArbolProcesos;
+pub use views::panel_cgroups::PanelCgroups;
+pub use views::panel_flota::PanelFlota;
+pub use views::panel_configuracion::PanelConfiguracion;
@@ -1,98 +1,1090 @@
 //archivo para ir recopilando la infgormacion a mostrar en la GUI
 
-use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::panic::{self, AssertUnwindSafe};
 use chrono::Utc;
 use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use instrumentos::cgroups::CgroupsInfo;
 use instrumentos::cpu::CPUInfo;
 use instrumentos::disco::DiscosInfo;
+use instrumentos::error::{ErrorInstrumentos, Resultado};
 use instrumentos::memoria::MemoriaInfo;
+use instrumentos::muestreador::INTERVALO_MINIMO_CPU;
 use instrumentos::interfaces::InterfacesInfo;
+use instrumentos::inventario::InventarioInfo;
 use instrumentos::procesos::ProcesosInfo;
-#[derive(Debug, Clone)]
+use instrumentos::recolector::{Lectura, Recolector, Registro};
+use crate::almacen::{self, Almacen};
+use crate::config::{Configuracion, Zona};
+use crate::daemon::Cliente;
+use crate::grabacion::Grabacion;
+
+/// Clave de `Metric::errores` con el último fallo del almacén en disco.
+pub const ERROR_ALMACEN: &str = "almacen";
+/// Puntos aproximados que `ConsultaHistorial::completar` lee del almacén en disco.
+const PUNTOS_HISTORIAL: i64 = 500;
+/// Cuánto espera `MonitorHandle::stop` a que el hilo termine su muestreo en curso.
+const ESPERA_DETENER: Duration = Duration::from_secs(2);
+
+/// Snapshot de todas las fuentes registradas en un instante.
+///
+/// Las lecturas se guardan por nombre de recolector; los accesores `cpu()`,
+/// `memoria()`, etc. devuelven `None` si esa fuente no está registrada.
+/// Como cada fuente tiene su propio intervalo, el snapshot contiene la última
+/// lectura de cada una y `marcas` indica cuándo se tomó cada lectura.
+/// Si una fuente falla se conserva su última lectura válida y el motivo queda
+/// en `errores` hasta que vuelva a muestrearse con éxito. Los fallos del
+/// almacén en disco quedan ahí con la clave `ERROR_ALMACEN`.
+///
+/// Las lecturas se comparten con `Arc`: los snapshots consecutivos reutilizan
+/// la lectura de las fuentes que no se volvieron a muestrear.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Metric {
     pub id: String, // timestamp
-    pub cpu: CPUInfo,
-    pub memoria: MemoriaInfo,
-    pub red: InterfacesInfo,
-    pub disco: DiscosInfo,
-    pub procesos: ProcesosInfo,
+    pub marca: i64, // ms desde la época UNIX en que se publicó el snapshot
+    pub lecturas: BTreeMap<String, Arc<Lectura>>,
+    pub marcas: BTreeMap<String, i64>, // ms desde la época UNIX, por fuente
+    pub errores: BTreeMap<String, String>,
 }
+/// Hilo de recolección en segundo plano.
+///
+/// El hilo nunca toma el mutex de `Metrics`: cada `Metric` terminado se
+/// publica por un canal y la GUI lo recoge con `Metrics::recibir`. En sentido
+/// contrario recibe las `Orden`es por otro canal.
 pub struct MonitorHandle{
-    stop_flag: Arc<AtomicBool>,
+    ordenes: Sender<Orden>,
     thread: Option<thread::JoinHandle<()>>,
 }
+/// Órdenes al hilo de monitoreo; las que cambian la configuración se aplican
+/// sin reiniciarlo.
+enum Orden {
+    Detener,
+    /// Reemplaza los recolectores (fuentes e intervalos).
+    Fuentes(Registro),
+    /// Zona horaria del `id` de los snapshots siguientes.
+    Zona(Tz),
+}
+/// Política de retención del histórico en memoria.
+///
+/// - `completa`: Cuánto tiempo se guardan los snapshots completos.
+/// - `procesos`: Dentro de `completa`, cuánto tiempo se conservan las lecturas
+///   pesadas (procesos y cgroups); en los snapshots más viejos se descartan.
+/// - `niveles`: Resúmenes promediados, como pares (resolución, retención).
+#[derive(Debug, Clone)]
+pub struct Retencion {
+    pub completa: Duration,
+    pub procesos: Duration,
+    pub niveles: Vec<(Duration, Duration)>,
+}
+impl Default for Retencion {
+    /// Resolución completa 1 h (procesos 10 min), promedios de 1 min por 24 h
+    /// y promedios de 15 min por 30 días.
+    fn default() -> Self {
+        const MINUTO: u64 = 60;
+        const HORA: u64 = 60 * MINUTO;
+        const DIA: u64 = 24 * HORA;
+        Retencion {
+            completa: Duration::from_secs(HORA),
+            procesos: Duration::from_secs(10 * MINUTO),
+            niveles: vec![
+                (Duration::from_secs(MINUTO), Duration::from_secs(DIA)),
+                (Duration::from_secs(15 * MINUTO), Duration::from_secs(30 * DIA)),
+            ],
+        }
+    }
+}
+/// Promedio de las series numéricas de un intervalo que empieza en `marca` (ms).
+#[derive(Debug, Clone)]
+pub struct Resumen {
+    pub marca: i64,
+    pub valores: BTreeMap<String, f64>,
+}
+/// Suma y cantidad de muestras de cada serie en el intervalo abierto de un nivel.
+type Acumulados = BTreeMap<String, (f64, u32)>;
+/// Un nivel de resúmenes: acumula los valores de cada intervalo de `resolucion`
+/// ms y, al cerrarlo, guarda el promedio.
+#[derive(Debug, Clone)]
+struct Nivel {
+    resolucion: i64,
+    retencion: i64,
+    puntos: VecDeque<Resumen>,
+    abierto: Option<(i64, Acumulados)>,
+}
 pub struct Metrics {
-    pub vector: Vec<Metric>,
+    pub vector: VecDeque<Metric>,
+    // puntos de cada serie de `vector`, calculados una vez al agregar el snapshot
+    series: BTreeMap<String, VecDeque<(i64, f64)>>,
+    retencion: Retencion,
+    niveles: Vec<Nivel>,
+    aligerados: usize, // snapshots al frente de `vector` que ya no tienen procesos
+    almacen: Option<PathBuf>, // directorio donde el monitoreo persiste las series
+    retencion_almacen: Duration, // antigüedad a partir de la cual se borran días del almacén
+    zona: Tz, // zona horaria del `id` de los snapshots
+    grabacion: Option<Grabacion>,
     monitor_handle: Option<MonitorHandle>,
+    receptor: Option<Receiver<Metric>>,
 }
 impl Clone for Metrics {
     fn clone(&self) -> Self {
         Metrics {
             vector: self.vector.clone(),
+            series: self.series.clone(),
+            retencion: self.retencion.clone(),
+            niveles: self.niveles.clone(),
+            aligerados: self.aligerados,
+            almacen: self.almacen.clone(),
+            retencion_almacen: self.retencion_almacen,
+            zona: self.zona,
+            grabacion: None, // la grabación sigue solo en el original
             monitor_handle: None, // do not clone the handle/thread
+            receptor: None,
+        }
+    }
+}
+impl Metric {
+    /// Recolecta un snapshot completo del sistema con las fuentes integradas.
+    ///
+    /// Crea un registro nuevo y espera lo mínimo necesario para los deltas de CPU;
+    /// para muestreo continuo usar `desde_registro` con un registro de larga vida.
+    /// El `id` se escribe en la zona horaria `zona` (ver `Configuracion::zona`).
+    pub fn recolectar(zona: Tz) -> Self {
+        let mut registro = Registro::por_defecto();
+        thread::sleep(INTERVALO_MINIMO_CPU);
+        Self::desde_registro(&mut registro, zona)
+    }
+
+    /// Toma un snapshot muestreando todos los recolectores del registro.
+    pub fn desde_registro(registro: &mut Registro, zona: Tz) -> Self {
+        let mut metric = Metric::vacio();
+        for recolector in registro.recolectores_mut() {
+            let resultado = muestrear_aislado(recolector.as_mut());
+            metric.actualizar(recolector.nombre(), resultado);
+        }
+        metric.sellar(zona);
+        metric
+    }
+
+    fn vacio() -> Self {
+        Metric { id: String::new(), marca: 0, lecturas: BTreeMap::new(), marcas: BTreeMap::new(), errores: BTreeMap::new() }
+    }
+
+    /// Reemplaza la lectura de una fuente y registra cuándo se tomó.
+    ///
+    /// Si el muestreo falló se conserva la lectura anterior y se anota el error.
+    fn actualizar(&mut self, nombre: &str, resultado: Resultado<Lectura>) {
+        match resultado {
+            Ok(lectura) => {
+                self.lecturas.insert(nombre.to_string(), Arc::new(lectura));
+                self.marcas.insert(nombre.to_string(), Utc::now().timestamp_millis());
+                self.errores.remove(nombre);
+            }
+            Err(error) => {
+                self.errores.insert(nombre.to_string(), error.to_string());
+            }
+        }
+    }
+
+    // El id es el instante en que se publica el snapshot, en la zona indicada
+    fn sellar(&mut self, zona: Tz) {
+        let ahora = Utc::now();
+        self.marca = ahora.timestamp_millis();
+        self.id = ahora.with_timezone(&zona).format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+    }
+
+    /// Series numéricas del snapshot, por nombre (`cpu.uso`, `memoria.uso`, ...).
+    ///
+    /// Son los valores que se conservan en los resúmenes de largo plazo.
+    pub fn valores(&self) -> BTreeMap<String, f64> {
+        self.lecturas.values().flat_map(|lectura| Self::valores_de(lectura)).collect()
+    }
+
+    /// Series numéricas de una sola lectura (ver `valores`).
+    pub fn valores_de(lectura: &Lectura) -> Vec<(String, f64)> {
+        let mut valores = Vec::new();
+        match lectura {
+            Lectura::Cpu(cpu) => {
+                let nucleos = cpu.get_uso_nucleos();
+                if !nucleos.is_empty() {
+                    let promedio = nucleos.iter().map(|&u| u as f64).sum::<f64>() / nucleos.len() as f64;
+                    valores.push(("cpu.uso".to_string(), promedio));
+                }
+            }
+            Lectura::Memoria(memoria) => {
+                if memoria.get_memoria_total() > 0 {
+                    let porcentaje = memoria.get_memoria_usada() as f64 / memoria.get_memoria_total() as f64 * 100.0;
+                    valores.push(("memoria.uso".to_string(), porcentaje));
+                }
+                valores.push(("memoria.usada_mb".to_string(), memoria.get_memoria_usada() as f64));
+                valores.push(("swap.usada_mb".to_string(), memoria.get_swap_usada() as f64));
+            }
+            Lectura::Red(red) => {
+                valores.push(("red.recibidos_mb".to_string(), red.get_bytes_recibidos() as f64 / 1_000_000.0));
+                valores.push(("red.enviados_mb".to_string(), red.get_bytes_enviados() as f64 / 1_000_000.0));
+            }
+            Lectura::Disco(disco) if disco.get_espacio_total() > 0.0 => {
+                valores.push(("disco.uso".to_string(), disco.get_espacio_usado() / disco.get_espacio_total() * 100.0));
+            }
+            Lectura::Procesos(procesos) => {
+                valores.push(("procesos.cantidad".to_string(), procesos.get_cantidad_procesos() as f64));
+            }
+            _ => {}
+        }
+        valores
+    }
+
+    // Descarta las lecturas por proceso, que son las que más memoria ocupan
+    fn aligerar(&mut self) {
+        self.lecturas.retain(|_, l| !matches!(l.as_ref(), Lectura::Procesos(_) | Lectura::Cgroups(_)));
+    }
+
+    /// Copia del snapshot con solo las lecturas cuya marca difiere de `marcas`
+    /// (las del último snapshot enviado o escrito); `marcas` va completo.
+    pub fn cambios_desde(&self, marcas: &BTreeMap<String, i64>) -> Metric {
+        let mut cambios = self.clone();
+        cambios.lecturas.retain(|nombre, _| marcas.get(nombre) != self.marcas.get(nombre));
+        cambios
+    }
+
+    /// Inverso de `cambios_desde`: las lecturas que no vinieron se toman de
+    /// `anterior`, para las fuentes que siguen en `marcas`.
+    pub fn completar_con(&mut self, anterior: &Metric) {
+        for (nombre, lectura) in &anterior.lecturas {
+            if self.marcas.contains_key(nombre) {
+                self.lecturas.entry(nombre.clone()).or_insert_with(|| Arc::clone(lectura));
+            }
         }
     }
+
+    /// Segundos entre la última lectura de la fuente indicada y la publicación del snapshot.
+    ///
+    /// Se mide contra `marca` y no contra el reloj para que también tenga
+    /// sentido al reproducir una grabación.
+    pub fn antiguedad(&self, nombre: &str) -> Option<f64> {
+        let marca = self.marcas.get(nombre)?;
+        Some((self.marca - marca).max(0) as f64 / 1000.0)
+    }
+
+    pub fn cpu(&self) -> Option<&CPUInfo> {
+        self.lecturas.values().find_map(|l| match l.as_ref() { Lectura::Cpu(v) => Some(v), _ => None })
+    }
+    pub fn memoria(&self) -> Option<&MemoriaInfo> {
+        self.lecturas.values().find_map(|l| match l.as_ref() { Lectura::Memoria(v) => Some(v), _ => None })
+    }
+    pub fn red(&self) -> Option<&InterfacesInfo> {
+        self.lecturas.values().find_map(|l| match l.as_ref() { Lectura::Red(v) => Some(v), _ => None })
+    }
+    pub fn disco(&self) -> Option<&DiscosInfo> {
+        self.lecturas.values().find_map(|l| match l.as_ref() { Lectura::Disco(v) => Some(v), _ => None })
+    }
+    pub fn procesos(&self) -> Option<&ProcesosInfo> {
+        self.lecturas.values().find_map(|l| match l.as_ref() { Lectura::Procesos(v) => Some(v), _ => None })
+    }
+    pub fn cgroups(&self) -> Option<&CgroupsInfo> {
+        self.lecturas.values().find_map(|l| match l.as_ref() { Lectura::Cgroups(v) => Some(v), _ => None })
+    }
+    pub fn inventario(&self) -> Option<&InventarioInfo> {
+        self.lecturas.values().find_map(|l| match l.as_ref() { Lectura::Inventario(v) => Some(v), _ => None })
+    }
+    /// Lecturas de fuentes sin variante propia (`Lectura::Otro`), por nombre.
+    pub fn otras(&self) -> impl Iterator<Item = (&String, &serde_json::Value)> {
+        self.lecturas.iter().filter_map(|(nombre, l)| match l.as_ref() { Lectura::Otro(v) => Some((nombre, v)), _ => None })
+    }
 }
 impl Metrics {
     pub fn new() -> Self  {
-        Self { vector: Vec::new(), monitor_handle: None }
+        Self::con_retencion(Retencion::default())
+    }
+
+    /// Crea el histórico con una política de retención concreta.
+    ///
+    /// La zona horaria es la por defecto; `con_configuracion` usa la del archivo.
+    pub fn con_retencion(retencion: Retencion) -> Self {
+        let niveles = retencion
+            .niveles
+            .iter()
+            .map(|(resolucion, duracion)| Nivel::new(*resolucion, *duracion))
+            .collect();
+        Self {
+            vector: VecDeque::new(),
+            series: BTreeMap::new(),
+            retencion,
+            niveles,
+            aligerados: 0,
+            almacen: almacen::directorio_por_defecto(),
+            retencion_almacen: almacen::RETENCION_POR_DEFECTO,
+            zona: Zona::default().0,
+            grabacion: None,
+            monitor_handle: None,
+            receptor: None,
+        }
+    }
+
+    /// Crea el histórico con la retención, el almacén y la zona horaria de la configuración.
+    ///
+    /// Las fuentes y sus intervalos se eligen al iniciar el monitoreo con
+    /// `start_monitoring_con(configuracion.registro())`.
+    pub fn con_configuracion(configuracion: &Configuracion) -> Self {
+        let mut metrics = Self::con_retencion(configuracion.retencion.retencion());
+        metrics.almacen = configuracion.almacen.directorio();
+        metrics.retencion_almacen = configuracion.almacen.retencion.0;
+        metrics.zona = configuracion.zona();
+        metrics
     }
 
     pub fn refresh(&mut self) {
-        let zona = chrono_tz::America::Mexico_City; // Zona horaria de México
-        let fecha_hora = Utc::now().with_timezone(&zona);
-        let fecha = fecha_hora.format("%Y-%m-%d").to_string();
-        let hora = fecha_hora.format("%H:%M:%S").to_string();
-        let metric = Metric {
-            id: fecha_hora.format("%Y-%m-%dT%H:%M:%S%:z").to_string(),
-            cpu: CPUInfo::new(),
-            memoria: MemoriaInfo::new(),
-            red: InterfacesInfo::new(),
-            disco: DiscosInfo::new(),
-            procesos: ProcesosInfo::new(),
-        };
-        self.vector.push(metric);
+        self.agregar(Metric::recolectar(self.zona));
     }
 
     pub fn latest(&self) -> Option<&Metric> {
-        self.vector.last()
+        self.vector.back()
+    }
+
+    /// Agrega un snapshot al histórico, aplica la política de retención y, si
+    /// hay una grabación en curso, se lo pasa a su hilo de escritura.
+    pub fn agregar(&mut self, metric: Metric) {
+        if let Some(grabacion) = &self.grabacion {
+            grabacion.enviar(&metric);
+        }
+        self.incorporar(metric);
+    }
+
+    // Agrega un snapshot al histórico sin grabarlo
+    fn incorporar(&mut self, metric: Metric) {
+        let marca = metric.marca;
+        self.acumular(marca, metric.valores());
+        self.vector.push_back(metric);
+        self.recortar(marca);
+    }
+
+    // Agrega puntos sueltos de las series, sin snapshot, a la resolución completa y a los niveles
+    fn incorporar_puntos(&mut self, series: BTreeMap<String, Vec<(i64, f64)>>) {
+        let mut por_marca: BTreeMap<i64, BTreeMap<String, f64>> = BTreeMap::new();
+        for (nombre, puntos) in series {
+            for (marca, valor) in puntos {
+                por_marca.entry(marca).or_default().insert(nombre.clone(), valor);
+            }
+        }
+        for (marca, valores) in por_marca {
+            self.acumular(marca, valores);
+        }
+    }
+
+    fn acumular(&mut self, marca: i64, valores: BTreeMap<String, f64>) {
+        for nivel in &mut self.niveles {
+            nivel.agregar(marca, &valores);
+        }
+        for (nombre, valor) in valores {
+            self.series.entry(nombre).or_default().push_back((marca, valor));
+        }
+    }
+
+    // Aplica la retención tomando `marca` como el instante actual
+    fn recortar(&mut self, marca: i64) {
+        let limite = marca - self.retencion.completa.as_millis() as i64;
+        while self.vector.front().is_some_and(|m| m.marca < limite) {
+            self.vector.pop_front();
+            self.aligerados = self.aligerados.saturating_sub(1);
+        }
+        for puntos in self.series.values_mut() {
+            while puntos.front().is_some_and(|p| p.0 < limite) {
+                puntos.pop_front();
+            }
+        }
+        self.series.retain(|_, puntos| !puntos.is_empty());
+        for nivel in &mut self.niveles {
+            nivel.podar(marca);
+        }
+        let limite_procesos = marca - self.retencion.procesos.as_millis() as i64;
+        // el último snapshot siempre conserva sus procesos para los paneles
+        while self.aligerados + 1 < self.vector.len() && self.vector[self.aligerados].marca < limite_procesos {
+            self.vector[self.aligerados].aligerar();
+            self.aligerados += 1;
+        }
+    }
+
+    /// Devuelve los puntos `(marca en ms, valor)` de una serie entre `desde` y `hasta`.
+    ///
+    /// Usa la resolución completa donde existe y, para los tramos más viejos,
+    /// los resúmenes de cada nivel, del más fino al más grueso.
+    pub fn serie(&self, nombre: &str, desde: i64, hasta: i64) -> Vec<(i64, f64)> {
+        let mut puntos: Vec<(i64, f64)> = Vec::new();
+        let mut cubierto_desde = self.vector.front().map_or(i64::MAX, |m| m.marca);
+        if let Some(serie) = self.series.get(nombre) {
+            // los puntos traídos de un daemon empiezan antes que los snapshots
+            cubierto_desde = cubierto_desde.min(serie.front().map_or(i64::MAX, |p| p.0));
+            let inicio = serie.partition_point(|p| p.0 < desde);
+            puntos.extend(serie.range(inicio..).take_while(|p| p.0 <= hasta));
+        }
+        for nivel in &self.niveles {
+            let anteriores: Vec<(i64, f64)> = nivel
+                .puntos
+                .iter()
+                .filter(|r| r.marca < cubierto_desde && r.marca >= desde && r.marca <= hasta)
+                .filter_map(|r| r.valores.get(nombre).map(|v| (r.marca, *v)))
+                .collect();
+            if let Some(primero) = nivel.puntos.front() {
+                cubierto_desde = cubierto_desde.min(primero.marca);
+            }
+            puntos.splice(0..0, anteriores);
+        }
+        puntos
+    }
+
+    /// Directorio del almacén persistente (`None` desactiva la persistencia).
+    pub fn get_almacen(&self) -> Option<&PathBuf> {
+        self.almacen.as_ref()
+    }
+
+    /// Cambia el directorio del almacén; aplica a partir del siguiente `start_monitoring`.
+    pub fn set_almacen(&mut self, dir: Option<PathBuf>) {
+        self.almacen = dir;
+    }
+
+    pub fn get_zona(&self) -> Tz {
+        self.zona
+    }
+
+    /// Cambia la zona horaria; si el monitoreo está corriendo, aplica desde el siguiente snapshot.
+    pub fn set_zona(&mut self, zona: Tz) {
+        self.zona = zona;
+        if let Some(handle) = &self.monitor_handle {
+            handle.enviar(Orden::Zona(zona));
+        }
+    }
+
+    /// Cambia la política de retención sin vaciar el histórico.
+    ///
+    /// Los niveles que conservan su resolución conservan también sus resúmenes;
+    /// lo que ya no entra en la retención nueva se descarta enseguida.
+    pub fn set_retencion(&mut self, retencion: Retencion) {
+        let mut anteriores = std::mem::take(&mut self.niveles);
+        self.niveles = retencion
+            .niveles
+            .iter()
+            .map(|(resolucion, duracion)| {
+                let nuevo = Nivel::new(*resolucion, *duracion);
+                match anteriores.iter().position(|n| n.resolucion == nuevo.resolucion) {
+                    Some(i) => Nivel { retencion: nuevo.retencion, ..anteriores.remove(i) },
+                    None => nuevo,
+                }
+            })
+            .collect();
+        self.retencion = retencion;
+        if let Some(marca) = self.latest().map(|m| m.marca) {
+            self.recortar(marca);
+        }
+    }
+
+    /// Reemplaza los recolectores del monitoreo en curso sin detenerlo ni vaciar
+    /// el histórico; las fuentes que ya no están dejan de aparecer en los snapshots.
+    ///
+    /// # Retorno
+    /// `false` si no hay monitoreo local corriendo (el registro se descarta).
+    pub fn set_fuentes(&mut self, registro: Registro) -> bool {
+        match &self.monitor_handle {
+            Some(handle) => handle.enviar(Orden::Fuentes(registro)),
+            None => false,
+        }
+    }
+
+    /// Aplica una configuración nueva sin reiniciar el monitoreo ni vaciar el histórico.
+    ///
+    /// Solo se tocan las partes que cambiaron respecto de `anterior`: retención,
+    /// zona horaria y fuentes. Un cambio de almacén se aplica a partir del
+    /// siguiente `start_monitoring`. Los umbrales no viven aquí: los aplica quien
+    /// evalúa las alertas.
+    pub fn aplicar_configuracion(&mut self, anterior: &Configuracion, nueva: &Configuracion) {
+        if nueva.retencion != anterior.retencion {
+            self.set_retencion(nueva.retencion.retencion());
+        }
+        if nueva.zona() != anterior.zona() {
+            self.set_zona(nueva.zona());
+        }
+        if nueva.monitoreo.recolectores != anterior.monitoreo.recolectores {
+            self.set_fuentes(nueva.registro());
+        }
+        self.almacen = nueva.almacen.directorio();
+        self.retencion_almacen = nueva.almacen.retencion.0;
+    }
+
+    /// Empieza a grabar en `ruta` cada snapshot que llegue (ver `grabacion`).
+    pub fn iniciar_grabacion(&mut self, ruta: PathBuf) -> io::Result<()> {
+        self.grabacion = Some(Grabacion::iniciar(ruta)?);
+        Ok(())
     }
-    pub fn start_monitoring(&mut self, shared_self: Arc<Mutex<Metrics>>) {
-        self.vector.clear();
 
-        let stop_flag = Arc::new(AtomicBool::new(false));
-        let stop_flag_clone = stop_flag.clone();
+    pub fn detener_grabacion(&mut self) {
+        self.grabacion = None;
+    }
+
+    pub fn get_grabacion(&self) -> Option<&Grabacion> {
+        self.grabacion.as_ref()
+    }
+
+    /// Como `serie`, pero completando con el almacén en disco el tramo anterior
+    /// a lo que hay en memoria (por ejemplo, sesiones anteriores).
+    ///
+    /// Solo copia lo que hay en memoria: el disco se lee en
+    /// `ConsultaHistorial::completar`, que se llama después de soltar el candado.
+    pub fn historial(&self, nombre: &str, desde: i64, hasta: i64) -> ConsultaHistorial {
+        ConsultaHistorial {
+            nombre: nombre.to_string(),
+            desde,
+            hasta,
+            almacen: self.almacen.clone(),
+            puntos: self.serie(nombre, desde, hasta),
+        }
+    }
+
+    /// Puntos de resolución completa de todas las series con `desde <= marca <= hasta`.
+    pub fn puntos(&self, desde: i64, hasta: i64) -> BTreeMap<String, Vec<(i64, f64)>> {
+        self.series
+            .iter()
+            .map(|(nombre, serie)| {
+                let inicio = serie.partition_point(|p| p.0 < desde);
+                (nombre.clone(), serie.range(inicio..).take_while(|p| p.0 <= hasta).copied().collect::<Vec<_>>())
+            })
+            .filter(|(_, puntos)| !puntos.is_empty())
+            .collect()
+    }
+
+    /// Snapshots en memoria con `desde <= marca <= hasta`, en orden.
+    pub fn rango(&self, desde: i64, hasta: i64) -> impl Iterator<Item = &Metric> {
+        self.vector.iter().filter(move |m| m.marca >= desde && m.marca <= hasta)
+    }
+
+    /// Nombres de todas las series numéricas disponibles en el histórico.
+    pub fn nombres_series(&self) -> Vec<String> {
+        let mut nombres: Vec<String> = self.latest().map(|m| m.valores().into_keys().collect()).unwrap_or_default();
+        for nivel in &self.niveles {
+            for resumen in &nivel.puntos {
+                nombres.extend(resumen.valores.keys().cloned());
+            }
+        }
+        nombres.sort();
+        nombres.dedup();
+        nombres
+    }
+
+    /// Incorpora los snapshots publicados por el hilo de monitoreo.
+    ///
+    /// No bloquea: solo vacía lo que ya esté en el canal.
+    ///
+    /// # Retorno
+    /// `true` si llegó al menos un snapshot nuevo.
+    pub fn recibir(&mut self) -> bool {
+        let mut nuevos = false;
+        while let Some(receptor) = &self.receptor {
+            match receptor.try_recv() {
+                Ok(metric) => {
+                    self.agregar(metric);
+                    nuevos = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.receptor = None;
+                    break;
+                }
+            }
+        }
+        nuevos
+    }
+
+    /// Inicia el monitoreo con las fuentes integradas.
+    pub fn start_monitoring(&mut self) {
+        self.start_monitoring_con(Registro::por_defecto());
+    }
+
+    /// Inicia el monitoreo recorriendo los recolectores del registro indicado.
+    ///
+    /// Cada recolector se muestrea según su propio `intervalo()`; cada vez que
+    /// alguno produce una lectura nueva se publica un snapshot con la última
+    /// lectura de todas las fuentes, que además se anexa al almacén persistente.
+    ///
+    /// El histórico que ya había se conserva: un reinicio del monitoreo continúa la serie.
+    pub fn start_monitoring_con(&mut self, mut registro: Registro) {
+        self.stop_monitoring();
+        let dir_almacen = self.almacen.clone();
+        let retencion_almacen = self.retencion_almacen;
+        let mut zona = self.zona;
+
+        let (ordenes, ordenes_rx) = mpsc::channel::<Orden>();
+        let (emisor, receptor) = mpsc::channel::<Metric>();
 
         let handle = thread::spawn(move || {
-            while !stop_flag_clone.load(Ordering::Relaxed) {
-                {
-                    let mut m = shared_self.lock().unwrap();
-                    m.refresh();
+            // primera referencia para los deltas de CPU
+            thread::sleep(INTERVALO_MINIMO_CPU);
+            let mut ultimo = Metric::vacio();
+            let mut almacen = dir_almacen.and_then(|dir| match Almacen::abrir(&dir) {
+                Ok(almacen) => Some(almacen),
+                Err(e) => {
+                    ultimo.errores.insert(ERROR_ALMACEN.to_string(), format!("no se pudo abrir {}: {}", dir.display(), e));
+                    None
+                }
+            });
+            let mut proximos = vec![Instant::now(); registro.nombres().len()];
+            loop {
+                // la recolección se hace sin tomar ningún lock
+                let ahora = Instant::now();
+                for (recolector, proximo) in registro.recolectores_mut().zip(proximos.iter_mut()) {
+                    if *proximo > ahora {
+                        continue;
+                    }
+                    let resultado = muestrear_aislado(recolector.as_mut());
+                    ultimo.actualizar(recolector.nombre(), resultado);
+                    // se conserva la cadencia; si el muestreo se atrasó, se reprograma desde ahora
+                    let intervalo = recolector.intervalo().max(INTERVALO_MINIMO_CPU);
+                    *proximo = (*proximo + intervalo).max(Instant::now());
+                }
+                ultimo.sellar(zona);
+                if let Some(a) = &mut almacen {
+                    // un disco lleno no debe detener el monitoreo; el error viaja en los snapshots
+                    match a.escribir(&ultimo).map(|()| a.podar(ultimo.marca, retencion_almacen)) {
+                        Ok(Ok(_)) => {
+                            ultimo.errores.remove(ERROR_ALMACEN);
+                        }
+                        Ok(Err(e)) => {
+                            ultimo.errores.insert(ERROR_ALMACEN.to_string(), format!("no se pudieron borrar días viejos: {}", e));
+                        }
+                        Err(e) => {
+                            ultimo.errores.insert(ERROR_ALMACEN.to_string(), format!("se dejó de escribir: {}", e));
+                            almacen = None;
+                        }
+                    }
+                }
+                if emisor.send(ultimo.clone()).is_err() {
+                    break; // el receptor ya no existe
+                }
+                let siguiente = proximos.iter().min().copied().unwrap_or(ahora + Duration::from_secs(1));
+                match ordenes_rx.recv_timeout(siguiente.saturating_duration_since(Instant::now())) {
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Ok(Orden::Fuentes(nuevo)) => {
+                        registro = nuevo;
+                        let nombres = registro.nombres();
+                        ultimo.lecturas.retain(|nombre, _| nombres.contains(&nombre.as_str()));
+                        ultimo.marcas.retain(|nombre, _| nombres.contains(&nombre.as_str()));
+                        ultimo.errores.retain(|nombre, _| nombre == ERROR_ALMACEN || nombres.contains(&nombre.as_str()));
+                        // el muestreador nuevo necesita una primera referencia para los deltas de CPU
+                        proximos = vec![Instant::now() + INTERVALO_MINIMO_CPU; nombres.len()];
+                    }
+                    Ok(Orden::Zona(nueva)) => zona = nueva,
+                    Ok(Orden::Detener) | Err(RecvTimeoutError::Disconnected) => break,
                 }
-                thread::sleep(Duration::from_secs(5));
             }
         });
 
+        self.receptor = Some(receptor);
         self.monitor_handle = Some(MonitorHandle {
-            stop_flag,
+            ordenes,
             thread: Some(handle),
         });
     }
 
+    /// Empieza a conectarse a un daemon (ver `daemon`) para recibir sus
+    /// snapshots en lugar de recolectarlos.
+    ///
+    /// La conexión y la carga del histórico corren en otro hilo, sin tocar este
+    /// `Metrics`: del daemon se traen solo los puntos de las series dentro de la
+    /// retención completa y el último snapshot. Cuando `ConexionDaemon::revisar`
+    /// entrega el resultado, `usar_daemon` lo instala.
+    pub fn conectar_daemon(&self, ruta: &Path) -> ConexionDaemon {
+        let (avisar, receptor) = mpsc::channel();
+        let retencion = self.retencion.clone();
+        let destino = ruta.to_path_buf();
+        thread::spawn(move || seguir_daemon(&destino, retencion, avisar));
+        ConexionDaemon { ruta: ruta.to_path_buf(), receptor }
+    }
+
+    /// Reemplaza el histórico local por el que trajo `conectar_daemon` y pasa a
+    /// recibir los snapshots del daemon. El daemon ya persiste las series, así
+    /// que aquí no se escribe en el almacén. `stop_monitoring` desconecta.
+    pub fn usar_daemon(&mut self, historico: HistoricoDaemon) {
+        self.stop_monitoring();
+        let HistoricoDaemon { metrics, receptor } = historico;
+        self.vector = metrics.vector;
+        self.series = metrics.series;
+        self.niveles = metrics.niveles;
+        self.aligerados = metrics.aligerados;
+        // por si la retención cambió mientras se conectaba
+        self.set_retencion(self.retencion.clone());
+        self.receptor = Some(receptor);
+    }
 
     pub fn stop_monitoring(&mut self) {
         if let Some(handle) = self.monitor_handle.take() {
             handle.stop();
         }
+        self.receptor = None;
+    }
+
+    pub fn monitoreando(&self) -> bool {
+        self.monitor_handle.is_some()
+    }
+}
+/// Historial de una serie con lo que había en memoria, pendiente de completar
+/// con el almacén en disco (ver `Metrics::historial`).
+pub struct ConsultaHistorial {
+    nombre: String,
+    desde: i64,
+    hasta: i64,
+    almacen: Option<PathBuf>,
+    puntos: Vec<(i64, f64)>,
+}
+impl ConsultaHistorial {
+    /// Agrega lo que hay en disco antes del primer punto en memoria.
+    ///
+    /// Lo que viene del almacén se promedia para no devolver más de unos
+    /// `PUNTOS_HISTORIAL` puntos; si no se puede leer, queda solo lo de memoria.
+    pub fn completar(self) -> Vec<(i64, f64)> {
+        let mut puntos = self.puntos;
+        let inicio_memoria = puntos.first().map_or(i64::MAX, |p| p.0);
+        if let Some(dir) = &self.almacen
+            && self.desde < inicio_memoria
+        {
+            let resolucion = self.hasta.saturating_sub(self.desde) / PUNTOS_HISTORIAL;
+            let anteriores = Almacen::abrir(dir)
+                .and_then(|a| a.consultar_promedios(&self.nombre, self.desde, self.hasta, resolucion))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|p| p.0 < inicio_memoria);
+            puntos.splice(0..0, anteriores);
+        }
+        puntos
+    }
+}
+/// Conexión con un daemon que se establece en otro hilo (ver `Metrics::conectar_daemon`).
+pub struct ConexionDaemon {
+    ruta: PathBuf,
+    receptor: Receiver<io::Result<HistoricoDaemon>>,
+}
+/// Histórico traído del daemon y la suscripción a sus snapshots, listos para `Metrics::usar_daemon`.
+pub struct HistoricoDaemon {
+    metrics: Metrics,
+    receptor: Receiver<Metric>,
+}
+impl ConexionDaemon {
+    pub fn get_ruta(&self) -> &Path {
+        &self.ruta
+    }
+
+    /// No bloquea.
+    ///
+    /// # Retorno
+    /// `None` mientras la conexión sigue en curso.
+    pub fn revisar(&self) -> Option<io::Result<HistoricoDaemon>> {
+        match self.receptor.try_recv() {
+            Ok(resultado) => Some(resultado),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(io::Error::other("el hilo de conexión terminó sin responder"))),
+        }
+    }
+
+    /// Como `revisar`, pero espera a que la conexión termine (para clientes sin interfaz que refrescar).
+    pub fn esperar(self) -> io::Result<HistoricoDaemon> {
+        self.receptor.recv().unwrap_or_else(|_| Err(io::Error::other("el hilo de conexión terminó sin responder")))
+    }
+}
+/// Hilo de `Metrics::conectar_daemon`: arma el histórico, lo entrega por
+/// `avisar` y después reenvía los snapshots del daemon hasta que alguna de
+/// las dos puntas se cierre.
+fn seguir_daemon(ruta: &Path, retencion: Retencion, avisar: Sender<io::Result<HistoricoDaemon>>) {
+    // primero la suscripción, para no perder los snapshots publicados mientras llega el histórico
+    let cargado = Cliente::conectar(ruta).and_then(Cliente::suscribir).and_then(|suscripcion| {
+        let mut cliente = Cliente::conectar(ruta)?;
+        let ultimo = cliente.snapshot()?;
+        let mut metrics = Metrics::con_retencion(retencion);
+        if let Some(ultimo) = ultimo {
+            let desde = ultimo.marca - metrics.retencion.completa.as_millis() as i64;
+            // el último snapshot aporta sus propios puntos
+            metrics.incorporar_puntos(cliente.puntos(desde, ultimo.marca - 1)?);
+            metrics.incorporar(ultimo);
+        }
+        Ok((suscripcion, metrics))
+    });
+    let (suscripcion, metrics) = match cargado {
+        Ok(cargado) => cargado,
+        Err(e) => {
+            let _ = avisar.send(Err(e));
+            return;
+        }
+    };
+    let ultima = metrics.latest().map_or(i64::MIN, |m| m.marca);
+    let (emisor, receptor) = mpsc::channel::<Metric>();
+    if avisar.send(Ok(HistoricoDaemon { metrics, receptor })).is_err() {
+        return; // ya nadie espera la conexión
+    }
+    // los que ya vinieron en el histórico se saltean
+    for metric in suscripcion.filter(|m| m.as_ref().map_or(true, |m| m.marca > ultima)) {
+        match metric {
+            Ok(metric) => {
+                if emisor.send(metric).is_err() {
+                    break; // el receptor ya no existe
+                }
+            }
+            Err(e) => {
+                eprintln!("Conexión con el daemon interrumpida: {}", e);
+                break;
+            }
+        }
+    }
+}
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Nivel {
+    fn new(resolucion: Duration, retencion: Duration) -> Self {
+        Nivel {
+            // una resolución de 0 ms dejaría cada intervalo sin cerrar
+            resolucion: (resolucion.as_millis() as i64).max(1),
+            retencion: retencion.as_millis() as i64,
+            puntos: VecDeque::new(),
+            abierto: None,
+        }
+    }
+
+    fn agregar(&mut self, marca: i64, valores: &BTreeMap<String, f64>) {
+        let inicio = marca - marca.rem_euclid(self.resolucion);
+        if self.abierto.as_ref().is_some_and(|(abierto, _)| *abierto != inicio) {
+            self.cerrar();
+        }
+        let (_, sumas) = self.abierto.get_or_insert_with(|| (inicio, BTreeMap::new()));
+        for (nombre, valor) in valores {
+            let suma = sumas.entry(nombre.clone()).or_insert((0.0, 0));
+            suma.0 += valor;
+            suma.1 += 1;
+        }
+    }
+
+    // Descarta los resúmenes que ya no entran en la retención, tomando `marca` como el instante actual
+    fn podar(&mut self, marca: i64) {
+        let limite = marca - self.retencion;
+        while self.puntos.front().is_some_and(|r| r.marca < limite) {
+            self.puntos.pop_front();
+        }
+    }
+
+    fn cerrar(&mut self) {
+        if let Some((marca, sumas)) = self.abierto.take() {
+            let valores = sumas.into_iter().map(|(nombre, (suma, n))| (nombre, suma / n as f64)).collect();
+            self.puntos.push_back(Resumen { marca, valores });
+        }
     }
 }
+/// Muestrea un recolector convirtiendo un pánico en `ErrorInstrumentos::Panico`,
+/// para que una fuente defectuosa no termine el hilo de monitoreo.
+fn muestrear_aislado(recolector: &mut dyn Recolector) -> Resultado<Lectura> {
+    panic::catch_unwind(AssertUnwindSafe(|| recolector.muestrear())).unwrap_or_else(|pago| {
+        let mensaje = pago
+            .downcast_ref::<&str>()
+            .map(|m| m.to_string())
+            .or_else(|| pago.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "pánico sin mensaje".to_string());
+        Err(ErrorInstrumentos::Panico { fuente: recolector.nombre().to_string(), mensaje })
+    })
+}
 impl MonitorHandle {
+    /// Pide al hilo que termine y espera a que salga, como mucho `ESPERA_DETENER`,
+    /// para que al reiniciar no queden dos hilos muestreando a la vez.
+    ///
+    /// Si el muestreo en curso tarda más (un `/proc` muy lento), el hilo se
+    /// suelta para no congelar la GUI y termina solo al acabar ese muestreo.
     pub fn stop(mut self) {
-        self.stop_flag.store(true, Ordering::Relaxed);
-        if let Some(handle) = self.thread.take() {
-            let _ = handle.join();
+        let _ = self.ordenes.send(Orden::Detener);
+        let Some(hilo) = self.thread.take() else { return };
+        let limite = Instant::now() + ESPERA_DETENER;
+        while !hilo.is_finished() && Instant::now() < limite {
+            thread::sleep(Duration::from_millis(10));
+        }
+        if hilo.is_finished() {
+            let _ = hilo.join();
+        }
+    }
+
+    /// # Retorno
+    /// `false` si el hilo ya terminó.
+    fn enviar(&self, orden: Orden) -> bool {
+        self.ordenes.send(orden).is_ok()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // 2024-03-10 00:00 UTC, múltiplo de todas las resoluciones de las pruebas
+    const BASE: i64 = 1_710_028_800_000;
+
+    fn snapshot(marca: i64, uso: f32) -> Metric {
+        serde_json::from_value(json!({
+            "id": "prueba",
+            "marca": marca,
+            "lecturas": {
+                "cpu": { "tipo": "cpu", "datos": { "brand": "x", "cantidad_nucleos": 1, "frecuencia": 0, "uso_nucleos": [uso] } },
+                "procesos": { "tipo": "procesos", "datos": {
+                    "cantidad_procesos": 0, "procesos": [], "top_procesos_uso_cpu": [], "top_procesos_uso_memoria": [],
+                    "top_procesos_tiempo_cpu": [], "top_procesos_tiempo_ejecucion": []
+                } }
+            },
+            "marcas": { "cpu": marca, "procesos": marca },
+            "errores": {}
+        }))
+        .unwrap()
+    }
+
+    // Resolución completa 4 s (procesos 2 s), promedios de 1 s por 10 s y de 5 s por 60 s
+    fn retencion() -> Retencion {
+        Retencion {
+            completa: Duration::from_secs(4),
+            procesos: Duration::from_secs(2),
+            niveles: vec![(Duration::from_secs(1), Duration::from_secs(10)), (Duration::from_secs(5), Duration::from_secs(60))],
+        }
+    }
+
+    // 30 s de snapshots cada 500 ms; el i-ésimo tiene un uso de CPU de `i`
+    fn alimentado() -> Metrics {
+        let mut metrics = Metrics::con_retencion(retencion());
+        for i in 0..60 {
+            metrics.incorporar(snapshot(BASE + i * 500, i as f32));
         }
+        metrics
     }
-}
\ No newline at end of file
+
+    fn puntos(nivel: &Nivel) -> Vec<(i64, f64)> {
+        nivel.puntos.iter().map(|r| (r.marca - BASE, r.valores["cpu.uso"])).collect()
+    }
+
+    #[test]
+    fn nivel_corta_los_intervalos_en_su_limite() {
+        let mut nivel = Nivel::new(Duration::from_secs(1), Duration::from_secs(60));
+        let valores = |v: f64| BTreeMap::from([("cpu.uso".to_string(), v)]);
+        nivel.agregar(BASE, &valores(1.0));
+        nivel.agregar(BASE + 999, &valores(3.0));
+        assert!(nivel.puntos.is_empty(), "el intervalo abierto no se publica");
+        nivel.agregar(BASE + 1000, &valores(10.0));
+        assert_eq!(puntos(&nivel), vec![(0, 2.0)]);
+        // un intervalo sin muestras no deja resumen
+        nivel.agregar(BASE + 3500, &valores(20.0));
+        assert_eq!(puntos(&nivel), vec![(0, 2.0), (1000, 10.0)]);
+    }
+
+    #[test]
+    fn cada_nivel_promedia_y_retiene_lo_suyo() {
+        let metrics = alimentado();
+        let marcas: Vec<i64> = metrics.vector.iter().map(|m| m.marca - BASE).collect();
+        assert_eq!(marcas, (51..60).map(|i| i * 500).collect::<Vec<_>>());
+
+        // 1 s: los últimos 10 s, promediando dos muestras por intervalo; el del último segundo sigue abierto
+        let esperado: Vec<(i64, f64)> = (20..29).map(|k| (k * 1000, 2.0 * k as f64 + 0.5)).collect();
+        assert_eq!(puntos(&metrics.niveles[0]), esperado);
+        // 5 s: todo, promediando diez muestras por intervalo
+        let esperado: Vec<(i64, f64)> = (0..5).map(|j| (j * 5000, 10.0 * j as f64 + 4.5)).collect();
+        assert_eq!(puntos(&metrics.niveles[1]), esperado);
+    }
+
+    #[test]
+    fn los_procesos_se_descartan_fuera_de_su_ventana() {
+        let metrics = alimentado();
+        for metric in &metrics.vector {
+            assert!(metric.cpu().is_some());
+            // la ventana de procesos termina 2 s antes del último snapshot
+            assert_eq!(metric.procesos().is_some(), metric.marca >= BASE + 27_500, "marca {}", metric.marca - BASE);
+        }
+        assert_eq!(metrics.aligerados, 4);
+    }
+
+    #[test]
+    fn serie_une_los_niveles_en_orden_y_sin_huecos() {
+        let metrics = alimentado();
+        let serie: Vec<(i64, f64)> = metrics.serie("cpu.uso", BASE, BASE + 29_500).into_iter().map(|(m, v)| (m - BASE, v)).collect();
+
+        let mut esperado: Vec<(i64, f64)> = (0..4).map(|j| (j * 5000, 10.0 * j as f64 + 4.5)).collect();
+        esperado.extend((20..26).map(|k| (k * 1000, 2.0 * k as f64 + 0.5)));
+        esperado.extend((51..60).map(|i| (i * 500, i as f64)));
+        assert_eq!(serie, esperado);
+        assert!(serie.windows(2).all(|p| p[0].0 < p[1].0));
+        // ningún salto supera la resolución más gruesa
+        assert!(serie.windows(2).all(|p| p[1].0 - p[0].0 <= 5000));
+
+        // un rango que solo toca la resolución completa no trae resúmenes
+        let reciente = metrics.serie("cpu.uso", BASE + 26_000, BASE + 27_000);
+        assert_eq!(reciente, vec![(BASE + 26_000, 52.0), (BASE + 26_500, 53.0), (BASE + 27_000, 54.0)]);
+        assert!(metrics.serie("memoria.uso", BASE, BASE + 29_500).is_empty());
+    }
+
+    #[test]
+    fn set_retencion_conserva_los_niveles_que_siguen_y_recorta_enseguida() {
+        let mut metrics = alimentado();
+        metrics.set_retencion(Retencion {
+            completa: Duration::from_secs(1),
+            procesos: Duration::ZERO,
+            niveles: vec![(Duration::from_secs(1), Duration::from_secs(3))],
+        });
+        let marcas: Vec<i64> = metrics.vector.iter().map(|m| m.marca - BASE).collect();
+        assert_eq!(marcas, vec![28_500, 29_000, 29_500]);
+        assert_eq!(metrics.niveles.len(), 1);
+        assert_eq!(puntos(&metrics.niveles[0]), vec![(27_000, 54.5), (28_000, 56.5)]);
+        // solo el último snapshot conserva los procesos
+        assert_eq!(metrics.vector.iter().filter(|m| m.procesos().is_some()).count(), 1);
+
+        let serie = metrics.serie("cpu.uso", BASE, BASE + 29_500);
+        assert_eq!(serie.iter().map(|p| p.0 - BASE).collect::<Vec<_>>(), vec![27_000, 28_000, 28_500, 29_000, 29_500]);
+
+        // el intervalo que estaba abierto se cierra con las muestras de antes del cambio
+        metrics.incorporar(snapshot(BASE + 30_000, 60.0));
+        assert_eq!(puntos(&metrics.niveles[0]).last(), Some(&(29_000, 58.5)));
+    }
+
+    #[test]
+    fn el_historico_de_un_daemon_se_arma_con_sus_puntos() {
+        let daemon = alimentado();
+        let ultimo = daemon.latest().unwrap().clone();
+        let desde = ultimo.marca - daemon.retencion.completa.as_millis() as i64;
+        let mut metrics = Metrics::con_retencion(retencion());
+        metrics.incorporar_puntos(daemon.puntos(desde, ultimo.marca - 1));
+        metrics.incorporar(ultimo);
+        assert_eq!(metrics.vector.len(), 1);
+        let reciente = (BASE + 25_500, BASE + 29_500);
+        assert_eq!(metrics.serie("cpu.uso", reciente.0, reciente.1), daemon.serie("cpu.uso", reciente.0, reciente.1));
+
+        // los snapshots que siguen continúan la serie; los resúmenes solo cubren lo que ya salió de la resolución completa
+        for i in 60..64 {
+            metrics.incorporar(snapshot(BASE + i * 500, i as f32));
+        }
+        let serie: Vec<(i64, f64)> = metrics.serie("cpu.uso", BASE, BASE + 31_500).into_iter().map(|(m, v)| (m - BASE, v)).collect();
+        let mut esperado = vec![(25_000, 51.0), (26_000, 52.5), (27_000, 54.5)];
+        esperado.extend((55..64).map(|i| (i * 500, i as f64)));
+        assert_eq!(serie, esperado);
+    }
+
+    #[test]
+    fn un_delta_se_completa_con_el_snapshot_anterior() {
+        let anterior = snapshot(BASE, 10.0);
+        let mut actual = snapshot(BASE + 500, 20.0);
+        // los procesos no se volvieron a muestrear
+        actual.marcas.insert("procesos".to_string(), BASE);
+
+        let mut delta = actual.cambios_desde(&anterior.marcas);
+        assert_eq!(delta.lecturas.keys().collect::<Vec<_>>(), vec!["cpu"]);
+        assert_eq!(delta.marcas, actual.marcas);
+        delta.completar_con(&anterior);
+        assert_eq!(delta.lecturas.len(), 2);
+        assert!(Arc::ptr_eq(&delta.lecturas["procesos"], &anterior.lecturas["procesos"]));
+        assert_eq!(delta.cpu().unwrap().get_uso_nucleos(), &[20.0]);
+
+        // sin marcas anteriores (conexión nueva) va completo; una fuente que ya no está no se arrastra
+        assert_eq!(actual.cambios_desde(&BTreeMap::new()).lecturas.len(), 2);
+        let mut sin_procesos = actual.cambios_desde(&actual.marcas);
+        sin_procesos.marcas.remove("procesos");
+        sin_procesos.completar_con(&anterior);
+        assert_eq!(sin_procesos.lecturas.keys().collect::<Vec<_>>(), vec!["cpu"]);
+    }
+}
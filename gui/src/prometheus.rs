@@ -0,0 +1,192 @@
+//! Exposición de un snapshot en el formato de texto de Prometheus (0.0.4).
+//!
+//! Las unidades se convierten a las base de Prometheus: bytes, hertz y
+//! proporciones de 0 a 1. Los contadores de red son acumulados desde el
+//! arranque, por lo que se exponen como `counter` con sufijo `_total`.
+//!
+//! - `CONTENT_TYPE`: Tipo de contenido de la respuesta.
+//! - `exponer`: Texto con todas las familias de métricas del snapshot.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use instrumentos::disco::DiscoInfo;
+use instrumentos::interfaces::InterfaceInfo;
+use crate::metrics::Metric;
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+const PREFIJO: &str = "sistema_defensa";
+
+/// Nombre, ayuda y valor de una familia que se repite por cada elemento `T`.
+type Familia<T, V> = (&'static str, &'static str, fn(&T) -> V);
+
+/// Texto en formato de exposición con las métricas del snapshot.
+///
+/// Las fuentes que no están en el snapshot simplemente no aparecen.
+pub fn exponer(metric: &Metric) -> String {
+    let mut e = Exposicion::default();
+
+    if let Some(cpu) = metric.cpu() {
+        e.familia("cpu_uso_ratio", "gauge", "Uso de cada núcleo de CPU (0 a 1).");
+        for (i, uso) in cpu.get_uso_nucleos().iter().enumerate() {
+            e.muestra("cpu_uso_ratio", &[("nucleo", &i.to_string())], *uso as f64 / 100.0);
+        }
+        e.familia("cpu_frecuencia_hertz", "gauge", "Frecuencia del CPU.");
+        e.muestra("cpu_frecuencia_hertz", &[], cpu.get_frecuencia() as f64 * 1_000_000.0);
+    }
+
+    if let Some(memoria) = metric.memoria() {
+        e.familia("memoria_ram_bytes", "gauge", "Memoria RAM por estado.");
+        let ram = [("total", memoria.get_total_ram_bytes()), ("usada", memoria.get_usada_ram_bytes()), ("libre", memoria.get_libre_ram_bytes())];
+        for (estado, bytes) in ram {
+            e.muestra("memoria_ram_bytes", &[("estado", estado)], bytes as f64);
+        }
+        e.familia("memoria_swap_bytes", "gauge", "Memoria swap por estado.");
+        let swap = [("total", memoria.get_swap_total_bytes()), ("usada", memoria.get_swap_usada_bytes()), ("libre", memoria.get_swap_libre_bytes())];
+        for (estado, bytes) in swap {
+            e.muestra("memoria_swap_bytes", &[("estado", estado)], bytes as f64);
+        }
+    }
+
+    if let Some(discos) = metric.disco() {
+        let familias: [Familia<DiscoInfo, f64>; 4] = [
+            ("disco_tamano_bytes", "Tamaño del sistema de archivos.", |d| d.get_bytes_total() as f64),
+            ("disco_usado_bytes", "Espacio usado del sistema de archivos.", |d| d.get_bytes_usados() as f64),
+            ("disco_libre_bytes", "Espacio disponible del sistema de archivos.", |d| d.get_bytes_libres() as f64),
+            ("disco_solo_lectura", "1 si el sistema de archivos está montado como solo lectura.", |d| d.get_solo_lectura() as u8 as f64),
+        ];
+        for (nombre, ayuda, valor) in familias {
+            e.familia(nombre, "gauge", ayuda);
+            for disco in discos.get_discos() {
+                let etiquetas = [
+                    ("dispositivo", disco.get_nombre()),
+                    ("punto_montaje", disco.get_ruta()),
+                    ("sistema_archivos", disco.get_sistema_archivos()),
+                ];
+                e.muestra(nombre, &etiquetas, valor(disco));
+            }
+        }
+    }
+
+    if let Some(red) = metric.red() {
+        let familias: [Familia<InterfaceInfo, u64>; 6] = [
+            ("red_recibidos_bytes_total", "Bytes recibidos por la interfaz.", |i| i.get_bytes_recibidos()),
+            ("red_enviados_bytes_total", "Bytes enviados por la interfaz.", |i| i.get_bytes_enviados()),
+            ("red_paquetes_recibidos_total", "Paquetes recibidos por la interfaz.", |i| i.get_numero_paquetes_recibidos()),
+            ("red_paquetes_enviados_total", "Paquetes enviados por la interfaz.", |i| i.get_numero_paquetes_enviados()),
+            ("red_errores_recibidos_total", "Errores al recibir en la interfaz.", |i| i.get_total_errores_recibidos()),
+            ("red_errores_enviados_total", "Errores al enviar en la interfaz.", |i| i.get_total_errores_enviados()),
+        ];
+        for (nombre, ayuda, valor) in familias {
+            e.familia(nombre, "counter", ayuda);
+            for interfaz in red.get_interfaces() {
+                e.muestra(nombre, &[("interfaz", interfaz.get_nombre())], valor(interfaz) as f64);
+            }
+        }
+    }
+
+    if let Some(procesos) = metric.procesos() {
+        let mut por_estado: BTreeMap<&str, usize> = BTreeMap::new();
+        for proceso in procesos.get_procesos() {
+            *por_estado.entry(proceso.get_estado().as_str()).or_default() += 1;
+        }
+        e.familia("procesos", "gauge", "Cantidad de procesos por estado.");
+        for (estado, cantidad) in por_estado {
+            e.muestra("procesos", &[("estado", estado)], cantidad as f64);
+        }
+        e.familia("procesos_sospechosos", "gauge", "Procesos con capacidades peligrosas fuera de los namespaces del host.");
+        e.muestra("procesos_sospechosos", &[], procesos.get_procesos_sospechosos().len() as f64);
+    }
+
+    if let Some(inventario) = metric.inventario() {
+        e.familia("arranque_segundos", "gauge", "Hora de arranque del sistema en segundos desde la época UNIX.");
+        e.muestra("arranque_segundos", &[], inventario.get_arranque() as f64);
+    }
+
+    e.familia("fuente_error", "gauge", "1 si el último muestreo de la fuente falló.");
+    for nombre in metric.lecturas.keys().chain(metric.errores.keys()).collect::<BTreeSet<_>>() {
+        e.muestra("fuente_error", &[("fuente", nombre)], metric.errores.contains_key(nombre) as u8 as f64);
+    }
+    e.familia("fuente_antiguedad_segundos", "gauge", "Segundos desde la última lectura válida de la fuente.");
+    for nombre in metric.lecturas.keys() {
+        if let Some(segundos) = metric.antiguedad(nombre) {
+            e.muestra("fuente_antiguedad_segundos", &[("fuente", nombre)], segundos);
+        }
+    }
+
+    e.texto
+}
+
+#[derive(Default)]
+struct Exposicion {
+    texto: String,
+}
+
+impl Exposicion {
+    fn familia(&mut self, nombre: &str, tipo: &str, ayuda: &str) {
+        let _ = writeln!(self.texto, "# HELP {}_{} {}", PREFIJO, nombre, ayuda);
+        let _ = writeln!(self.texto, "# TYPE {}_{} {}", PREFIJO, nombre, tipo);
+    }
+
+    fn muestra(&mut self, nombre: &str, etiquetas: &[(&str, &str)], valor: f64) {
+        let _ = write!(self.texto, "{}_{}", PREFIJO, nombre);
+        if !etiquetas.is_empty() {
+            let pares: Vec<String> = etiquetas.iter().map(|(clave, v)| format!("{}=\"{}\"", clave, escapar(v))).collect();
+            let _ = write!(self.texto, "{{{}}}", pares.join(","));
+        }
+        let _ = writeln!(self.texto, " {}", numero(valor));
+    }
+}
+
+// Escapa `\`, `"` y saltos de línea en el valor de una etiqueta
+fn escapar(valor: &str) -> String {
+    valor.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn numero(valor: f64) -> String {
+    if valor.is_nan() {
+        "NaN".to_string()
+    } else if valor.is_infinite() {
+        if valor > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        valor.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapar_barra_comillas_y_salto_de_linea() {
+        assert_eq!(escapar("eth0"), "eth0");
+        assert_eq!(escapar(r"C:\disco"), r"C:\\disco");
+        assert_eq!(escapar("di\"jo\""), "di\\\"jo\\\"");
+        assert_eq!(escapar("a\nb"), "a\\nb");
+        // la barra se escapa primero para no duplicar las que agregan las demás
+        assert_eq!(escapar("\\\"\n"), "\\\\\\\"\\n");
+    }
+
+    #[test]
+    fn muestra_con_etiquetas_escapadas() {
+        let mut e = Exposicion::default();
+        e.familia("disco_usado_bytes", "gauge", "Bytes usados.");
+        e.muestra("disco_usado_bytes", &[("montaje", "/mnt/\"x\""), ("dispositivo", "sda\n1")], 42.0);
+        e.muestra("procesos", &[], 3.0);
+        assert_eq!(
+            e.texto,
+            "# HELP sistema_defensa_disco_usado_bytes Bytes usados.\n\
+             # TYPE sistema_defensa_disco_usado_bytes gauge\n\
+             sistema_defensa_disco_usado_bytes{montaje=\"/mnt/\\\"x\\\"\",dispositivo=\"sda\\n1\"} 42\n\
+             sistema_defensa_procesos 3\n"
+        );
+    }
+
+    #[test]
+    fn numeros_especiales() {
+        assert_eq!(numero(f64::NAN), "NaN");
+        assert_eq!(numero(f64::INFINITY), "+Inf");
+        assert_eq!(numero(f64::NEG_INFINITY), "-Inf");
+        assert_eq!(numero(0.25), "0.25");
+    }
+}
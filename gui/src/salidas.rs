@@ -0,0 +1,537 @@
+//! Salidas de envío: serializan cada `Metric` en el protocolo de líneas de
+//! InfluxDB o en el texto plano de Graphite y lo envían a un destino, para
+//! los equipos que no se pueden consultar desde fuera (push en lugar de scrape).
+//!
+//! Las líneas se acumulan y se envían por lotes. Si el envío falla, las líneas
+//! se conservan y se reintenta con espera creciente; si el búfer se llena, se
+//! descartan las más viejas. Un lote que falló a medias se reenvía completo:
+//! por red, InfluxDB y Graphite sobrescriben el punto repetido; en un archivo,
+//! antes de reintentar se corta lo que se alcanzó a escribir de ese lote.
+//! Los valores NaN o infinitos se omiten, porque ninguno de los dos los acepta.
+//!
+//! - `Formato`: Protocolo de líneas de InfluxDB o texto plano de Graphite.
+//! - `Destino`: `udp://host:puerto`, `tcp://host:puerto` o un archivo.
+//! - `Salida`: Búfer de líneas de un formato hacia un destino.
+//! - `iniciar`: Corre varias salidas en un hilo y devuelve el emisor de snapshots.
+//! - `lineas_influx` / `lineas_graphite`: Serialización de un snapshot.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::metrics::Metric;
+
+/// Líneas que se acumulan antes de enviar un lote.
+const LOTE: usize = 500;
+/// Tiempo máximo que una línea espera en el búfer antes de enviarse.
+const INTERVALO_ENVIO: Duration = Duration::from_secs(10);
+/// Líneas que se conservan mientras el destino no responde.
+const MAXIMO_PENDIENTES: usize = 100_000;
+/// Espera inicial y máxima entre reintentos.
+const ESPERA_INICIAL: Duration = Duration::from_secs(1);
+const ESPERA_MAXIMA: Duration = Duration::from_secs(60);
+/// Tiempo máximo para conectar o escribir por TCP.
+const TIEMPO_RED: Duration = Duration::from_secs(5);
+/// Tamaño máximo de un datagrama UDP, para no fragmentar en redes con MTU 1500.
+const MAXIMO_DATAGRAMA: usize = 1400;
+/// Prefijo de las rutas de Graphite.
+const PREFIJO_GRAPHITE: &str = "sistema_defensa";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Formato {
+    Influx,
+    Graphite,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destino {
+    Udp(String),
+    Tcp(String),
+    Archivo(PathBuf),
+}
+
+impl Destino {
+    /// Interpreta `udp://host:puerto`, `tcp://host:puerto`, `file:///ruta` o una ruta.
+    pub fn desde_texto(texto: &str) -> Result<Self, String> {
+        if let Some(direccion) = texto.strip_prefix("udp://") {
+            Ok(Destino::Udp(direccion.to_string()))
+        } else if let Some(direccion) = texto.strip_prefix("tcp://") {
+            Ok(Destino::Tcp(direccion.to_string()))
+        } else if let Some(ruta) = texto.strip_prefix("file://") {
+            Ok(Destino::Archivo(PathBuf::from(ruta)))
+        } else if texto.contains("://") {
+            Err(format!("esquema desconocido en {}; usar udp://, tcp:// o file://", texto))
+        } else {
+            Ok(Destino::Archivo(PathBuf::from(texto)))
+        }
+    }
+}
+
+// Conexión abierta hacia el destino; se vuelve a abrir tras un error
+enum Conexion {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+    Archivo(File),
+}
+
+pub struct Salida {
+    formato: Formato,
+    destino: Destino,
+    conexion: Option<Conexion>,
+    pendientes: VecDeque<String>,
+    descartadas: u64,
+    ultimo_envio: Instant,
+    espera: Duration,
+    proximo_intento: Option<Instant>,
+}
+
+impl Salida {
+    pub fn new(formato: Formato, destino: Destino) -> Self {
+        Salida {
+            formato,
+            destino,
+            conexion: None,
+            pendientes: VecDeque::new(),
+            descartadas: 0,
+            ultimo_envio: Instant::now(),
+            espera: ESPERA_INICIAL,
+            proximo_intento: None,
+        }
+    }
+
+    pub fn get_destino(&self) -> &Destino {
+        &self.destino
+    }
+
+    /// Líneas en el búfer esperando envío.
+    pub fn get_pendientes(&self) -> usize {
+        self.pendientes.len()
+    }
+
+    /// Líneas descartadas por búfer lleno desde que se creó la salida.
+    pub fn get_descartadas(&self) -> u64 {
+        self.descartadas
+    }
+
+    /// Serializa el snapshot y lo agrega al búfer.
+    pub fn agregar(&mut self, metric: &Metric) {
+        let lineas = match self.formato {
+            Formato::Influx => lineas_influx(metric),
+            Formato::Graphite => lineas_graphite(metric),
+        };
+        self.pendientes.extend(lineas);
+        let sobrantes = self.pendientes.len().saturating_sub(MAXIMO_PENDIENTES);
+        if sobrantes > 0 {
+            self.pendientes.drain(..sobrantes);
+            self.descartadas += sobrantes as u64;
+        }
+    }
+
+    /// Envía lo pendiente si el lote está completo o pasó el intervalo de envío,
+    /// respetando la espera entre reintentos.
+    pub fn enviar_si_corresponde(&mut self) -> io::Result<()> {
+        if self.pendientes.is_empty() || self.proximo_intento.is_some_and(|t| Instant::now() < t) {
+            return Ok(());
+        }
+        if self.pendientes.len() >= LOTE || self.ultimo_envio.elapsed() >= INTERVALO_ENVIO || self.proximo_intento.is_some() {
+            return self.enviar();
+        }
+        Ok(())
+    }
+
+    /// Envía todo lo pendiente por lotes. Si falla, las líneas quedan en el
+    /// búfer y se programa el siguiente intento.
+    pub fn enviar(&mut self) -> io::Result<()> {
+        while !self.pendientes.is_empty() {
+            let cantidad = self.pendientes.len().min(LOTE);
+            let lote: Vec<&str> = self.pendientes.iter().take(cantidad).map(String::as_str).collect();
+            let conexion = match self.conexion.take() {
+                Some(conexion) => Ok(conexion),
+                None => conectar(&self.destino),
+            };
+            match conexion.and_then(|mut c| escribir_lote(&mut c, &lote).map(|_| c)) {
+                Ok(conexion) => self.conexion = Some(conexion),
+                Err(e) => {
+                    self.proximo_intento = Some(Instant::now() + self.espera);
+                    self.espera = (self.espera * 2).min(ESPERA_MAXIMA);
+                    return Err(e);
+                }
+            }
+            self.pendientes.drain(..cantidad);
+        }
+        self.ultimo_envio = Instant::now();
+        self.espera = ESPERA_INICIAL;
+        self.proximo_intento = None;
+        Ok(())
+    }
+}
+
+fn conectar(destino: &Destino) -> io::Result<Conexion> {
+    let resolver = |direccion: &str| {
+        direccion
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no se pudo resolver {}", direccion)))
+    };
+    match destino {
+        Destino::Udp(direccion) => {
+            let destino = resolver(direccion)?;
+            let socket = UdpSocket::bind(if destino.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" })?;
+            socket.connect(destino)?;
+            Ok(Conexion::Udp(socket))
+        }
+        Destino::Tcp(direccion) => {
+            let destino = resolver(direccion)?;
+            let stream = TcpStream::connect_timeout(&destino, TIEMPO_RED)?;
+            stream.set_write_timeout(Some(TIEMPO_RED))?;
+            Ok(Conexion::Tcp(stream))
+        }
+        Destino::Archivo(ruta) => {
+            if let Some(padre) = ruta.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(padre)?;
+            }
+            Ok(Conexion::Archivo(OpenOptions::new().create(true).append(true).open(ruta)?))
+        }
+    }
+}
+
+fn escribir_lote(conexion: &mut Conexion, lote: &[&str]) -> io::Result<()> {
+    match conexion {
+        Conexion::Udp(socket) => {
+            // varias líneas por datagrama sin pasar del tamaño máximo
+            let mut datagrama = String::new();
+            for linea in lote {
+                if !datagrama.is_empty() && datagrama.len() + linea.len() + 1 > MAXIMO_DATAGRAMA {
+                    socket.send(datagrama.as_bytes())?;
+                    datagrama.clear();
+                }
+                datagrama.push_str(linea);
+                datagrama.push('\n');
+            }
+            if !datagrama.is_empty() {
+                socket.send(datagrama.as_bytes())?;
+            }
+            Ok(())
+        }
+        Conexion::Tcp(stream) => escribir_lineas(stream, lote),
+        Conexion::Archivo(archivo) => {
+            // si el lote queda a medias se corta, para que el reintento no lo duplique
+            let largo = archivo.metadata()?.len();
+            escribir_lineas(archivo, lote).inspect_err(|_| {
+                let _ = archivo.set_len(largo);
+            })
+        }
+    }
+}
+
+fn escribir_lineas(escritor: &mut impl Write, lote: &[&str]) -> io::Result<()> {
+    let mut texto = lote.join("\n");
+    texto.push('\n');
+    escritor.write_all(texto.as_bytes())?;
+    escritor.flush()
+}
+
+/// Corre las salidas en un hilo propio y devuelve el emisor por el que se le
+/// pasan los snapshots. Al soltar el emisor se intenta un último envío y el
+/// hilo termina.
+pub fn iniciar(mut salidas: Vec<Salida>) -> Sender<Metric> {
+    let (emisor, receptor) = mpsc::channel::<Metric>();
+    thread::spawn(move || {
+        loop {
+            let terminar = match receptor.recv_timeout(ESPERA_INICIAL) {
+                Ok(metric) => {
+                    for salida in &mut salidas {
+                        salida.agregar(&metric);
+                    }
+                    false
+                }
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => true,
+            };
+            for salida in &mut salidas {
+                let resultado = if terminar { salida.enviar() } else { salida.enviar_si_corresponde() };
+                if let Err(e) = resultado {
+                    eprintln!(
+                        "No se pudo enviar a {:?} ({} líneas pendientes): {}",
+                        salida.get_destino(), salida.get_pendientes(), e
+                    );
+                }
+            }
+            if terminar {
+                break;
+            }
+        }
+    });
+    emisor
+}
+
+/// Nombre del equipo del snapshot, para etiquetar las líneas.
+fn equipo(metric: &Metric) -> &str {
+    metric.inventario().map_or("desconocido", |i| i.get_nombre_equipo())
+}
+
+/// Líneas del protocolo de InfluxDB, con marca de tiempo en nanosegundos.
+///
+/// Una medición por fuente (`cpu`, `memoria`, `disco`, `red`, `procesos`) con
+/// la etiqueta `host`; discos e interfaces llevan además su propia etiqueta.
+pub fn lineas_influx(metric: &Metric) -> Vec<String> {
+    let marca = metric.marca.saturating_mul(1_000_000);
+    let host = escapar_influx(equipo(metric));
+    let mut lineas = Vec::new();
+    if let Some(cpu) = metric.cpu() {
+        for (i, uso) in cpu.get_uso_nucleos().iter().enumerate() {
+            if let Some(campos) = campos_finitos(&[("uso", *uso as f64)]) {
+                lineas.push(format!("cpu,host={},nucleo={} {} {}", host, i, campos, marca));
+            }
+        }
+        lineas.push(format!("cpu,host={} frecuencia_mhz={}i {}", host, cpu.get_frecuencia(), marca));
+    }
+    if let Some(memoria) = metric.memoria() {
+        lineas.push(format!(
+            "memoria,host={} total_mb={}i,usada_mb={}i,libre_mb={}i,swap_total_mb={}i,swap_usada_mb={}i {}",
+            host,
+            memoria.get_total_ram(),
+            memoria.get_usada_ram(),
+            memoria.get_libre_ram(),
+            memoria.get_swap_total(),
+            memoria.get_swap_usada(),
+            marca
+        ));
+    }
+    if let Some(discos) = metric.disco() {
+        for disco in discos.get_discos() {
+            let campos = [
+                ("total_gb", disco.get_espacio_total()),
+                ("usado_gb", disco.get_espacio_usado()),
+                ("libre_gb", disco.get_espacio_libre()),
+            ];
+            if let Some(campos) = campos_finitos(&campos) {
+                lineas.push(format!(
+                    "disco,host={},dispositivo={},punto_montaje={} {} {}",
+                    host,
+                    escapar_influx(disco.get_nombre()),
+                    escapar_influx(disco.get_ruta()),
+                    campos,
+                    marca
+                ));
+            }
+        }
+    }
+    if let Some(red) = metric.red() {
+        for interfaz in red.get_interfaces() {
+            lineas.push(format!(
+                "red,host={},interfaz={} recibidos_bytes={}i,enviados_bytes={}i,errores={}i {}",
+                host,
+                escapar_influx(interfaz.get_nombre()),
+                interfaz.get_bytes_recibidos(),
+                interfaz.get_bytes_enviados(),
+                interfaz.get_total_errores(),
+                marca
+            ));
+        }
+    }
+    if let Some(procesos) = metric.procesos() {
+        lineas.push(format!(
+            "procesos,host={} cantidad={}i,sospechosos={}i {}",
+            host,
+            procesos.get_cantidad_procesos(),
+            procesos.get_procesos_sospechosos().len(),
+            marca
+        ));
+    }
+    lineas
+}
+
+// Campos de punto flotante sin los NaN ni infinitos; `None` si no queda ninguno
+fn campos_finitos(campos: &[(&str, f64)]) -> Option<String> {
+    let campos: Vec<String> = campos.iter().filter(|(_, v)| v.is_finite()).map(|(n, v)| format!("{}={}", n, v)).collect();
+    (!campos.is_empty()).then(|| campos.join(","))
+}
+
+// Escapa comas, signos de igual y espacios en nombres y valores de etiquetas
+fn escapar_influx(texto: &str) -> String {
+    let mut salida = String::with_capacity(texto.len());
+    for c in texto.chars() {
+        if matches!(c, ',' | '=' | ' ' | '\\') {
+            salida.push('\\');
+        }
+        salida.push(c);
+    }
+    salida
+}
+
+/// Líneas del texto plano de Graphite (`ruta valor marca_en_segundos`).
+///
+/// Las rutas son `sistema_defensa.<host>.<fuente>[.<elemento>].<campo>`.
+pub fn lineas_graphite(metric: &Metric) -> Vec<String> {
+    let marca = metric.marca / 1000;
+    let base = format!("{}.{}", PREFIJO_GRAPHITE, componente_graphite(equipo(metric)));
+    let mut texto = String::new();
+    let mut linea = |ruta: &str, valor: f64| {
+        if valor.is_finite() {
+            let _ = writeln!(texto, "{}.{} {} {}", base, ruta, valor, marca);
+        }
+    };
+    if let Some(cpu) = metric.cpu() {
+        for (i, uso) in cpu.get_uso_nucleos().iter().enumerate() {
+            linea(&format!("cpu.nucleo{}.uso", i), *uso as f64);
+        }
+        linea("cpu.frecuencia_mhz", cpu.get_frecuencia() as f64);
+    }
+    if let Some(memoria) = metric.memoria() {
+        linea("memoria.total_mb", memoria.get_total_ram() as f64);
+        linea("memoria.usada_mb", memoria.get_usada_ram() as f64);
+        linea("memoria.libre_mb", memoria.get_libre_ram() as f64);
+        linea("memoria.swap_total_mb", memoria.get_swap_total() as f64);
+        linea("memoria.swap_usada_mb", memoria.get_swap_usada() as f64);
+    }
+    if let Some(discos) = metric.disco() {
+        for disco in discos.get_discos() {
+            let punto = componente_graphite(disco.get_ruta());
+            linea(&format!("disco.{}.total_gb", punto), disco.get_espacio_total());
+            linea(&format!("disco.{}.usado_gb", punto), disco.get_espacio_usado());
+            linea(&format!("disco.{}.libre_gb", punto), disco.get_espacio_libre());
+        }
+    }
+    if let Some(red) = metric.red() {
+        for interfaz in red.get_interfaces() {
+            let nombre = componente_graphite(interfaz.get_nombre());
+            linea(&format!("red.{}.recibidos_bytes", nombre), interfaz.get_bytes_recibidos() as f64);
+            linea(&format!("red.{}.enviados_bytes", nombre), interfaz.get_bytes_enviados() as f64);
+            linea(&format!("red.{}.errores", nombre), interfaz.get_total_errores() as f64);
+        }
+    }
+    if let Some(procesos) = metric.procesos() {
+        linea("procesos.cantidad", procesos.get_cantidad_procesos() as f64);
+        linea("procesos.sospechosos", procesos.get_procesos_sospechosos().len() as f64);
+    }
+    texto.lines().map(str::to_string).collect()
+}
+
+// Un componente de ruta de Graphite no puede tener puntos ni espacios
+fn componente_graphite(texto: &str) -> String {
+    let limpio: String = texto
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    if limpio.is_empty() { "_".to_string() } else { limpio }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use serde_json::json;
+
+    // Snapshot con CPU, memoria, un disco e inventario, tomado el 2023-11-14 a las 22:13:20 UTC
+    fn snapshot() -> Metric {
+        serde_json::from_value(json!({
+            "id": "prueba",
+            "marca": 1_700_000_000_000i64,
+            "lecturas": {
+                "cpu": { "tipo": "cpu", "datos": { "brand": "x", "cantidad_nucleos": 2, "frecuencia": 2400, "uso_nucleos": [12.5, 50.0] } },
+                "memoria": { "tipo": "memoria", "datos": {
+                    "total": 3072, "libre": 2048, "usada": 1024, "total_ram": 2048, "libre_ram": 1536, "usada_ram": 512,
+                    "swap_total": 1024, "swap_libre": 512, "swap_usada": 512
+                } },
+                "disco": { "tipo": "disco", "datos": {
+                    "cantidad_discos": 1, "espacio_total": 100.0, "espacio_libre": 40.0, "espacio_usado": 60.0,
+                    "discos": [{
+                        "nombre": "/dev/sda1", "sistema_archivos": "ext4", "espacio_total": 100.0, "espacio_libre": 40.0,
+                        "espacio_usado": 60.0, "ruta": "/mnt/datos 1", "removible": false, "solo_lectura": false
+                    }]
+                } },
+                "inventario": { "tipo": "inventario", "datos": {
+                    "nombre_equipo": "web 01", "sistema_operativo": "Linux", "version_kernel": "6.1", "arquitectura": "x86_64",
+                    "modelo_cpu": "x", "nucleos_fisicos": 2, "nucleos_logicos": 2, "memoria_total": 2048, "swap_total": 1024, "arranque": 0
+                } }
+            },
+            "marcas": {},
+            "errores": {}
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn influx_escapa_etiquetas_y_marca_en_nanosegundos() {
+        let lineas = lineas_influx(&snapshot());
+        assert!(lineas.contains(&r"cpu,host=web\ 01,nucleo=0 uso=12.5 1700000000000000000".to_string()));
+        assert!(lineas.contains(&r"cpu,host=web\ 01 frecuencia_mhz=2400i 1700000000000000000".to_string()));
+        assert!(lineas.contains(
+            &r"memoria,host=web\ 01 total_mb=2048i,usada_mb=512i,libre_mb=1536i,swap_total_mb=1024i,swap_usada_mb=512i 1700000000000000000"
+                .to_string()
+        ));
+        assert!(lineas.contains(
+            &r"disco,host=web\ 01,dispositivo=/dev/sda1,punto_montaje=/mnt/datos\ 1 total_gb=100,usado_gb=60,libre_gb=40 1700000000000000000"
+                .to_string()
+        ));
+    }
+
+    #[test]
+    fn escapar_influx_antepone_barra() {
+        assert_eq!(escapar_influx(r"a,b=c d\e"), r"a\,b\=c\ d\\e");
+    }
+
+    #[test]
+    fn graphite_limpia_componentes_y_marca_en_segundos() {
+        let lineas = lineas_graphite(&snapshot());
+        assert!(lineas.contains(&"sistema_defensa.web_01.cpu.nucleo1.uso 50 1700000000".to_string()));
+        assert!(lineas.contains(&"sistema_defensa.web_01.memoria.usada_mb 512 1700000000".to_string()));
+        assert!(lineas.contains(&"sistema_defensa.web_01.disco._mnt_datos_1.libre_gb 40 1700000000".to_string()));
+        assert!(lineas.iter().all(|l| l.split(' ').count() == 3));
+    }
+
+    #[test]
+    fn se_omiten_valores_no_finitos() {
+        assert_eq!(campos_finitos(&[("a", f64::NAN), ("b", 1.5), ("c", f64::INFINITY)]), Some("b=1.5".to_string()));
+        assert_eq!(campos_finitos(&[("a", f64::NAN), ("c", f64::NEG_INFINITY)]), None);
+    }
+
+    #[test]
+    fn reenvia_por_tcp_lo_que_no_se_pudo_enviar() {
+        // un puerto que nadie escucha hasta la segunda vuelta
+        let direccion = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut salida = Salida::new(Formato::Influx, Destino::Tcp(direccion.to_string()));
+        salida.agregar(&snapshot());
+        let esperadas = lineas_influx(&snapshot());
+        assert_eq!(salida.get_pendientes(), esperadas.len());
+        assert!(salida.enviar().is_err());
+        assert_eq!(salida.get_pendientes(), esperadas.len());
+        assert!(salida.proximo_intento.is_some());
+
+        let listener = TcpListener::bind(direccion).unwrap();
+        let receptor = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut texto = String::new();
+            stream.read_to_string(&mut texto).unwrap();
+            texto
+        });
+        salida.enviar().unwrap();
+        assert_eq!(salida.get_pendientes(), 0);
+        assert!(salida.proximo_intento.is_none());
+        drop(salida); // cierra la conexión para que el receptor termine de leer
+        let recibidas: Vec<String> = receptor.join().unwrap().lines().map(str::to_string).collect();
+        assert_eq!(recibidas, esperadas);
+    }
+
+    #[test]
+    fn archivo_recibe_cada_lote_una_vez() {
+        let ruta = std::env::temp_dir().join(format!("sistema_defensa_salidas_{}.txt", std::process::id()));
+        let _ = fs::remove_file(&ruta);
+        let mut salida = Salida::new(Formato::Graphite, Destino::Archivo(ruta.clone()));
+        salida.agregar(&snapshot());
+        salida.enviar().unwrap();
+        salida.agregar(&snapshot());
+        salida.enviar().unwrap();
+        let texto = fs::read_to_string(&ruta).unwrap();
+        fs::remove_file(&ruta).unwrap();
+        assert_eq!(texto.lines().count(), 2 * lineas_graphite(&snapshot()).len());
+        assert!(texto.ends_with('\n'));
+    }
+}
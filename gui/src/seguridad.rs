@@ -0,0 +1,502 @@
+//! TLS y autenticación por token para las interfaces de red (API HTTP,
+//! `/metrics` y agentes de la flota).
+//!
+//! TLS usa rustls con el proveedor criptográfico `ring`. Con `ca_clientes` el
+//! servidor exige además un certificado de cliente firmado por esa CA (TLS
+//! mutuo). Los tokens autorizan: cada uno tiene un `Alcance`, y sin archivo
+//! de tokens las rutas de lectura quedan abiertas y las de control deshabilitadas.
+//! Por eso, sin tokens un servidor solo puede escuchar en una dirección de
+//! loopback, salvo que el operador lo permita con `sin_autenticacion`.
+//!
+//! Archivo de tokens: una línea por cliente, `nombre alcance token`; las
+//! líneas vacías y las que empiezan con `#` se ignoran.
+//!
+//! ```text
+//! grafana   lectura  3f1c9a0e7b2d4c6f8a1e5b7d9c3f2a4e
+//! operador  control  9b8e7d6c5f4a3b2c1d0e9f8a7b6c5d4e
+//! web-01    agente   5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d
+//! ```
+//!
+//! - `OpcionesSeguridad`: Rutas de certificado, clave, CA de clientes y tokens.
+//! - `Seguridad`: Configuración cargada de un servidor; acepta conexiones.
+//! - `OpcionesCliente` / `conectar`: Lado cliente (agentes) con TLS y token.
+//! - `Flujo`: Conexión TCP plana o TLS.
+//! - `Alcance` / `Tokens` / `Denegado`: Autorización por token.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use rustls::crypto::{ring, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, ServerConfig, ServerConnection, StreamOwned};
+
+/// Longitud mínima de un token, para que no se pueda adivinar.
+const LONGITUD_MINIMA_TOKEN: usize = 16;
+
+/// Opciones de seguridad de un servidor; todas opcionales.
+///
+/// - `certificado` y `clave`: Cadena de certificados y clave privada en PEM; activan TLS.
+/// - `ca_clientes`: CA en PEM que firma los certificados de cliente; activa TLS mutuo.
+/// - `tokens`: Archivo de tokens (ver el módulo).
+/// - `sin_autenticacion`: Permite escuchar sin tokens fuera de loopback (ver `Seguridad::permitir_escucha`).
+#[derive(Debug, Clone, Default)]
+pub struct OpcionesSeguridad {
+    pub certificado: Option<PathBuf>,
+    pub clave: Option<PathBuf>,
+    pub ca_clientes: Option<PathBuf>,
+    pub tokens: Option<PathBuf>,
+    pub sin_autenticacion: bool,
+}
+
+/// Configuración de seguridad cargada, compartida entre los hilos de un servidor.
+#[derive(Clone, Default)]
+pub struct Seguridad {
+    tls: Option<Arc<ServerConfig>>,
+    tokens: Tokens,
+    sin_autenticacion: bool,
+}
+
+impl Seguridad {
+    /// Lee los certificados y el archivo de tokens.
+    ///
+    /// Falla si falta la clave o el certificado del otro, o si `ca_clientes`
+    /// se da sin TLS: un error de configuración no debe dejar el servidor abierto.
+    pub fn cargar(opciones: &OpcionesSeguridad) -> io::Result<Self> {
+        let tls = match (&opciones.certificado, &opciones.clave) {
+            (Some(certificado), Some(clave)) => Some(Arc::new(configuracion_servidor(certificado, clave, opciones.ca_clientes.as_deref())?)),
+            (None, None) if opciones.ca_clientes.is_some() => return Err(invalida("la CA de clientes requiere certificado y clave del servidor")),
+            (None, None) => None,
+            _ => return Err(invalida("TLS necesita certificado y clave")),
+        };
+        let tokens = match &opciones.tokens {
+            Some(ruta) => Tokens::cargar(ruta)?,
+            None => Tokens::default(),
+        };
+        Ok(Seguridad { tls, tokens, sin_autenticacion: opciones.sin_autenticacion })
+    }
+
+    pub fn get_tls(&self) -> bool {
+        self.tls.is_some()
+    }
+
+    pub fn get_tokens(&self) -> &Tokens {
+        &self.tokens
+    }
+
+    /// Comprueba que un servidor pueda escuchar en `direccion` con esta configuración.
+    ///
+    /// Sin tokens cualquiera que llegue al puerto lee los procesos o se presenta
+    /// como agente, así que fuera de loopback hace falta `sin_autenticacion`.
+    ///
+    /// # Retorno
+    /// `io::ErrorKind::PermissionDenied` si la dirección no es de loopback, no hay
+    /// tokens y el operador no permitió escuchar sin autenticación.
+    pub fn permitir_escucha(&self, direccion: &SocketAddr) -> io::Result<()> {
+        if self.tokens.activos() || self.sin_autenticacion || direccion.ip().is_loopback() {
+            return Ok(());
+        }
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "{} no es una dirección de loopback y no hay archivo de tokens; configurar tokens o permitir explícitamente el acceso sin autenticación",
+                direccion
+            ),
+        ))
+    }
+
+    /// Envuelve una conexión aceptada en TLS si está configurado.
+    ///
+    /// El handshake ocurre en la primera lectura o escritura, así que un
+    /// cliente que no presenta certificado falla ahí y no aquí.
+    pub fn aceptar(&self, stream: TcpStream) -> io::Result<Flujo> {
+        match &self.tls {
+            Some(configuracion) => {
+                let conexion = ServerConnection::new(Arc::clone(configuracion)).map_err(io::Error::other)?;
+                Ok(Flujo::Servidor(Box::new(StreamOwned::new(conexion, stream))))
+            }
+            None => Ok(Flujo::Plano(stream)),
+        }
+    }
+}
+
+/// Opciones de un cliente (agente) hacia un servidor.
+///
+/// - `ca`: CA en PEM que firma el certificado del servidor; activa TLS.
+/// - `certificado` y `clave`: Certificado de cliente para TLS mutuo.
+/// - `token`: Token que se presenta al servidor.
+#[derive(Debug, Clone, Default)]
+pub struct OpcionesCliente {
+    pub ca: Option<PathBuf>,
+    pub certificado: Option<PathBuf>,
+    pub clave: Option<PathBuf>,
+    pub token: Option<String>,
+}
+
+/// Conecta a `direccion` (`host:puerto`), con TLS si `opciones.ca` está definido.
+///
+/// El certificado del servidor debe ser válido para `host` (nombre o IP).
+pub fn conectar(direccion: &str, opciones: &OpcionesCliente) -> io::Result<Flujo> {
+    let stream = TcpStream::connect(direccion)?;
+    let Some(ca) = &opciones.ca else {
+        if opciones.certificado.is_some() {
+            return Err(invalida("el certificado de cliente requiere TLS (falta la CA del servidor)"));
+        }
+        return Ok(Flujo::Plano(stream));
+    };
+    let host = direccion.rsplit_once(':').map_or(direccion, |(host, _)| host);
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let nombre = ServerName::try_from(host.to_string()).map_err(|e| invalida(&format!("{}: {}", host, e)))?;
+    let configuracion = ClientConfig::builder_with_provider(proveedor())
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?
+        .with_root_certificates(raices(ca)?);
+    let configuracion = match (&opciones.certificado, &opciones.clave) {
+        (Some(certificado), Some(clave)) => configuracion
+            .with_client_auth_cert(leer_certificados(certificado)?, leer_clave(clave)?)
+            .map_err(|e| invalida(&e.to_string()))?,
+        (None, None) => configuracion.with_no_client_auth(),
+        _ => return Err(invalida("el certificado de cliente necesita certificado y clave")),
+    };
+    let conexion = ClientConnection::new(Arc::new(configuracion), nombre).map_err(io::Error::other)?;
+    Ok(Flujo::Cliente(Box::new(StreamOwned::new(conexion, stream))))
+}
+
+/// Conexión TCP, plana o cifrada.
+pub enum Flujo {
+    Plano(TcpStream),
+    Servidor(Box<StreamOwned<ServerConnection, TcpStream>>),
+    Cliente(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl Flujo {
+    /// Socket TCP subyacente, para ajustar tiempos de espera.
+    pub fn get_tcp(&self) -> &TcpStream {
+        match self {
+            Flujo::Plano(stream) => stream,
+            Flujo::Servidor(tls) => &tls.sock,
+            Flujo::Cliente(tls) => &tls.sock,
+        }
+    }
+
+    /// Indica si el handshake TLS no terminó (en TCP plano, nunca).
+    pub fn negociando(&self) -> bool {
+        match self {
+            Flujo::Plano(_) => false,
+            Flujo::Servidor(tls) => tls.conn.is_handshaking(),
+            Flujo::Cliente(tls) => tls.conn.is_handshaking(),
+        }
+    }
+
+    /// Avisa el cierre (`close_notify`) en TLS; en TCP plano no hace nada.
+    pub fn cerrar(&mut self) -> io::Result<()> {
+        match self {
+            Flujo::Plano(_) => return Ok(()),
+            Flujo::Servidor(tls) => tls.conn.send_close_notify(),
+            Flujo::Cliente(tls) => tls.conn.send_close_notify(),
+        }
+        self.flush()
+    }
+}
+
+impl Read for Flujo {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Flujo::Plano(stream) => stream.read(buf),
+            Flujo::Servidor(tls) => tls.read(buf),
+            Flujo::Cliente(tls) => tls.read(buf),
+        }
+    }
+}
+
+impl Write for Flujo {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Flujo::Plano(stream) => stream.write(buf),
+            Flujo::Servidor(tls) => tls.write(buf),
+            Flujo::Cliente(tls) => tls.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Flujo::Plano(stream) => stream.flush(),
+            Flujo::Servidor(tls) => tls.flush(),
+            Flujo::Cliente(tls) => tls.flush(),
+        }
+    }
+}
+
+/// Qué puede hacer el portador de un token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alcance {
+    /// Consultar snapshots, historial, alertas y `/metrics`.
+    Lectura,
+    /// Lo mismo que `Lectura` y además acciones (enviar señales a procesos).
+    Control,
+    /// Solo enviar snapshots como agente de la flota.
+    Agente,
+}
+
+impl Alcance {
+    pub fn nombre(self) -> &'static str {
+        match self {
+            Alcance::Lectura => "lectura",
+            Alcance::Control => "control",
+            Alcance::Agente => "agente",
+        }
+    }
+
+    /// Indica si este alcance alcanza para una operación que requiere `requerido`.
+    pub fn permite(self, requerido: Alcance) -> bool {
+        self == requerido || (self == Alcance::Control && requerido == Alcance::Lectura)
+    }
+}
+
+/// Por qué se rechazó un token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Denegado {
+    /// No se presentó un token o no corresponde a ningún cliente (HTTP 401).
+    NoAutenticado,
+    /// El token es válido pero su alcance no alcanza (HTTP 403).
+    SinPermiso,
+    /// La operación requiere tokens y el servidor no tiene ninguno configurado (HTTP 403).
+    Deshabilitado,
+}
+
+impl fmt::Display for Denegado {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Denegado::NoAutenticado => write!(f, "token ausente o inválido"),
+            Denegado::SinPermiso => write!(f, "el token no tiene el alcance necesario"),
+            Denegado::Deshabilitado => write!(f, "operación deshabilitada: el servidor no tiene tokens configurados"),
+        }
+    }
+}
+
+/// Tokens de los clientes autorizados.
+#[derive(Debug, Clone, Default)]
+pub struct Tokens {
+    clientes: Vec<(String, Alcance, String)>, // (nombre, alcance, token)
+}
+
+impl Tokens {
+    /// Lee el archivo de tokens; los errores indican la línea.
+    pub fn cargar(ruta: &Path) -> io::Result<Self> {
+        let texto = fs::read_to_string(ruta).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", ruta.display(), e)))?;
+        let mut clientes = Vec::new();
+        for (i, linea) in texto.lines().enumerate() {
+            let linea = linea.trim();
+            if linea.is_empty() || linea.starts_with('#') {
+                continue;
+            }
+            let error = |mensaje: &str| invalida(&format!("{}:{}: {}", ruta.display(), i + 1, mensaje));
+            let partes: Vec<&str> = linea.split_whitespace().collect();
+            let [nombre, alcance, token] = partes[..] else {
+                return Err(error("se esperaba `nombre alcance token`"));
+            };
+            let alcance = match alcance {
+                "lectura" => Alcance::Lectura,
+                "control" => Alcance::Control,
+                "agente" => Alcance::Agente,
+                otro => return Err(error(&format!("alcance desconocido '{}'; usar lectura, control o agente", otro))),
+            };
+            if token.len() < LONGITUD_MINIMA_TOKEN {
+                return Err(error(&format!("el token de {} debe tener al menos {} caracteres", nombre, LONGITUD_MINIMA_TOKEN)));
+            }
+            if clientes.iter().any(|(_, _, t): &(String, Alcance, String)| t == token) {
+                return Err(error(&format!("el token de {} está repetido", nombre)));
+            }
+            clientes.push((nombre.to_string(), alcance, token.to_string()));
+        }
+        Ok(Tokens { clientes })
+    }
+
+    /// Indica si hay tokens configurados (si no, la autorización está desactivada).
+    pub fn activos(&self) -> bool {
+        !self.clientes.is_empty()
+    }
+
+    /// Verifica `token` para una operación que requiere `requerido`.
+    ///
+    /// Sin tokens configurados, la lectura y el envío de agentes se permiten
+    /// y el control se rechaza.
+    ///
+    /// # Retorno
+    /// El nombre del cliente (vacío si la autorización está desactivada).
+    pub fn autorizar(&self, token: Option<&str>, requerido: Alcance) -> Result<&str, Denegado> {
+        if !self.activos() {
+            return if requerido == Alcance::Control { Err(Denegado::Deshabilitado) } else { Ok("") };
+        }
+        let token = token.ok_or(Denegado::NoAutenticado)?;
+        // se comparan todos para que el tiempo de respuesta no delate cuál coincide
+        let mut encontrado = None;
+        for (nombre, alcance, esperado) in &self.clientes {
+            if iguales(esperado.as_bytes(), token.as_bytes()) {
+                encontrado = Some((nombre.as_str(), *alcance));
+            }
+        }
+        match encontrado {
+            Some((nombre, alcance)) if alcance.permite(requerido) => Ok(nombre),
+            Some(_) => Err(Denegado::SinPermiso),
+            None => Err(Denegado::NoAutenticado),
+        }
+    }
+}
+
+// Comparación en tiempo constante respecto del contenido
+fn iguales(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn proveedor() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+fn configuracion_servidor(certificado: &Path, clave: &Path, ca_clientes: Option<&Path>) -> io::Result<ServerConfig> {
+    let constructor = ServerConfig::builder_with_provider(proveedor())
+        .with_safe_default_protocol_versions()
+        .map_err(io::Error::other)?;
+    let constructor = match ca_clientes {
+        Some(ca) => {
+            let verificador = WebPkiClientVerifier::builder_with_provider(Arc::new(raices(ca)?), proveedor())
+                .build()
+                .map_err(|e| invalida(&format!("{}: {}", ca.display(), e)))?;
+            constructor.with_client_cert_verifier(verificador)
+        }
+        None => constructor.with_no_client_auth(),
+    };
+    constructor
+        .with_single_cert(leer_certificados(certificado)?, leer_clave(clave)?)
+        .map_err(|e| invalida(&format!("{}: {}", certificado.display(), e)))
+}
+
+fn raices(ca: &Path) -> io::Result<RootCertStore> {
+    let mut raices = RootCertStore::empty();
+    for certificado in leer_certificados(ca)? {
+        raices.add(certificado).map_err(|e| invalida(&format!("{}: {}", ca.display(), e)))?;
+    }
+    Ok(raices)
+}
+
+fn leer_certificados(ruta: &Path) -> io::Result<Vec<CertificateDer<'static>>> {
+    let certificados = CertificateDer::pem_file_iter(ruta)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| invalida(&format!("{}: {}", ruta.display(), e)))?;
+    if certificados.is_empty() {
+        return Err(invalida(&format!("{}: no contiene certificados PEM", ruta.display())));
+    }
+    Ok(certificados)
+}
+
+fn leer_clave(ruta: &Path) -> io::Result<PrivateKeyDer<'static>> {
+    PrivateKeyDer::from_pem_file(ruta).map_err(|e| invalida(&format!("{}: {}", ruta.display(), e)))
+}
+
+fn invalida(mensaje: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, mensaje.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LECTOR: &str = "3f1c9a0e7b2d4c6f8a1e5b7d9c3f2a4e";
+    const OPERADOR: &str = "9b8e7d6c5f4a3b2c1d0e9f8a7b6c5d4e";
+    const AGENTE: &str = "5a4b3c2d1e0f9a8b7c6d5e4f3a2b1c0d";
+
+    // Escribe `texto` en un archivo de tokens propio de la prueba y lo carga
+    fn cargar(nombre: &str, texto: &str) -> io::Result<Tokens> {
+        let ruta = std::env::temp_dir().join(format!("sistema_defensa-tokens-{}-{}", nombre, std::process::id()));
+        fs::write(&ruta, texto).unwrap();
+        let tokens = Tokens::cargar(&ruta);
+        fs::remove_file(&ruta).unwrap();
+        tokens
+    }
+
+    fn error(nombre: &str, texto: &str) -> String {
+        let error = cargar(nombre, texto).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        error.to_string()
+    }
+
+    #[test]
+    fn carga_un_cliente_por_linea_ignorando_comentarios() {
+        let texto = format!("# clientes\n\ngrafana lectura {}\n  operador\tcontrol  {}  \nweb-01 agente {}\n", LECTOR, OPERADOR, AGENTE);
+        let tokens = cargar("validos", &texto).unwrap();
+        assert!(tokens.activos());
+        assert_eq!(tokens.autorizar(Some(LECTOR), Alcance::Lectura), Ok("grafana"));
+        assert_eq!(tokens.autorizar(Some(OPERADOR), Alcance::Control), Ok("operador"));
+        assert_eq!(tokens.autorizar(Some(AGENTE), Alcance::Agente), Ok("web-01"));
+    }
+
+    #[test]
+    fn archivo_sin_clientes_desactiva_la_autorizacion() {
+        let tokens = cargar("vacio", "# nadie\n\n").unwrap();
+        assert!(!tokens.activos());
+        assert_eq!(tokens.autorizar(None, Alcance::Lectura), Ok(""));
+        assert_eq!(tokens.autorizar(None, Alcance::Control), Err(Denegado::Deshabilitado));
+    }
+
+    #[test]
+    fn errores_indican_la_linea() {
+        let mensaje = error("campos", &format!("grafana lectura {}\noperador control\n", LECTOR));
+        assert!(mensaje.contains(":2: se esperaba `nombre alcance token`"), "{}", mensaje);
+
+        let mensaje = error("alcance", &format!("# x\ngrafana escritura {}\n", LECTOR));
+        assert!(mensaje.contains(":2: alcance desconocido 'escritura'"), "{}", mensaje);
+
+        let mensaje = error("corto", "grafana lectura corto\n");
+        assert!(mensaje.contains(":1: el token de grafana debe tener al menos 16 caracteres"), "{}", mensaje);
+
+        let mensaje = error("repetido", &format!("a lectura {}\nb control {}\n", LECTOR, LECTOR));
+        assert!(mensaje.contains(":2: el token de b está repetido"), "{}", mensaje);
+    }
+
+    #[test]
+    fn archivo_inexistente_indica_la_ruta() {
+        let ruta = std::env::temp_dir().join("sistema_defensa-tokens-no-existe");
+        let error = Tokens::cargar(&ruta).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(error.to_string().starts_with(&ruta.display().to_string()));
+    }
+
+    fn seguridad(tokens: Tokens, sin_autenticacion: bool) -> Seguridad {
+        Seguridad { tls: None, tokens, sin_autenticacion }
+    }
+
+    #[test]
+    fn sin_tokens_solo_se_escucha_en_loopback() {
+        let sin_tokens = seguridad(Tokens::default(), false);
+        for direccion in ["127.0.0.1:9100", "[::1]:9100", "127.0.0.2:0"] {
+            assert!(sin_tokens.permitir_escucha(&direccion.parse().unwrap()).is_ok(), "{}", direccion);
+        }
+        for direccion in ["0.0.0.0:9200", "[::]:9200", "10.0.0.5:9100"] {
+            let error = sin_tokens.permitir_escucha(&direccion.parse().unwrap()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::PermissionDenied, "{}", direccion);
+        }
+    }
+
+    #[test]
+    fn fuera_de_loopback_hacen_falta_tokens_o_permiso_explicito() {
+        let direccion = "0.0.0.0:9200".parse().unwrap();
+        assert!(seguridad(Tokens::default(), true).permitir_escucha(&direccion).is_ok());
+        let tokens = cargar("escucha", &format!("grafana lectura {}\n", LECTOR)).unwrap();
+        assert!(seguridad(tokens, false).permitir_escucha(&direccion).is_ok());
+    }
+
+    #[test]
+    fn autorizar_respeta_el_alcance() {
+        let texto = format!("grafana lectura {}\noperador control {}\nweb-01 agente {}\n", LECTOR, OPERADOR, AGENTE);
+        let tokens = cargar("alcance", &texto).unwrap();
+        assert_eq!(tokens.autorizar(Some(OPERADOR), Alcance::Lectura), Ok("operador"));
+        assert_eq!(tokens.autorizar(Some(LECTOR), Alcance::Control), Err(Denegado::SinPermiso));
+        assert_eq!(tokens.autorizar(Some(AGENTE), Alcance::Lectura), Err(Denegado::SinPermiso));
+        assert_eq!(tokens.autorizar(Some("otro-token-que-no-existe"), Alcance::Lectura), Err(Denegado::NoAutenticado));
+        assert_eq!(tokens.autorizar(None, Alcance::Lectura), Err(Denegado::NoAutenticado));
+    }
+}
@@ -1,3 +1,8 @@
 pub mod panel_metricas;
 pub mod panel_alertas;
-pub mod panel_graficas;
\ No newline at end of file
+pub mod panel_graficas;
+pub mod panel_procesos;
+pub mod panel_arbol_procesos;
+pub mod panel_cgroups;
+pub mod panel_flota;
+pub mod panel_configuracion;
@@ -1,5 +1,6 @@
 use eframe::egui;
 use std::sync::{Arc, Mutex};
+use crate::alertas::{self, Umbrales};
 use crate::metrics::Metrics;
 
 pub struct PanelAlertas;
@@ -27,30 +28,9 @@ impl PanelAlertas {
 
         if let Ok(metrics_guard) = metrics.try_lock() {
             if let Some(metric) = metrics_guard.latest() {
-                // CPU alert
-                let mut sum = 0.0;
-                for usage in metric.cpu.get_uso_nucleos() {
-                    sum += usage;
-                }
-                let cpu_usage = (sum / metric.cpu.get_cantidad_nucleos() as f32) as f64;
-                if cpu_usage > *cpu_alerta_limite {
-                    ui.colored_label(
-                        egui::Color32::RED,
-                        format!("ALERTA: CPU usada {:.1}% excede el limite", cpu_usage),
-                    );
-                }
-
-                // Memory alert
-                let mem_used = metric.memoria.get_memoria_usada() as f64;
-                let mem_total = metric.memoria.get_memoria_total() as f64;
-                if mem_total > 0.0 {
-                    let mem_usage = (mem_used / mem_total) * 100.0;
-                    if mem_usage > *mem_alerta_limite {
-                        ui.colored_label(
-                            egui::Color32::RED,
-                            format!("ALERTA: Memoria usada {:.1}% excede el limite", mem_usage),
-                        );
-                    }
+                let umbrales = Umbrales { cpu: *cpu_alerta_limite, memoria: *mem_alerta_limite };
+                for alerta in alertas::evaluar(metric, &umbrales) {
+                    ui.colored_label(egui::Color32::RED, alerta.to_string());
                 }
             }
         }
@@ -59,4 +39,4 @@ impl PanelAlertas {
             ui.label(log);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,220 @@
+use eframe::egui;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use instrumentos::procesos::ProcesoInfo;
+use crate::metrics::Metrics;
+
+/// Uso acumulado de un proceso y todos sus descendientes.
+#[derive(Clone, Copy, Default)]
+struct Agregado {
+    uso_cpu: f32,
+    uso_memoria: u64,
+    descendientes: usize,
+}
+
+/// Árbol padre/hijo de los procesos del último muestreo.
+struct Arbol<'a> {
+    por_pid: HashMap<i32, &'a ProcesoInfo>,
+    hijos: HashMap<i32, Vec<i32>>,
+    raices: Vec<i32>,
+    agregados: HashMap<i32, Agregado>,
+}
+
+impl<'a> Arbol<'a> {
+    fn construir(procesos: &'a [ProcesoInfo]) -> Self {
+        let por_pid: HashMap<i32, &ProcesoInfo> = procesos.iter().map(|p| (p.get_pid(), p)).collect();
+        let mut hijos: HashMap<i32, Vec<i32>> = HashMap::new();
+        let mut raices = Vec::new();
+        for proceso in procesos {
+            // un padre que no aparece en la lista (o el propio pid) se trata como raíz
+            match proceso.get_ppid() {
+                Some(ppid) if ppid != proceso.get_pid() && por_pid.contains_key(&ppid) => {
+                    hijos.entry(ppid).or_default().push(proceso.get_pid());
+                }
+                _ => raices.push(proceso.get_pid()),
+            }
+        }
+        raices.sort();
+        for lista in hijos.values_mut() {
+            lista.sort();
+        }
+
+        let mut arbol = Arbol { por_pid, hijos, raices, agregados: HashMap::new() };
+        let mut pila: Vec<(i32, bool)> = arbol.raices.iter().map(|&pid| (pid, false)).collect();
+        // recorrido postorden iterativo para no depender de la profundidad del árbol
+        while let Some((pid, visitado)) = pila.pop() {
+            if visitado {
+                let proceso = arbol.por_pid[&pid];
+                let mut agregado = Agregado {
+                    uso_cpu: proceso.get_uso_cpu(),
+                    uso_memoria: proceso.get_uso_memoria(),
+                    descendientes: 0,
+                };
+                for hijo in arbol.hijos.get(&pid).into_iter().flatten() {
+                    let h = arbol.agregados[hijo];
+                    agregado.uso_cpu += h.uso_cpu;
+                    agregado.uso_memoria += h.uso_memoria;
+                    agregado.descendientes += h.descendientes + 1;
+                }
+                arbol.agregados.insert(pid, agregado);
+            } else {
+                pila.push((pid, true));
+                for hijo in arbol.hijos.get(&pid).into_iter().flatten() {
+                    pila.push((*hijo, false));
+                }
+            }
+        }
+        arbol
+    }
+
+    fn ancestros(&self, pid: i32) -> HashSet<i32> {
+        let mut ancestros = HashSet::new();
+        let mut actual = self.por_pid.get(&pid).and_then(|p| p.get_ppid());
+        while let Some(ppid) = actual {
+            if !self.por_pid.contains_key(&ppid) || !ancestros.insert(ppid) {
+                break;
+            }
+            actual = self.por_pid[&ppid].get_ppid();
+        }
+        ancestros
+    }
+}
+
+/// Vista de árbol de procesos por relación padre/hijo, con el uso de CPU y
+/// memoria agregado por subárbol.
+///
+/// Al seleccionar un nodo se resaltan (y expanden) todos sus ancestros para
+/// seguir la cadena de lanzamiento del proceso.
+pub struct PanelArbolProcesos {
+    seleccionado: Option<i32>,
+    expandir_ancestros: bool,
+}
+
+impl PanelArbolProcesos {
+    pub fn new() -> Self {
+        Self { seleccionado: None, expandir_ancestros: false }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, metrics: &Arc<Mutex<Metrics>>) {
+        ui.heading("Árbol de Procesos");
+
+        let procesos = match metrics.lock() {
+            Ok(metrics_guard) => match metrics_guard.latest() {
+                Some(metric) => metric.procesos().map(|p| p.get_procesos().clone()).unwrap_or_default(),
+                None => {
+                    ui.label("No metrics available.");
+                    return;
+                }
+            },
+            Err(_) => return,
+        };
+        let arbol = Arbol::construir(&procesos);
+        // la expansión pedida al seleccionar se aplica en el cuadro siguiente
+        let expandir = std::mem::take(&mut self.expandir_ancestros);
+
+        let ancestros = match self.seleccionado {
+            Some(pid) => {
+                match arbol.por_pid.get(&pid) {
+                    Some(proceso) => {
+                        let cadena = self.cadena_lanzamiento(&arbol, proceso);
+                        ui.label(format!("Cadena de lanzamiento: {}", cadena));
+                    }
+                    None => {
+                        ui.colored_label(egui::Color32::YELLOW, format!("El proceso {} ya no existe", pid));
+                    }
+                }
+                arbol.ancestros(pid)
+            }
+            None => HashSet::new(),
+        };
+
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            for &raiz in &arbol.raices {
+                self.mostrar_nodo(ui, &arbol, raiz, &ancestros, expandir);
+            }
+        });
+    }
+
+    fn cadena_lanzamiento(&self, arbol: &Arbol, proceso: &ProcesoInfo) -> String {
+        let mut cadena = vec![format!("{} ({})", proceso.get_nombre(), proceso.get_pid())];
+        let mut actual = proceso.get_ppid();
+        let mut vistos = HashSet::new();
+        while let Some(ppid) = actual {
+            let Some(padre) = arbol.por_pid.get(&ppid) else { break };
+            if !vistos.insert(ppid) {
+                break;
+            }
+            cadena.push(format!("{} ({})", padre.get_nombre(), ppid));
+            actual = padre.get_ppid();
+        }
+        cadena.reverse();
+        cadena.join(" → ")
+    }
+
+    fn mostrar_nodo(&mut self, ui: &mut egui::Ui, arbol: &Arbol, pid: i32, ancestros: &HashSet<i32>, expandir: bool) {
+        let proceso = arbol.por_pid[&pid];
+        let agregado = arbol.agregados.get(&pid).copied().unwrap_or_default();
+        let hijos = arbol.hijos.get(&pid);
+
+        let mut texto = egui::RichText::new(if hijos.is_some() {
+            format!(
+                "{} ({})  CPU {:.1}% [subárbol {:.1}%]  RAM {} MB [subárbol {} MB, {} desc.]",
+                proceso.get_nombre(), pid, proceso.get_uso_cpu(), agregado.uso_cpu,
+                proceso.get_uso_memoria(), agregado.uso_memoria, agregado.descendientes
+            )
+        } else {
+            format!(
+                "{} ({})  CPU {:.1}%  RAM {} MB",
+                proceso.get_nombre(), pid, proceso.get_uso_cpu(), proceso.get_uso_memoria()
+            )
+        });
+        if ancestros.contains(&pid) {
+            texto = texto.color(egui::Color32::from_rgb(255, 190, 60)).strong();
+        }
+        let seleccionado = self.seleccionado == Some(pid);
+
+        match hijos {
+            Some(hijos) => {
+                let id = ui.make_persistent_id(("arbol_proceso", pid));
+                let mut estado = egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false);
+                if expandir && ancestros.contains(&pid) {
+                    estado.set_open(true);
+                }
+                estado
+                    .show_header(ui, |ui| {
+                        if ui.selectable_label(seleccionado, texto).clicked() {
+                            self.seleccionar(pid);
+                        }
+                    })
+                    .body(|ui| {
+                        for &hijo in hijos {
+                            self.mostrar_nodo(ui, arbol, hijo, ancestros, expandir);
+                        }
+                    });
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    ui.add_space(ui.spacing().indent);
+                    if ui.selectable_label(seleccionado, texto).clicked() {
+                        self.seleccionar(pid);
+                    }
+                });
+            }
+        }
+    }
+
+    fn seleccionar(&mut self, pid: i32) {
+        if self.seleccionado == Some(pid) {
+            self.seleccionado = None;
+        } else {
+            self.seleccionado = Some(pid);
+            self.expandir_ancestros = true;
+        }
+    }
+}
+
+impl Default for PanelArbolProcesos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,169 @@
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use std::sync::{Arc, Mutex};
+use instrumentos::cgroups::CgroupInfo;
+use crate::metrics::Metrics;
+
+/// Bytes por MiB: la misma unidad en que `CgroupInfo` y `ProcesoInfo` dan la memoria.
+const B_A_MIB: u64 = 1024 * 1024;
+
+/// Vista de contabilidad de recursos por cgroup v2 y por contenedor.
+///
+/// Muestra una tabla con CPU, memoria, eventos OOM, E/S y PIDs de cada
+/// cgroup; al seleccionar uno se listan los procesos que contiene.
+pub struct PanelCgroups {
+    solo_contenedores: bool,
+    ocultar_vacios: bool,
+    seleccionado: Option<String>,
+}
+
+impl PanelCgroups {
+    pub fn new() -> Self {
+        Self { solo_contenedores: false, ocultar_vacios: true, seleccionado: None }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, metrics: &Arc<Mutex<Metrics>>) {
+        ui.heading("Cgroups y Contenedores");
+
+        let Ok(metrics_guard) = metrics.lock() else { return };
+        let Some(metric) = metrics_guard.latest() else {
+            ui.label("No metrics available.");
+            return;
+        };
+        let Some(cgroups) = metric.cgroups() else {
+            // sin jerarquía cgroup v2 el recolector falla con SinDatos
+            match metric.errores.get("cgroups") {
+                Some(error) => ui.colored_label(egui::Color32::RED, format!("No se pudieron leer los cgroups: {}", error)),
+                None => ui.label("El recolector de cgroups no está registrado."),
+            };
+            return;
+        };
+
+        ui.label(format!(
+            "Raíz: {}  |  Cgroups: {}  |  Contenedores: {}",
+            cgroups.get_raiz(), cgroups.get_cantidad_cgroups(), cgroups.get_cantidad_contenedores()
+        ));
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.solo_contenedores, "Solo contenedores");
+            ui.checkbox(&mut self.ocultar_vacios, "Ocultar cgroups sin procesos");
+        });
+
+        let visibles: Vec<&CgroupInfo> = cgroups
+            .get_cgroups()
+            .iter()
+            .filter(|c| !self.solo_contenedores || c.es_contenedor())
+            .filter(|c| !self.ocultar_vacios || c.get_cantidad_pids() > 0)
+            .collect();
+
+        if let Some(ruta) = self.seleccionado.clone() {
+            egui::SidePanel::right("detalles_cgroup")
+                .resizable(true)
+                .default_width(300.0)
+                .show_inside(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.heading("Procesos");
+                        if ui.button("Cerrar").clicked() {
+                            self.seleccionado = None;
+                        }
+                    });
+                    ui.label(&ruta);
+                    if let Some(cgroup) = cgroups.get_cgroup(&ruta) {
+                        let eventos = cgroup.get_eventos_memoria();
+                        ui.label(format!(
+                            "Eventos memoria: low {} high {} max {} oom {} oom_kill {}",
+                            eventos.low, eventos.high, eventos.max, eventos.oom, eventos.oom_kill
+                        ));
+                        for io in cgroup.get_io() {
+                            ui.label(format!(
+                                "E/S {}: R {} bytes ({} ops), W {} bytes ({} ops)",
+                                io.dispositivo, io.bytes_leidos, io.operaciones_lectura,
+                                io.bytes_escritos, io.operaciones_escritura
+                            ));
+                        }
+                    }
+                    ui.separator();
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        let procesos = metric.procesos().map(|p| cgroups.procesos_de(&ruta, p)).unwrap_or_default();
+                        for proceso in procesos {
+                            ui.label(format!(
+                                "PID: {}, Name: {}, CPU: {:.2}%, RAM: {} MiB",
+                                proceso.get_pid(), proceso.get_nombre(), proceso.get_uso_cpu(), proceso.get_uso_memoria()
+                            ));
+                        }
+                    });
+                });
+        }
+
+        let mut nueva_seleccion = None;
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .sense(egui::Sense::click())
+            .column(Column::initial(320.0).at_least(120.0).clip(true))
+            .column(Column::auto().at_least(110.0))
+            .column(Column::auto().at_least(60.0))
+            .column(Column::auto().at_least(120.0))
+            .column(Column::auto().at_least(50.0))
+            .column(Column::auto().at_least(90.0))
+            .column(Column::auto().at_least(40.0))
+            .header(20.0, |mut header| {
+                for titulo in ["Cgroup", "Tipo", "CPU %", "Memoria (MiB)", "OOM", "E/S (MiB)", "PIDs"] {
+                    header.col(|ui| {
+                        ui.strong(titulo);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(18.0, visibles.len(), |mut row| {
+                    let cgroup = visibles[row.index()];
+                    row.set_selected(self.seleccionado.as_deref() == Some(cgroup.get_ruta()));
+                    row.col(|ui| {
+                        ui.label(cgroup.get_ruta());
+                    });
+                    row.col(|ui| {
+                        ui.label(cgroup.get_tipo().to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(format!("{:.2}", cgroup.get_uso_cpu()));
+                    });
+                    row.col(|ui| {
+                        match cgroup.get_memoria_max() {
+                            Some(max) => ui.label(format!("{} / {}", cgroup.get_memoria_actual(), max)),
+                            None => ui.label(format!("{}", cgroup.get_memoria_actual())),
+                        };
+                    });
+                    row.col(|ui| {
+                        let oom = cgroup.get_eventos_memoria().oom_kill;
+                        if oom > 0 {
+                            ui.colored_label(egui::Color32::RED, oom.to_string());
+                        } else {
+                            ui.label("0");
+                        }
+                    });
+                    row.col(|ui| {
+                        let (leidos, escritos) = cgroup
+                            .get_io()
+                            .iter()
+                            .fold((0, 0), |(r, w), io| (r + io.bytes_leidos, w + io.bytes_escritos));
+                        ui.label(format!("R {} / W {}", leidos / B_A_MIB, escritos / B_A_MIB));
+                    });
+                    row.col(|ui| {
+                        ui.label(cgroup.get_cantidad_pids().to_string());
+                    });
+                    if row.response().clicked() {
+                        nueva_seleccion = Some(cgroup.get_ruta().to_string());
+                    }
+                });
+            });
+
+        if let Some(ruta) = nueva_seleccion {
+            self.seleccionado = if self.seleccionado.as_ref() == Some(&ruta) { None } else { Some(ruta) };
+        }
+    }
+}
+
+impl Default for PanelCgroups {
+    fn default() -> Self {
+        Self::new()
+    }
+}
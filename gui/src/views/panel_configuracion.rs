@@ -0,0 +1,263 @@
+use std::path::{Path, PathBuf};
+use eframe::egui;
+use instrumentos::recolector::INTEGRADOS;
+use crate::config::{
+    Alertas, ConfigAlmacen, ConfigNivel, ConfigRetencion, Configuracion, Duracion, Interfaz, Monitoreo, Porcentaje,
+    Recolectores, Unidades, Zona,
+};
+
+/// Lo que pidió el operador en este cuadro.
+pub enum AccionConfiguracion {
+    /// Aplicar sin guardar.
+    Aplicar(Configuracion),
+    /// Aplicar y guardar en el archivo de configuración.
+    Guardar(Configuracion),
+}
+
+/// Vista "Configuración": edita la configuración activa (ver `config`) y la
+/// aplica o la guarda en el archivo.
+///
+/// Los campos se editan como texto y se validan al aplicar con las mismas
+/// reglas que al leer el archivo; hasta entonces la configuración activa no
+/// cambia. Si la activa cambia por otro lado (recarga del archivo, deslizadores
+/// de alertas), el borrador se vuelve a cargar desde ella.
+pub struct PanelConfiguracion {
+    // configuración de la que salió el borrador
+    base: Option<Configuracion>,
+    cpu: f64,
+    memoria: f64,
+    zona: String,
+    unidades: Unidades,
+    // nombre, activa e intervalo de cada fuente integrada
+    recolectores: Vec<(&'static str, bool, String)>,
+    completa: String,
+    procesos: String,
+    // resolución y duración de cada nivel de resúmenes
+    niveles: Vec<(String, String)>,
+    almacen_activo: bool,
+    directorio: String,
+    retencion_almacen: String,
+    mensaje: Option<Result<String, String>>,
+}
+
+impl PanelConfiguracion {
+    pub fn new() -> Self {
+        Self {
+            base: None,
+            cpu: 0.0,
+            memoria: 0.0,
+            zona: String::new(),
+            unidades: Unidades::default(),
+            recolectores: Vec::new(),
+            completa: String::new(),
+            procesos: String::new(),
+            niveles: Vec::new(),
+            almacen_activo: true,
+            directorio: String::new(),
+            retencion_almacen: String::new(),
+            mensaje: None,
+        }
+    }
+
+    /// Muestra el resultado de la última acción (`Err` en rojo).
+    pub fn informar(&mut self, resultado: Result<String, String>) {
+        self.mensaje = Some(resultado);
+    }
+
+    // Rellena el borrador con `configuracion`
+    fn cargar(&mut self, configuracion: &Configuracion) {
+        self.cpu = configuracion.alertas.cpu.0;
+        self.memoria = configuracion.alertas.memoria.0;
+        self.zona = configuracion.zona().name().to_string();
+        self.unidades = configuracion.interfaz.unidades;
+        let recolectores = &configuracion.monitoreo.recolectores;
+        self.recolectores = INTEGRADOS
+            .iter()
+            .map(|&(nombre, por_defecto)| {
+                let intervalo = recolectores.campo(nombre);
+                (nombre, intervalo.is_some(), intervalo.unwrap_or(Duracion(por_defecto)).to_string())
+            })
+            .collect();
+        self.completa = configuracion.retencion.completa.to_string();
+        self.procesos = configuracion.retencion.procesos.to_string();
+        self.niveles = configuracion
+            .retencion
+            .niveles
+            .iter()
+            .map(|n| (n.resolucion.to_string(), n.duracion.to_string()))
+            .collect();
+        self.almacen_activo = configuracion.almacen.activo;
+        self.directorio = configuracion.almacen.directorio.as_ref().map(|d| d.display().to_string()).unwrap_or_default();
+        self.retencion_almacen = configuracion.almacen.retencion.to_string();
+        self.base = Some(configuracion.clone());
+    }
+
+    // Arma la configuración del borrador, o el primer error que encuentre
+    fn leer(&self) -> Result<Configuracion, String> {
+        let duracion = |campo: &str, texto: &str| texto.parse::<Duracion>().map_err(|e| format!("{}: {}", campo, e));
+        let zona: Zona = self.zona.parse()?;
+        let mut recolectores = Recolectores::default();
+        for (nombre, activo, intervalo) in &self.recolectores {
+            if let Some(campo) = recolectores.campo_mut(nombre) {
+                *campo = if *activo { Some(duracion(nombre, intervalo)?) } else { None };
+            }
+        }
+        let niveles = self
+            .niveles
+            .iter()
+            .map(|(resolucion, dur)| {
+                Ok(ConfigNivel { resolucion: duracion("resolución del nivel", resolucion)?, duracion: duracion("duración del nivel", dur)? })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let retencion = ConfigRetencion {
+            completa: duracion("resolución completa", &self.completa)?,
+            procesos: duracion("procesos", &self.procesos)?,
+            niveles,
+        };
+        retencion.validar()?;
+        let directorio = Some(self.directorio.trim()).filter(|d| !d.is_empty()).map(PathBuf::from);
+        let retencion_almacen = duracion("retención del almacén", &self.retencion_almacen)?;
+        Ok(Configuracion {
+            alertas: Alertas { cpu: Porcentaje(self.cpu), memoria: Porcentaje(self.memoria) },
+            monitoreo: Monitoreo { zona_horaria: zona, recolectores },
+            retencion,
+            almacen: ConfigAlmacen { activo: self.almacen_activo, directorio, retencion: retencion_almacen },
+            interfaz: Interfaz { unidades: self.unidades },
+        })
+    }
+
+    /// `actual` es la configuración activa, `ruta` el archivo en el que se
+    /// guarda y `sin_guardar` si la activa difiere de lo que hay en ese archivo.
+    ///
+    /// # Retorno
+    /// La acción pedida en este cuadro, si hubo una.
+    pub fn show(&mut self, ui: &mut egui::Ui, actual: &Configuracion, ruta: Option<&Path>, sin_guardar: bool) -> Option<AccionConfiguracion> {
+        if self.base.as_ref() != Some(actual) {
+            self.cargar(actual);
+        }
+        ui.heading("Configuración");
+        match ruta {
+            Some(ruta) => ui.label(format!("Archivo: {}", ruta.display())),
+            None => ui.label("Sin ruta para el archivo de configuración: los cambios no se pueden guardar."),
+        };
+        if sin_guardar {
+            ui.colored_label(egui::Color32::YELLOW, "La configuración activa tiene cambios sin guardar.");
+        }
+
+        let mut accion = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.separator();
+            ui.strong("Alertas");
+            egui::Grid::new("config_alertas").num_columns(2).show(ui, |ui| {
+                ui.label("Uso de CPU:");
+                ui.add(egui::Slider::new(&mut self.cpu, 0.0..=100.0).suffix("%"));
+                ui.end_row();
+                ui.label("Uso de memoria:");
+                ui.add(egui::Slider::new(&mut self.memoria, 0.0..=100.0).suffix("%"));
+                ui.end_row();
+            });
+
+            ui.separator();
+            ui.strong("Muestreo");
+            egui::Grid::new("config_muestreo").num_columns(2).show(ui, |ui| {
+                ui.label("Zona horaria:");
+                ui.text_edit_singleline(&mut self.zona).on_hover_text("Nombre IANA, p. ej. America/Mexico_City o UTC");
+                ui.end_row();
+                ui.label("Unidades de memoria:");
+                ui.horizontal(|ui| {
+                    for unidades in Unidades::TODAS {
+                        ui.radio_value(&mut self.unidades, unidades, unidades.nombre());
+                    }
+                });
+                ui.end_row();
+            });
+            egui::Grid::new("config_recolectores").num_columns(2).show(ui, |ui| {
+                for (nombre, activo, intervalo) in &mut self.recolectores {
+                    ui.checkbox(activo, *nombre);
+                    ui.add_enabled(*activo, egui::TextEdit::singleline(intervalo).desired_width(80.0));
+                    ui.end_row();
+                }
+            });
+            ui.small("Intervalos como 500ms, 5s, 10m o 1h.");
+
+            ui.separator();
+            ui.strong("Retención en memoria");
+            let mut quitar = None;
+            egui::Grid::new("config_retencion").num_columns(3).show(ui, |ui| {
+                ui.label("Resolución completa:");
+                ui.add(egui::TextEdit::singleline(&mut self.completa).desired_width(80.0));
+                ui.end_row();
+                ui.label("Con procesos:");
+                ui.add(egui::TextEdit::singleline(&mut self.procesos).desired_width(80.0));
+                ui.end_row();
+                for (i, (resolucion, duracion)) in self.niveles.iter_mut().enumerate() {
+                    ui.label(format!("Promedios, nivel {}:", i + 1));
+                    ui.horizontal(|ui| {
+                        ui.label("cada");
+                        ui.add(egui::TextEdit::singleline(resolucion).desired_width(60.0));
+                        ui.label("durante");
+                        ui.add(egui::TextEdit::singleline(duracion).desired_width(60.0));
+                    });
+                    if ui.button("Quitar").clicked() {
+                        quitar = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(i) = quitar {
+                self.niveles.remove(i);
+            }
+            if ui.button("Agregar nivel").clicked() {
+                self.niveles.push(("1h".to_string(), "365d".to_string()));
+            }
+
+            ui.separator();
+            ui.strong("Almacén en disco");
+            ui.checkbox(&mut self.almacen_activo, "Guardar las series en disco");
+            ui.add_enabled_ui(self.almacen_activo, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Directorio:");
+                    ui.text_edit_singleline(&mut self.directorio).on_hover_text("Vacío: el directorio por defecto");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Borrar los días más viejos que:");
+                    ui.add(egui::TextEdit::singleline(&mut self.retencion_almacen).desired_width(80.0));
+                });
+            });
+            ui.small("El cambio de almacén aplica la próxima vez que se inicie el monitoreo.");
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let aplicar = ui.button("Aplicar").clicked();
+                let guardar = ui.add_enabled(ruta.is_some(), egui::Button::new("Guardar")).clicked();
+                if ui.button("Descartar cambios").clicked() {
+                    self.cargar(actual);
+                    self.mensaje = None;
+                }
+                if aplicar || guardar {
+                    match self.leer() {
+                        Ok(nueva) if guardar => accion = Some(AccionConfiguracion::Guardar(nueva)),
+                        Ok(nueva) => accion = Some(AccionConfiguracion::Aplicar(nueva)),
+                        Err(e) => self.mensaje = Some(Err(e)),
+                    }
+                }
+            });
+            match &self.mensaje {
+                Some(Ok(mensaje)) => {
+                    ui.label(mensaje);
+                }
+                Some(Err(error)) => {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                None => {}
+            }
+        });
+        accion
+    }
+}
+
+impl Default for PanelConfiguracion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,191 @@
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use chrono::{Local, TimeZone, Utc};
+use crate::alertas::{self, Umbrales};
+use crate::flota::{Equipo, Salud, ServidorFlota};
+use crate::seguridad::{OpcionesSeguridad, Seguridad};
+
+/// Vista de la flota: equipos con agente conectados a esta instancia.
+///
+/// Muestra la salud, la última vez que se supo de cada equipo y sus alertas;
+/// al hacer clic en un equipo, el resto de los paneles pasa a mostrarlo.
+/// En "Seguridad" se configuran TLS, TLS mutuo y el archivo de tokens.
+pub struct PanelFlota {
+    direccion: String,
+    // rutas de `OpcionesSeguridad`; vacías = sin usar
+    certificado: String,
+    clave: String,
+    ca_clientes: String,
+    tokens: String,
+    sin_autenticacion: bool,
+    servidor: Option<ServidorFlota>,
+    error: Option<String>,
+}
+
+impl PanelFlota {
+    pub fn new() -> Self {
+        Self {
+            direccion: "0.0.0.0:9200".to_string(),
+            certificado: String::new(),
+            clave: String::new(),
+            ca_clientes: String::new(),
+            tokens: String::new(),
+            sin_autenticacion: false,
+            servidor: None,
+            error: None,
+        }
+    }
+
+    pub fn escuchando(&self) -> bool {
+        self.servidor.is_some()
+    }
+
+    /// # Retorno
+    /// El equipo en el que se hizo clic en este cuadro, si hubo uno.
+    pub fn show(&mut self, ui: &mut egui::Ui, umbrales: &Umbrales) -> Option<Equipo> {
+        ui.heading("Flota");
+
+        let mut detener = false;
+
+        ui.horizontal(|ui| match &self.servidor {
+            Some(servidor) => {
+                ui.label(format!("Escuchando agentes en {}", servidor.get_direccion()));
+                if ui.button("Detener").clicked() {
+                    detener = true;
+                }
+            }
+            None => {
+                ui.label("Dirección:");
+                ui.text_edit_singleline(&mut self.direccion);
+                if ui.button("Escuchar agentes").clicked() {
+                    let ruta = |texto: &str| Some(texto.trim()).filter(|t| !t.is_empty()).map(Into::into);
+                    let opciones = OpcionesSeguridad {
+                        certificado: ruta(&self.certificado),
+                        clave: ruta(&self.clave),
+                        ca_clientes: ruta(&self.ca_clientes),
+                        tokens: ruta(&self.tokens),
+                        sin_autenticacion: self.sin_autenticacion,
+                    };
+                    match Seguridad::cargar(&opciones).and_then(|seguridad| ServidorFlota::iniciar(self.direccion.trim(), seguridad)) {
+                        Ok(servidor) => {
+                            self.servidor = Some(servidor);
+                            self.error = None;
+                        }
+                        Err(e) => self.error = Some(format!("No se pudo escuchar en {}: {}", self.direccion.trim(), e)),
+                    }
+                }
+            }
+        });
+        if detener {
+            self.servidor = None;
+        }
+        if self.servidor.is_none() {
+            egui::CollapsingHeader::new("Seguridad").show(ui, |ui| {
+                egui::Grid::new("seguridad_flota").num_columns(2).show(ui, |ui| {
+                    for (etiqueta, campo) in [
+                        ("Certificado (PEM):", &mut self.certificado),
+                        ("Clave (PEM):", &mut self.clave),
+                        ("CA de clientes (TLS mutuo):", &mut self.ca_clientes),
+                        ("Archivo de tokens:", &mut self.tokens),
+                    ] {
+                        ui.label(etiqueta);
+                        ui.text_edit_singleline(campo);
+                        ui.end_row();
+                    }
+                });
+                ui.checkbox(&mut self.sin_autenticacion, "Escuchar sin tokens fuera de loopback");
+                ui.small("Sin archivo de tokens solo se escucha en loopback; con la casilla marcada, cualquier agente que llegue al puerto puede presentarse.");
+            });
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        let Some(servidor) = &self.servidor else {
+            ui.label("En cada equipo: sistema_defensa agent --server DIRECCION:PUERTO [--tls-ca PEM] [--token TOKEN]");
+            return None;
+        };
+
+        let equipos = servidor.equipos();
+        if equipos.is_empty() {
+            ui.label("Todavía no se conectó ningún agente.");
+            return None;
+        }
+
+        let ahora = Utc::now().timestamp_millis();
+        let mut elegido = None;
+        TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .column(Column::initial(200.0).at_least(100.0).clip(true))
+            .column(Column::auto().at_least(150.0))
+            .column(Column::auto().at_least(100.0))
+            .column(Column::auto().at_least(150.0))
+            .column(Column::auto().at_least(60.0))
+            .column(Column::auto().at_least(80.0))
+            .column(Column::remainder())
+            .header(20.0, |mut header| {
+                for titulo in ["Equipo", "Dirección", "Salud", "Última vez", "CPU %", "Memoria %", "Alertas"] {
+                    header.col(|ui| {
+                        ui.strong(titulo);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(20.0, equipos.len(), |mut row| {
+                    let equipo = &equipos[row.index()];
+                    let (valores, alertas) = match equipo.get_metrics().try_lock() {
+                        Ok(metrics) => match metrics.latest() {
+                            Some(metric) => (metric.valores(), alertas::evaluar(metric, umbrales)),
+                            None => Default::default(),
+                        },
+                        Err(_) => Default::default(),
+                    };
+                    row.col(|ui| {
+                        if ui.link(equipo.get_nombre()).on_hover_text("Ver en los paneles").clicked() {
+                            elegido = Some(equipo.clone());
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(equipo.get_direccion().to_string());
+                    });
+                    row.col(|ui| {
+                        let salud = equipo.salud(ahora);
+                        let color = match salud {
+                            Salud::Activo => egui::Color32::GREEN,
+                            Salud::Atrasado => egui::Color32::YELLOW,
+                            Salud::Desconectado => egui::Color32::RED,
+                        };
+                        ui.colored_label(color, salud.nombre());
+                    });
+                    row.col(|ui| {
+                        let hora = Local.timestamp_millis_opt(equipo.get_ultima_vez()).single();
+                        let segundos = (ahora - equipo.get_ultima_vez()) / 1000;
+                        match hora {
+                            Some(hora) => ui.label(format!("{} (hace {} s)", hora.format("%H:%M:%S"), segundos)),
+                            None => ui.label("-"),
+                        };
+                    });
+                    for serie in ["cpu.uso", "memoria.uso"] {
+                        row.col(|ui| {
+                            ui.label(valores.get(serie).map_or("-".to_string(), |v| format!("{:.1}", v)));
+                        });
+                    }
+                    row.col(|ui| {
+                        if alertas.is_empty() {
+                            ui.label("0");
+                        } else {
+                            let detalle: Vec<String> = alertas.iter().map(|a| a.to_string()).collect();
+                            ui.colored_label(egui::Color32::RED, alertas.len().to_string()).on_hover_text(detalle.join("\n"));
+                        }
+                    });
+                });
+            });
+        elegido
+    }
+}
+
+impl Default for PanelFlota {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,170 +1,151 @@
 use eframe::egui;
 use egui_plot::{Line, Plot, PlotPoints};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::sync::{Arc, Mutex};
-use std::collections::VecDeque;
-use crate::metrics::{Metrics, Metric};
-
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use crate::almacen::Almacen;
+use crate::metrics::Metrics;
+
+/// Ventanas de tiempo que se pueden graficar, en minutos.
+const VENTANAS: [(&str, i64); 4] = [("5 min", 5), ("1 h", 60), ("24 h", 24 * 60), ("30 días", 30 * 24 * 60)];
+/// Puntos aproximados por gráfica al leer del almacén en disco.
+const PUNTOS_ALMACEN: i64 = 500;
+/// Cada cuánto se vuelve a leer el almacén en disco.
+const REFRESCO_ALMACEN: Duration = Duration::from_secs(60);
+
+/// Gráficas de las series numéricas del histórico de `Metrics`.
+///
+/// El eje X son minutos antes del momento actual; para ventanas largas los
+/// puntos vienen de los resúmenes promediados (ver `Retencion`). Lo que queda
+/// antes del histórico en memoria (sesiones anteriores) se lee del almacén
+/// en otro hilo y se guarda por ventana; mientras tanto se muestra lo que ya había.
 pub struct PanelGraficas {
-    historico_cpu: VecDeque<f64>,
-    historico_memoria: VecDeque<f64>,
-    historico_red: VecDeque<f64>,
-    historico_disco: VecDeque<f64>,
-    max_puntos: usize,
+    ventana: usize,
+    // por ventana: cuándo se leyó el almacén y los promedios de cada serie
+    almacenado: HashMap<usize, (Instant, Series)>,
+    cargando: Option<Receiver<(usize, Series)>>,
 }
 
+/// Puntos de cada serie graficada.
+type Series = HashMap<&'static str, Vec<(i64, f64)>>;
+
 impl PanelGraficas {
     pub fn new() -> Self {
-        Self {
-            historico_cpu: VecDeque::with_capacity(100),
-            historico_memoria: VecDeque::with_capacity(100),
-            historico_red: VecDeque::with_capacity(100),
-            historico_disco: VecDeque::with_capacity(100),
-            max_puntos: 100,
-        }
+        Self { ventana: 0, almacenado: HashMap::new(), cargando: None }
     }
 
     pub fn show(&mut self, ui: &mut egui::Ui, metrics: &Arc<Mutex<Metrics>>) {
         ui.heading("Panel de Gráficas");
 
-        if let Ok(metrics_guard) = metrics.lock() {
-            if let Some(metric) = metrics_guard.latest() {
-                // Actualizar datos históricos
-                self.actualizar_historicos(metric);
-
-                // Gráfica CPU
-                ui.collapsing("CPU", |ui| {
-                    self.mostrar_grafica_cpu(ui);
-                });
-
-                // Gráfica Memoria
-                ui.collapsing("Memoria", |ui| {
-                    self.mostrar_grafica_memoria(ui);
-                });
-
-                // Gráfica Red
-                ui.collapsing("Red", |ui| {
-                    self.mostrar_grafica_red(ui);
-                });
-
-                // Gráfica Disco
-                ui.collapsing("Disco", |ui| {
-                    self.mostrar_grafica_disco(ui);
-                });
+        ui.horizontal(|ui| {
+            ui.label("Ventana:");
+            for (i, (titulo, _)) in VENTANAS.iter().enumerate() {
+                ui.selectable_value(&mut self.ventana, i, *titulo);
             }
+        });
+
+        let graficas = [
+            ("CPU", "cpu.uso", "CPU Usage", egui::Color32::from_rgb(100, 200, 100), Some(100.0)),
+            ("Memoria", "memoria.uso", "Memoria Usage", egui::Color32::from_rgb(100, 100, 200), Some(100.0)),
+            ("Red", "red.recibidos_mb", "Red RX (MB)", egui::Color32::from_rgb(200, 100, 100), None),
+            ("Disco (usado %)", "disco.uso", "Disco usado %", egui::Color32::from_rgb(100, 200, 200), Some(100.0)),
+        ];
+        // solo se copia lo que hay en memoria; el disco se lee sin el candado
+        let Ok(metrics_guard) = metrics.lock() else { return };
+        // en una reproducción el "ahora" es el del último snapshot, no el del reloj
+        let ahora = metrics_guard.latest().map_or_else(|| Utc::now().timestamp_millis(), |m| m.marca);
+        let desde = ahora - VENTANAS[self.ventana].1 * 60_000;
+        let dir_almacen = metrics_guard.get_almacen().cloned();
+        let en_memoria: Vec<Vec<(i64, f64)>> = graficas.iter().map(|g| metrics_guard.serie(g.1, desde, ahora)).collect();
+        drop(metrics_guard);
+
+        self.recibir_almacenado();
+        let vigente = self.almacenado.get(&self.ventana).is_some_and(|(instante, _)| instante.elapsed() < REFRESCO_ALMACEN);
+        if !vigente
+            && self.cargando.is_none()
+            && let Some(dir) = dir_almacen
+        {
+            let series = graficas.map(|g| g.1);
+            self.cargar_almacenado(ui.ctx().clone(), dir, series, desde, ahora);
         }
-    }
-
-    fn actualizar_historicos(&mut self, metric: &Metric) {
-        //generar variable suma
-        let mut suma = 0.0;
-
-
-        for uso in metric.cpu.get_uso_nucleos() {
-            suma += uso;
-        }
-        let promedio = (suma as f64 / metric.cpu.get_cantidad_nucleos() as f64) as f64;
-        self.historico_cpu.push_back(promedio);
-        let porcentaje = (metric.memoria.get_memoria_usada() as f64 / metric.memoria.get_memoria_total() as f64) * 100.0;
-        self.historico_memoria.push_back(porcentaje);
-        self.historico_red.push_back(metric.red.get_bytes_recibidos() as f64 / 1000000.0);
-        let disco = metric.disco.get_espacio_total() - metric.disco.get_espacio_usado();
-        let porcentaje_disco = (disco as f64 / metric.disco.get_espacio_total() as f64) * 100.0;
-        self.historico_disco.push_back(porcentaje_disco);
-
-        while self.historico_cpu.len() > self.max_puntos {
-            self.historico_cpu.pop_front();
-        }
-        // Repeat for other VecDeque fields as needed
-    }
 
-    fn mostrar_grafica_cpu(&self, ui: &mut egui::Ui) {
-        let points: PlotPoints = self.historico_cpu
-            .iter()
-            .enumerate()
-            .map(|(i, &v)| [i as f64, v])
-            .collect();
-
-        let line = Line::new(points)
-            .color(egui::Color32::from_rgb(100, 200, 100))
-            .name("CPU Usage");
-
-        Plot::new("cpu_plot")
-            .view_aspect(2.0)
-            .include_y(0.0)
-            .include_y(100.0)
-            .label_formatter(|name, value| {
-                format!("{}: {:.1}%", name, value.y)
-            })
-            .show(ui, |plot_ui| {
-                plot_ui.line(line);
+        let almacenado = self.almacenado.get(&self.ventana).map(|(_, series)| series);
+        for ((titulo, serie, nombre, color, maximo), mut puntos) in graficas.into_iter().zip(en_memoria) {
+            // lo almacenado solo completa el tramo anterior a lo que hay en memoria
+            let inicio_memoria = puntos.first().map_or(i64::MAX, |p| p.0);
+            if let Some(almacenado) = almacenado.and_then(|series| series.get(serie)) {
+                let anteriores = almacenado.iter().copied().filter(|p| p.0 >= desde && p.0 < inicio_memoria);
+                puntos.splice(0..0, anteriores);
+            }
+            ui.collapsing(titulo, |ui| {
+                Self::mostrar_grafica(ui, serie, nombre, color, maximo, ahora, &puntos);
             });
+        }
     }
 
-    fn mostrar_grafica_memoria(&self, ui: &mut egui::Ui) {
-        let points: PlotPoints = self.historico_memoria
-            .iter()
-            .enumerate()
-            .map(|(i, &v)| [i as f64, v])
-            .collect();
-
-        let line = Line::new(points)
-            .color(egui::Color32::from_rgb(100, 100, 200))
-            .name("Memoria Usage");
-
-        Plot::new("memoria_plot")
-            .view_aspect(2.0)
-            .include_y(0.0)
-            .include_y(100.0)
-            .label_formatter(|name, value| {
-                format!("{}: {:.1}%", name, value.y)
-            })
-            .show(ui, |plot_ui| {
-                plot_ui.line(line);
-            });
+    // Lee del almacén los promedios de `series` en otro hilo; llegan por `cargando`
+    fn cargar_almacenado(&mut self, ctx: egui::Context, dir: PathBuf, series: [&'static str; 4], desde: i64, hasta: i64) {
+        let (emisor, receptor) = mpsc::channel();
+        let ventana = self.ventana;
+        thread::spawn(move || {
+            let resolucion = (hasta - desde) / PUNTOS_ALMACEN;
+            // si no se puede leer, la gráfica queda solo con lo que hay en memoria
+            let leidas: Series = match Almacen::abrir(&dir) {
+                Ok(almacen) => series
+                    .into_iter()
+                    .map(|serie| (serie, almacen.consultar_promedios(serie, desde, hasta, resolucion).unwrap_or_default()))
+                    .collect(),
+                Err(_) => Series::new(),
+            };
+            if emisor.send((ventana, leidas)).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+        self.cargando = Some(receptor);
     }
 
-    fn mostrar_grafica_red(&self, ui: &mut egui::Ui) {
-        let points: PlotPoints = self.historico_red
-            .iter()
-            .enumerate()
-            .map(|(i, &v)| [i as f64, v])
-            .collect();
-
-        let line = Line::new(points)
-            .color(egui::Color32::from_rgb(200, 100, 100))
-            .name("Red Usage");
-
-        Plot::new("red_plot")
-            .view_aspect(2.0)
-            .include_y(0.0)
-            .label_formatter(|name, value| {
-                format!("{}: {:.1}", name, value.y)
-            })
-            .show(ui, |plot_ui| {
-                plot_ui.line(line);
-            });
+    // Guarda la lectura del almacén si ya terminó
+    fn recibir_almacenado(&mut self) {
+        let Some(receptor) = &self.cargando else { return };
+        match receptor.try_recv() {
+            Ok((ventana, series)) => {
+                self.almacenado.insert(ventana, (Instant::now(), series));
+                self.cargando = None;
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => self.cargando = None,
+        }
     }
 
-    fn mostrar_grafica_disco(&self, ui: &mut egui::Ui) {
-        let points: PlotPoints = self.historico_disco
+    fn mostrar_grafica(
+        ui: &mut egui::Ui,
+        id: &str,
+        nombre: &str,
+        color: egui::Color32,
+        maximo: Option<f64>,
+        ahora: i64,
+        puntos: &[(i64, f64)],
+    ) {
+        let points: PlotPoints = puntos
             .iter()
-            .enumerate()
-            .map(|(i, &v)| [i as f64, v])
+            .map(|&(marca, v)| [(marca - ahora) as f64 / 60_000.0, v])
             .collect();
 
-        let line = Line::new(points)
-            .color(egui::Color32::from_rgb(100, 200, 200))
-            .name("Disco Usage");
+        let line = Line::new(points).color(color).name(nombre);
 
-        Plot::new("disco_plot")
+        let mut plot = Plot::new(id)
             .view_aspect(2.0)
             .include_y(0.0)
-            .include_y(100.0)
-            .label_formatter(|name, value| {
-                format!("{}: {:.1}%", name, value.y)
-            })
-            .show(ui, |plot_ui| {
-                plot_ui.line(line);
-            });
+            .label_formatter(|name, value| format!("{}: {:.1} (hace {:.1} min)", name, value.y, -value.x));
+        if let Some(maximo) = maximo {
+            plot = plot.include_y(maximo);
+        }
+        plot.show(ui, |plot_ui| {
+            plot_ui.line(line);
+        });
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,464 @@
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use regex::Regex;
+use std::cmp::Ordering;
+use std::sync::{Arc, Mutex};
+use instrumentos::procesos::{nombres_capacidades, ArchivoAbierto, MapasMemoria, ProcesoInfo, SeguridadProceso};
+use crate::config::Unidades;
+use crate::metrics::Metrics;
+
+/// Columnas por las que se puede ordenar la tabla de procesos.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColumnaProceso {
+    Pid,
+    Nombre,
+    Usuario,
+    Cpu,
+    Memoria,
+    MemoriaVirtual,
+    Estado,
+    TiempoEjecucion,
+}
+
+impl ColumnaProceso {
+    const TODAS: [ColumnaProceso; 8] = [
+        ColumnaProceso::Pid,
+        ColumnaProceso::Nombre,
+        ColumnaProceso::Usuario,
+        ColumnaProceso::Cpu,
+        ColumnaProceso::Memoria,
+        ColumnaProceso::MemoriaVirtual,
+        ColumnaProceso::Estado,
+        ColumnaProceso::TiempoEjecucion,
+    ];
+
+    fn titulo(&self, unidades: Unidades) -> String {
+        let titulo = match self {
+            ColumnaProceso::Pid => "PID",
+            ColumnaProceso::Nombre => "Nombre",
+            ColumnaProceso::Usuario => "Usuario",
+            ColumnaProceso::Cpu => "CPU %",
+            ColumnaProceso::Memoria => "RSS",
+            ColumnaProceso::MemoriaVirtual => "Virtual",
+            ColumnaProceso::Estado => "Estado",
+            ColumnaProceso::TiempoEjecucion => "Tiempo (s)",
+        };
+        match self {
+            ColumnaProceso::Memoria | ColumnaProceso::MemoriaVirtual => format!("{} ({})", titulo, unidades.nombre()),
+            _ => titulo.to_string(),
+        }
+    }
+
+    fn comparar(&self, a: &ProcesoInfo, b: &ProcesoInfo) -> Ordering {
+        match self {
+            ColumnaProceso::Pid => a.get_pid().cmp(&b.get_pid()),
+            ColumnaProceso::Nombre => a.get_nombre().to_lowercase().cmp(&b.get_nombre().to_lowercase()),
+            ColumnaProceso::Usuario => a.get_usuario().cmp(b.get_usuario()),
+            ColumnaProceso::Cpu => a.get_uso_cpu().total_cmp(&b.get_uso_cpu()),
+            ColumnaProceso::Memoria => a.get_uso_memoria().cmp(&b.get_uso_memoria()),
+            ColumnaProceso::MemoriaVirtual => a.get_uso_memoria_virtual().cmp(&b.get_uso_memoria_virtual()),
+            ColumnaProceso::Estado => a.get_estado().cmp(b.get_estado()),
+            ColumnaProceso::TiempoEjecucion => a.get_tiempo_ejecucion().cmp(&b.get_tiempo_ejecucion()),
+        }
+    }
+
+    fn celda(&self, proceso: &ProcesoInfo, unidades: Unidades) -> String {
+        match self {
+            ColumnaProceso::Pid => proceso.get_pid().to_string(),
+            ColumnaProceso::Nombre => proceso.get_nombre().clone(),
+            ColumnaProceso::Usuario => proceso.get_usuario().clone(),
+            ColumnaProceso::Cpu => format!("{:.2}", proceso.get_uso_cpu()),
+            ColumnaProceso::Memoria => unidades.valor(proceso.get_uso_memoria()),
+            ColumnaProceso::MemoriaVirtual => unidades.valor(proceso.get_uso_memoria_virtual()),
+            ColumnaProceso::Estado => proceso.get_estado().clone(),
+            ColumnaProceso::TiempoEjecucion => proceso.get_tiempo_ejecucion().to_string(),
+        }
+    }
+}
+
+/// Vista con la tabla completa de procesos: ordenable por columna, filtrable
+/// por texto o expresión regular y con un panel lateral de detalles.
+///
+/// La selección se guarda por PID, de modo que se mantiene entre refrescos
+/// aunque cambie el orden de la tabla.
+pub struct PanelProcesos {
+    orden: ColumnaProceso,
+    ascendente: bool,
+    filtro: String,
+    usar_regex: bool,
+    regex: Option<Result<Regex, String>>,
+    seleccionado: Option<i32>,
+    // última copia conocida del proceso fijado, por si termina entre refrescos
+    ultimo_seleccionado: Option<ProcesoInfo>,
+    // consultas bajo demanda del proceso seleccionado
+    archivos: Option<Result<Vec<ArchivoAbierto>, String>>,
+    mapas: Option<Result<MapasMemoria, String>>,
+    unidades: Unidades,
+}
+
+impl PanelProcesos {
+    pub fn new() -> Self {
+        Self {
+            orden: ColumnaProceso::Cpu,
+            ascendente: false,
+            filtro: String::new(),
+            usar_regex: false,
+            regex: None,
+            seleccionado: None,
+            ultimo_seleccionado: None,
+            archivos: None,
+            mapas: None,
+            unidades: Unidades::default(),
+        }
+    }
+
+    /// Los tamaños de memoria se muestran en `unidades`.
+    pub fn show(&mut self, ui: &mut egui::Ui, metrics: &Arc<Mutex<Metrics>>, unidades: Unidades) {
+        ui.heading("Panel de Procesos");
+        self.unidades = unidades;
+
+        let (procesos, host_ilegible) = match metrics.lock() {
+            Ok(metrics_guard) => match metrics_guard.latest() {
+                Some(metric) => metric
+                    .procesos()
+                    .map(|p| (p.get_procesos().clone(), p.get_namespaces_host_ilegibles()))
+                    .unwrap_or_default(),
+                None => {
+                    ui.label("No metrics available.");
+                    return;
+                }
+            },
+            Err(_) => return,
+        };
+        if host_ilegible {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                "No se pudieron leer los namespaces de PID 1: la detección de procesos fuera del host no está activa",
+            );
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Filtro:");
+            let cambio_texto = ui.text_edit_singleline(&mut self.filtro).changed();
+            let cambio_modo = ui.checkbox(&mut self.usar_regex, "Regex").changed();
+            if cambio_texto || cambio_modo {
+                self.regex = None;
+            }
+            if let Some(Err(e)) = self.compilar_regex() {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+        });
+
+        let mut visibles: Vec<&ProcesoInfo> = procesos.iter().filter(|p| self.coincide(p)).collect();
+        visibles.sort_by(|a, b| {
+            let orden = self.orden.comparar(a, b);
+            if self.ascendente { orden } else { orden.reverse() }
+        });
+        ui.label(format!("Mostrando {} de {} procesos", visibles.len(), procesos.len()));
+
+        if let Some(pid) = self.seleccionado {
+            if let Some(actual) = procesos.iter().find(|p| p.get_pid() == pid) {
+                self.ultimo_seleccionado = Some(actual.clone());
+            }
+            let sigue_vivo = procesos.iter().any(|p| p.get_pid() == pid);
+            egui::SidePanel::right("detalles_proceso")
+                .resizable(true)
+                .default_width(260.0)
+                .show_inside(ui, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        self.mostrar_detalles(ui, sigue_vivo);
+                    });
+                });
+        }
+
+        self.mostrar_tabla(ui, &visibles);
+    }
+
+    fn compilar_regex(&mut self) -> Option<&Result<Regex, String>> {
+        if !self.usar_regex || self.filtro.is_empty() {
+            return None;
+        }
+        if self.regex.is_none() {
+            self.regex = Some(Regex::new(&format!("(?i){}", self.filtro)).map_err(|e| e.to_string()));
+        }
+        self.regex.as_ref()
+    }
+
+    fn coincide(&self, proceso: &ProcesoInfo) -> bool {
+        if self.filtro.is_empty() {
+            return true;
+        }
+        let campos = [
+            proceso.get_pid().to_string(),
+            proceso.get_nombre().clone(),
+            proceso.get_usuario().clone(),
+        ];
+        if self.usar_regex {
+            match &self.regex {
+                Some(Ok(re)) => campos.iter().any(|c| re.is_match(c)),
+                // una expresión inválida no oculta la tabla
+                _ => true,
+            }
+        } else {
+            let filtro = self.filtro.to_lowercase();
+            campos.iter().any(|c| c.to_lowercase().contains(&filtro))
+        }
+    }
+
+    fn mostrar_tabla(&mut self, ui: &mut egui::Ui, visibles: &[&ProcesoInfo]) {
+        let mut nueva_seleccion = None;
+        let alto_fila = 18.0;
+
+        let mut tabla = TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .sense(egui::Sense::click())
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+        for columna in ColumnaProceso::TODAS {
+            tabla = match columna {
+                ColumnaProceso::Nombre => tabla.column(Column::initial(180.0).at_least(80.0).clip(true)),
+                _ => tabla.column(Column::auto().at_least(60.0)),
+            };
+        }
+
+        tabla
+            .header(22.0, |mut header| {
+                for columna in ColumnaProceso::TODAS {
+                    header.col(|ui| {
+                        let mut titulo = columna.titulo(self.unidades);
+                        if columna == self.orden {
+                            titulo.push_str(if self.ascendente { " ▲" } else { " ▼" });
+                        }
+                        if ui.button(titulo).clicked() {
+                            if self.orden == columna {
+                                self.ascendente = !self.ascendente;
+                            } else {
+                                self.orden = columna;
+                                self.ascendente = false;
+                            }
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                // solo se construyen las filas visibles en pantalla
+                body.rows(alto_fila, visibles.len(), |mut row| {
+                    let proceso = visibles[row.index()];
+                    row.set_selected(self.seleccionado == Some(proceso.get_pid()));
+                    for columna in ColumnaProceso::TODAS {
+                        row.col(|ui| {
+                            ui.label(columna.celda(proceso, self.unidades));
+                        });
+                    }
+                    if row.response().clicked() {
+                        nueva_seleccion = Some(proceso.clone());
+                    }
+                });
+            });
+
+        if let Some(proceso) = nueva_seleccion {
+            self.archivos = None;
+            self.mapas = None;
+            if self.seleccionado == Some(proceso.get_pid()) {
+                self.seleccionado = None;
+                self.ultimo_seleccionado = None;
+            } else {
+                self.seleccionado = Some(proceso.get_pid());
+                self.ultimo_seleccionado = Some(proceso);
+            }
+        }
+    }
+
+    fn mostrar_detalles(&mut self, ui: &mut egui::Ui, sigue_vivo: bool) {
+        ui.horizontal(|ui| {
+            ui.heading("Detalles");
+            if ui.button("Cerrar").clicked() {
+                self.seleccionado = None;
+            }
+        });
+        let Some(proceso) = &self.ultimo_seleccionado else {
+            return;
+        };
+        if !sigue_vivo {
+            ui.colored_label(egui::Color32::YELLOW, "El proceso ya no existe (últimos datos conocidos)");
+        }
+        egui::Grid::new("grid_detalles_proceso").num_columns(2).striped(true).show(ui, |ui| {
+            ui.label("PID");
+            ui.label(proceso.get_pid().to_string());
+            ui.end_row();
+            ui.label("PID padre");
+            ui.label(proceso.get_ppid().map(|p| p.to_string()).unwrap_or_else(|| "-".to_string()));
+            ui.end_row();
+            ui.label("Nombre");
+            ui.label(proceso.get_nombre());
+            ui.end_row();
+            ui.label("Usuario");
+            ui.label(proceso.get_usuario());
+            ui.end_row();
+            ui.label("Estado");
+            ui.label(proceso.get_estado());
+            ui.end_row();
+            ui.label("CPU");
+            ui.label(format!("{:.2}%", proceso.get_uso_cpu()));
+            ui.end_row();
+            ui.label("RSS");
+            ui.label(self.unidades.formatear(proceso.get_uso_memoria()));
+            ui.end_row();
+            ui.label("Memoria virtual");
+            ui.label(self.unidades.formatear(proceso.get_uso_memoria_virtual()));
+            ui.end_row();
+            ui.label("Tiempo de ejecución");
+            ui.label(ProcesoInfo::formatear_tiempo(proceso.get_tiempo_ejecucion()));
+            ui.end_row();
+            ui.label("Tiempo en CPU");
+            ui.label(format!("{} ms", proceso.get_tiempo_en_cpu()));
+            ui.end_row();
+            ui.label("Cgroup");
+            ui.label(proceso.get_cgroup().unwrap_or("-"));
+            ui.end_row();
+        });
+
+        ui.separator();
+        ui.strong("Seguridad");
+        match proceso.get_seguridad() {
+            Some(seguridad) => Self::mostrar_seguridad(ui, seguridad),
+            None => {
+                ui.label("No se pudo leer /proc/<pid>/status");
+            }
+        }
+
+        self.mostrar_archivos_y_mapas(ui);
+    }
+
+    fn mostrar_seguridad(ui: &mut egui::Ui, seguridad: &SeguridadProceso) {
+        if seguridad.get_sospechoso() {
+            let texto = match seguridad.get_capacidades_efectivas() {
+                Some(_) => format!("Capacidades peligrosas fuera del host: {}", seguridad.get_capacidades_peligrosas().join(", ")),
+                None => "Capacidades ilegibles fuera del host".to_string(),
+            };
+            ui.colored_label(egui::Color32::RED, texto);
+        }
+        let ns = seguridad.get_namespaces();
+        let inodo = |v: Option<u64>| v.map(|i| i.to_string()).unwrap_or_else(|| "?".to_string());
+        egui::Grid::new("grid_seguridad_proceso").num_columns(2).striped(true).show(ui, |ui| {
+            ui.label("Namespaces");
+            ui.label(format!(
+                "pid {} net {} mnt {} user {}",
+                inodo(ns.pid), inodo(ns.net), inodo(ns.mnt), inodo(ns.user)
+            ));
+            ui.end_row();
+            ui.label("Fuera del host (pid/net/user)");
+            ui.label(if seguridad.get_fuera_del_host() { "sí" } else { "no" });
+            ui.end_row();
+            ui.label("NoNewPrivs");
+            ui.label(if seguridad.get_no_new_privs() { "sí" } else { "no" });
+            ui.end_row();
+            ui.label("Seccomp");
+            ui.label(seguridad.get_seccomp().map(|m| format!("{:?}", m)).unwrap_or_else(|| "?".to_string()));
+            ui.end_row();
+        });
+        for (titulo, mascara) in [
+            ("Capacidades efectivas", seguridad.get_capacidades_efectivas()),
+            ("Capacidades permitidas", seguridad.get_capacidades_permitidas()),
+        ] {
+            match mascara {
+                Some(mascara) => {
+                    ui.collapsing(format!("{} ({:016x})", titulo, mascara), |ui| {
+                        ui.label(nombres_capacidades(mascara).join(", "));
+                    });
+                }
+                None => {
+                    ui.label(format!("{}: ilegibles", titulo));
+                }
+            }
+        }
+    }
+
+    fn mostrar_archivos_y_mapas(&mut self, ui: &mut egui::Ui) {
+        let Some(proceso) = &self.ultimo_seleccionado else {
+            return;
+        };
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.strong("Archivos abiertos");
+            if ui.button("Cargar").clicked() {
+                self.archivos = Some(proceso.archivos_abiertos().map_err(|e| e.to_string()));
+            }
+        });
+        match &self.archivos {
+            Some(Ok(archivos)) => {
+                ui.collapsing(format!("{} descriptores", archivos.len()), |ui| {
+                    for archivo in archivos {
+                        ui.label(format!("{} [{:?}] {}", archivo.fd, archivo.tipo, archivo.destino));
+                    }
+                });
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+            None => {}
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.strong("Mapas de memoria");
+            if ui.button("Cargar").clicked() {
+                self.mapas = Some(proceso.mapas_memoria().map_err(|e| e.to_string()));
+            }
+        });
+        match &self.mapas {
+            Some(Ok(mapas)) => {
+                ui.label(format!(
+                    "RSS {} kB  PSS {} kB  USS {} kB  Swap {} kB",
+                    mapas.rss_kb, mapas.pss_kb, mapas.uss_kb, mapas.swap_kb
+                ));
+                if !mapas.lineas_invalidas.is_empty() {
+                    ui.collapsing(format!("{} líneas de maps ilegibles", mapas.lineas_invalidas.len()), |ui| {
+                        for linea in &mapas.lineas_invalidas {
+                            ui.label(linea);
+                        }
+                    });
+                }
+                let sospechosas = mapas.regiones_sospechosas();
+                if !sospechosas.is_empty() {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        format!("{} regiones ejecutables sospechosas", sospechosas.len()),
+                    );
+                    for region in sospechosas {
+                        ui.label(format!(
+                            "{:x}-{:x} {} {}",
+                            region.inicio, region.fin, region.permisos,
+                            if region.ruta.is_empty() { "[anónima]" } else { &region.ruta }
+                        ));
+                    }
+                }
+                let bibliotecas = mapas.bibliotecas();
+                ui.collapsing(format!("{} bibliotecas", bibliotecas.len()), |ui| {
+                    for biblioteca in bibliotecas {
+                        ui.label(biblioteca);
+                    }
+                });
+                ui.collapsing(format!("{} regiones", mapas.regiones.len()), |ui| {
+                    for region in &mapas.regiones {
+                        ui.label(format!(
+                            "{:x}-{:x} {} {} kB {}",
+                            region.inicio, region.fin, region.permisos, region.tamano_kb(), region.ruta
+                        ));
+                    }
+                });
+            }
+            Some(Err(e)) => {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+            None => {}
+        }
+    }
+}
+
+impl Default for PanelProcesos {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,550 @@
+//! Módulo que implementa la contabilidad de recursos por cgroup (cgroup v2).
+//! Recorre la jerarquía unificada montada en `/sys/fs/cgroup` y obtiene por cada cgroup:
+//! - Uso de CPU acumulado y porcentaje de uso entre dos lecturas.
+//! - Memoria actual y límite máximo.
+//! - Eventos de memoria (low, high, max, oom, oom_kill).
+//! - Estadísticas de E/S por dispositivo.
+//! - Cantidad de PIDs y lista de procesos del cgroup.
+//!
+//! Además reconoce por la ruta del cgroup los contenedores (docker, podman,
+//! containerd, CRI-O) y las unidades de systemd (slices, servicios y scopes).
+//!
+//! Este módulo define las siguientes estructuras:
+//!
+//! ### `CgroupsInfo`
+//! Representa todos los cgroups encontrados en la jerarquía.
+//! - `raiz`: Punto de montaje de la jerarquía cgroup v2 utilizada.
+//! - `cantidad_cgroups`: Número total de cgroups detectados.
+//! - `cantidad_contenedores`: Número de cgroups reconocidos como contenedores.
+//! - `cgroups`: Vector con la información de cada cgroup (`Vec<CgroupInfo>`).
+//!
+//! #### Métodos de `CgroupsInfo`
+//! - `new`: Constructor que lee la jerarquía dos veces para calcular el uso de CPU.
+//! - `leer`: Lee la jerarquía una sola vez (sin porcentaje de uso de CPU).
+//!   Ambos devuelven `ErrorInstrumentos::SinDatos` si no hay jerarquía cgroup v2 y
+//!   `ErrorInstrumentos::Formato` si un archivo de estadísticas tiene una línea inesperada.
+//! - `calcular_uso_cpu`: Calcula el porcentaje de CPU a partir de una lectura anterior.
+//! - `get_cgroup`: Busca un cgroup por su ruta.
+//! - `cgroup_de_proceso`: Devuelve el cgroup al que pertenece un `ProcesoInfo`.
+//! - `procesos_de`: Devuelve los procesos de `ProcesosInfo` que pertenecen a un cgroup.
+//! - Métodos *getter* para acceder a los campos anteriores.
+//!
+//! ### `CgroupInfo`
+//! Representa la información de un solo cgroup.
+//! - `ruta`: Ruta del cgroup relativa a la raíz (por ejemplo, "/system.slice/docker-<id>.scope").
+//! - `tipo`: Clasificación del cgroup (`TipoCgroup`).
+//! - `uso_cpu_usec`: Tiempo de CPU acumulado en microsegundos.
+//! - `uso_cpu`: Porcentaje de CPU usado entre dos lecturas (100% = un núcleo).
+//! - `memoria_actual`: Memoria usada en MB.
+//! - `memoria_max`: Límite de memoria en MB (`None` si no tiene límite).
+//! - `eventos_memoria`: Contadores de `memory.events`.
+//! - `io`: Estadísticas de E/S por dispositivo (`Vec<IoDispositivo>`).
+//! - `cantidad_pids`: Número de tareas en el cgroup (`pids.current`).
+//! - `pids`: PIDs de los procesos del cgroup (`cgroup.procs`).
+//!
+//! ### `TipoCgroup`
+//! - `Contenedor`: Contenedor de un runtime conocido, con el runtime y el id del contenedor.
+//! - `Slice`, `Servicio`, `Scope`: Unidades de systemd.
+//! - `Otro`: Cualquier otro cgroup.
+//!
+//! ### Función principal
+//! - `obtener_info_cgroups`: Devuelve una instancia de `CgroupsInfo` con la información actual del sistema.
+//! - `cgroup_de_pid`: Lee `/proc/<pid>/cgroup` y devuelve la ruta cgroup v2 del proceso.
+//!
+//! Historial de cambios
+//! - 2026-10-18: Creación del módulo y definición de las estructuras `CgroupsInfo` y `CgroupInfo`.
+//! - 2026-10-18: `mostrar_info` imprime la implementación de `Display`, que usa también la CLI.
+//! - 2026-10-18: `new`, `leer` y `obtener_info_cgroups` devuelven `Resultado`; los archivos mal formados son un error.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::error::{ErrorInstrumentos, Resultado};
+use crate::procesos::{ProcesoInfo, ProcesosInfo};
+
+/// Constante para la conversión de bytes a megabytes.
+const B_TO_MB: u64 = 1024 * 1024;
+
+/// Puntos de montaje donde se busca la jerarquía unificada (modo puro y modo híbrido).
+const RAICES_CGROUP: [&str; 2] = ["/sys/fs/cgroup", "/sys/fs/cgroup/unified"];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CgroupsInfo {
+    raiz: String,
+    cantidad_cgroups: usize,
+    cantidad_contenedores: usize,
+    cgroups: Vec<CgroupInfo>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CgroupInfo {
+    ruta: String,
+    tipo: TipoCgroup,
+    uso_cpu_usec: u64,
+    uso_cpu: f32,
+    memoria_actual: u64,
+    memoria_max: Option<u64>,
+    eventos_memoria: EventosMemoria,
+    io: Vec<IoDispositivo>,
+    cantidad_pids: u64,
+    pids: Vec<i32>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TipoCgroup {
+    Contenedor { runtime: String, id: String },
+    Slice,
+    Servicio,
+    Scope,
+    Otro,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EventosMemoria {
+    pub low: u64,
+    pub high: u64,
+    pub max: u64,
+    pub oom: u64,
+    pub oom_kill: u64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct IoDispositivo {
+    pub dispositivo: String, // "mayor:menor"
+    pub bytes_leidos: u64,
+    pub bytes_escritos: u64,
+    pub operaciones_lectura: u64,
+    pub operaciones_escritura: u64,
+}
+
+impl CgroupsInfo {
+    // Getters
+    /// Devuelve el punto de montaje de la jerarquía cgroup v2.
+    ///
+    /// # Retorno
+    /// La ruta de la raíz, o una cadena vacía si no se encontró la jerarquía.
+    pub fn get_raiz(&self) -> &str { &self.raiz }
+    /// Devuelve la cantidad de cgroups detectados.
+    pub fn get_cantidad_cgroups(&self) -> usize { self.cantidad_cgroups }
+    /// Devuelve la cantidad de cgroups reconocidos como contenedores.
+    pub fn get_cantidad_contenedores(&self) -> usize { self.cantidad_contenedores }
+    /// Devuelve la lista de cgroups.
+    pub fn get_cgroups(&self) -> &Vec<CgroupInfo> { &self.cgroups }
+
+    /// Busca un cgroup por su ruta relativa a la raíz.
+    ///
+    /// # Retorno
+    /// El cgroup encontrado, o `None` si no existe.
+    pub fn get_cgroup(&self, ruta: &str) -> Option<&CgroupInfo> {
+        self.cgroups.iter().find(|c| c.ruta == ruta)
+    }
+
+    /// Devuelve el cgroup al que pertenece un proceso.
+    pub fn cgroup_de_proceso(&self, proceso: &ProcesoInfo) -> Option<&CgroupInfo> {
+        proceso.get_cgroup().and_then(|ruta| self.get_cgroup(ruta))
+    }
+
+    /// Devuelve los procesos que pertenecen al cgroup con la ruta indicada.
+    ///
+    /// # Parámetros
+    /// - `ruta`: Ruta del cgroup.
+    /// - `procesos`: Muestreo de procesos sobre el que se hace la búsqueda.
+    pub fn procesos_de<'a>(&self, ruta: &str, procesos: &'a ProcesosInfo) -> Vec<&'a ProcesoInfo> {
+        procesos
+            .get_procesos()
+            .iter()
+            .filter(|p| p.get_cgroup().is_some_and(|c| c == ruta))
+            .collect()
+    }
+
+    /// Crea una nueva instancia de `CgroupsInfo`.
+    ///
+    /// Lee la jerarquía dos veces separadas por `MINIMUM_CPU_UPDATE_INTERVAL`
+    /// para poder calcular el porcentaje de uso de CPU de cada cgroup.
+    pub fn new() -> Resultado<Self> {
+        let anterior = Self::leer()?;
+        let inicio = Instant::now();
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        let mut actual = Self::leer()?;
+        actual.calcular_uso_cpu(&anterior, inicio.elapsed());
+        Ok(actual)
+    }
+
+    /// Lee la jerarquía de cgroups una sola vez.
+    ///
+    /// El campo `uso_cpu` queda en cero; usar `calcular_uso_cpu` con una lectura anterior.
+    pub fn leer() -> Resultado<Self> {
+        let raiz = Self::buscar_raiz().ok_or(ErrorInstrumentos::SinDatos("cgroups"))?;
+
+        let mut cgroups = Vec::new();
+        let mut pendientes = vec![raiz.clone()];
+        while let Some(dir) = pendientes.pop() {
+            if let Ok(entradas) = fs::read_dir(&dir) {
+                for entrada in entradas.flatten() {
+                    if entrada.file_type().is_ok_and(|t| t.is_dir()) {
+                        pendientes.push(entrada.path());
+                    }
+                }
+            }
+            let relativa = dir.strip_prefix(&raiz).unwrap_or(Path::new(""));
+            let ruta = format!("/{}", relativa.to_string_lossy());
+            cgroups.push(CgroupInfo::leer(&dir, ruta)?);
+        }
+        cgroups.sort_by(|a, b| a.ruta.cmp(&b.ruta));
+
+        Ok(CgroupsInfo {
+            raiz: raiz.to_string_lossy().into_owned(),
+            cantidad_cgroups: cgroups.len(),
+            cantidad_contenedores: cgroups.iter().filter(|c| c.es_contenedor()).count(),
+            cgroups,
+        })
+    }
+
+    /// Calcula el porcentaje de CPU de cada cgroup a partir de una lectura anterior.
+    ///
+    /// # Parámetros
+    /// - `anterior`: Lectura previa de la jerarquía.
+    /// - `transcurrido`: Tiempo transcurrido entre ambas lecturas.
+    pub fn calcular_uso_cpu(&mut self, anterior: &CgroupsInfo, transcurrido: Duration) {
+        let usec = transcurrido.as_micros() as f64;
+        if usec <= 0.0 {
+            return;
+        }
+        for cgroup in &mut self.cgroups {
+            if let Some(previo) = anterior.get_cgroup(&cgroup.ruta) {
+                let delta = cgroup.uso_cpu_usec.saturating_sub(previo.uso_cpu_usec) as f64;
+                cgroup.uso_cpu = (delta / usec * 100.0) as f32;
+            }
+        }
+    }
+
+    fn buscar_raiz() -> Option<PathBuf> {
+        RAICES_CGROUP
+            .iter()
+            .map(PathBuf::from)
+            .find(|r| r.join("cgroup.controllers").exists())
+    }
+
+    pub fn mostrar_info(&self) {
+        print!("{}", self);
+    }
+}
+
+impl CgroupInfo {
+    /// Devuelve la ruta del cgroup relativa a la raíz.
+    pub fn get_ruta(&self) -> &str { &self.ruta }
+    /// Devuelve el tipo de cgroup.
+    pub fn get_tipo(&self) -> &TipoCgroup { &self.tipo }
+    /// Devuelve el tiempo de CPU acumulado en microsegundos.
+    pub fn get_uso_cpu_usec(&self) -> u64 { self.uso_cpu_usec }
+    /// Devuelve el porcentaje de CPU usado entre dos lecturas.
+    pub fn get_uso_cpu(&self) -> f32 { self.uso_cpu }
+    /// Devuelve la memoria usada en MB.
+    pub fn get_memoria_actual(&self) -> u64 { self.memoria_actual }
+    /// Devuelve el límite de memoria en MB, o `None` si no tiene límite.
+    pub fn get_memoria_max(&self) -> Option<u64> { self.memoria_max }
+    /// Devuelve los contadores de eventos de memoria.
+    pub fn get_eventos_memoria(&self) -> &EventosMemoria { &self.eventos_memoria }
+    /// Devuelve las estadísticas de E/S por dispositivo.
+    pub fn get_io(&self) -> &Vec<IoDispositivo> { &self.io }
+    /// Devuelve la cantidad de tareas del cgroup.
+    pub fn get_cantidad_pids(&self) -> u64 { self.cantidad_pids }
+    /// Devuelve los PIDs de los procesos del cgroup.
+    pub fn get_pids(&self) -> &Vec<i32> { &self.pids }
+
+    /// Indica si el cgroup corresponde a un contenedor.
+    pub fn es_contenedor(&self) -> bool {
+        matches!(self.tipo, TipoCgroup::Contenedor { .. })
+    }
+
+    // Los archivos que faltan (la raíz no tiene memory.*, un cgroup que
+    // desaparece a mitad de la lectura) cuentan como vacíos
+    fn leer(dir: &Path, ruta: String) -> Resultado<Self> {
+        let cpu_stat = leer_pares(&dir.join("cpu.stat"))?;
+        let eventos = leer_pares(&dir.join("memory.events"))?;
+        let evento = |clave: &str| eventos.iter().find(|(k, _)| k == clave).map(|(_, v)| *v).unwrap_or(0);
+        let pids: Vec<i32> = fs::read_to_string(dir.join("cgroup.procs"))
+            .map(|c| c.lines().filter_map(|l| l.trim().parse().ok()).collect())
+            .unwrap_or_default();
+        let cantidad_pids = leer_numero(&dir.join("pids.current")).unwrap_or(pids.len() as u64);
+
+        Ok(CgroupInfo {
+            tipo: TipoCgroup::desde_ruta(&ruta),
+            ruta,
+            uso_cpu_usec: cpu_stat.iter().find(|(k, _)| k == "usage_usec").map(|(_, v)| *v).unwrap_or(0),
+            uso_cpu: 0.0,
+            memoria_actual: leer_numero(&dir.join("memory.current")).unwrap_or(0) / B_TO_MB,
+            // "max" significa sin límite
+            memoria_max: leer_numero(&dir.join("memory.max")).map(|m| m / B_TO_MB),
+            eventos_memoria: EventosMemoria {
+                low: evento("low"),
+                high: evento("high"),
+                max: evento("max"),
+                oom: evento("oom"),
+                oom_kill: evento("oom_kill"),
+            },
+            io: leer_io(&dir.join("io.stat"))?,
+            cantidad_pids,
+            pids,
+        })
+    }
+
+    pub fn mostrar_info(&self) {
+        print!("{}", self);
+    }
+}
+
+impl TipoCgroup {
+    /// Clasifica un cgroup a partir de su ruta.
+    ///
+    /// Reconoce los nombres que generan docker (`docker-<id>.scope`, `/docker/<id>`),
+    /// podman (`libpod-<id>.scope`), containerd (`cri-containerd-<id>.scope`),
+    /// CRI-O (`crio-<id>.scope`) y las unidades de systemd por su extensión.
+    pub fn desde_ruta(ruta: &str) -> Self {
+        let componentes: Vec<&str> = ruta.split('/').filter(|c| !c.is_empty()).collect();
+        let Some(ultimo) = componentes.last() else {
+            return TipoCgroup::Otro;
+        };
+
+        const PREFIJOS: [(&str, &str); 5] = [
+            ("docker-", "docker"),
+            ("libpod-conmon-", "podman"),
+            ("libpod-", "podman"),
+            ("cri-containerd-", "containerd"),
+            ("crio-", "cri-o"),
+        ];
+        let sin_scope = ultimo.strip_suffix(".scope").unwrap_or(ultimo);
+        for (prefijo, runtime) in PREFIJOS {
+            if let Some(id) = sin_scope.strip_prefix(prefijo)
+                && es_id_contenedor(id)
+            {
+                return TipoCgroup::Contenedor { runtime: runtime.to_string(), id: id.to_string() };
+            }
+        }
+        // controlador cgroupfs: /docker/<id>, /libpod_parent/libpod-<id>, /kubepods/.../<id>
+        if componentes.len() >= 2 && es_id_contenedor(ultimo) {
+            let padre = componentes[componentes.len() - 2];
+            let runtime = if padre == "docker" {
+                Some("docker")
+            } else if componentes.iter().any(|c| c.starts_with("kubepods")) {
+                Some("containerd")
+            } else {
+                None
+            };
+            if let Some(runtime) = runtime {
+                return TipoCgroup::Contenedor { runtime: runtime.to_string(), id: ultimo.to_string() };
+            }
+        }
+
+        if ultimo.ends_with(".slice") {
+            TipoCgroup::Slice
+        } else if ultimo.ends_with(".service") {
+            TipoCgroup::Servicio
+        } else if ultimo.ends_with(".scope") {
+            TipoCgroup::Scope
+        } else {
+            TipoCgroup::Otro
+        }
+    }
+}
+
+/// Implementación del trait Display para `TipoCgroup`.
+impl std::fmt::Display for TipoCgroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TipoCgroup::Contenedor { runtime, id } => {
+                write!(f, "contenedor {} {}", runtime, &id[..id.len().min(12)])
+            }
+            TipoCgroup::Slice => write!(f, "slice"),
+            TipoCgroup::Servicio => write!(f, "servicio"),
+            TipoCgroup::Scope => write!(f, "scope"),
+            TipoCgroup::Otro => write!(f, "otro"),
+        }
+    }
+}
+
+// Los runtimes usan identificadores hexadecimales de 64 caracteres
+fn es_id_contenedor(id: &str) -> bool {
+    id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn leer_numero(ruta: &Path) -> Option<u64> {
+    fs::read_to_string(ruta).ok()?.trim().parse().ok()
+}
+
+fn leer_pares(ruta: &Path) -> Resultado<Vec<(String, u64)>> {
+    match fs::read_to_string(ruta) {
+        Ok(contenido) => analizar_pares(&contenido, ruta),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+fn leer_io(ruta: &Path) -> Resultado<Vec<IoDispositivo>> {
+    match fs::read_to_string(ruta) {
+        Ok(contenido) => analizar_io(&contenido, ruta),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+fn error_formato(ruta: &Path, numero: usize, linea: &str) -> ErrorInstrumentos {
+    ErrorInstrumentos::Formato { ruta: ruta.display().to_string(), detalle: format!("línea {}: {:?}", numero, linea) }
+}
+
+// Interpreta archivos con formato "clave valor" por línea (cpu.stat, memory.events, memory.stat)
+fn analizar_pares(contenido: &str, ruta: &Path) -> Resultado<Vec<(String, u64)>> {
+    contenido
+        .lines()
+        .enumerate()
+        .filter(|(_, linea)| !linea.trim().is_empty())
+        .map(|(i, linea)| {
+            let mut partes = linea.split_whitespace();
+            match (partes.next(), partes.next().map(str::parse::<u64>), partes.next()) {
+                (Some(clave), Some(Ok(valor)), None) => Ok((clave.to_string(), valor)),
+                _ => Err(error_formato(ruta, i + 1, linea)),
+            }
+        })
+        .collect()
+}
+
+// Interpreta io.stat: "8:0 rbytes=1 wbytes=2 rios=3 wios=4 dbytes=0 dios=0"
+// Las claves desconocidas se ignoran, pero todas deben tener un valor numérico
+fn analizar_io(contenido: &str, ruta: &Path) -> Resultado<Vec<IoDispositivo>> {
+    let mut dispositivos = Vec::new();
+    for (i, linea) in contenido.lines().enumerate() {
+        let mut partes = linea.split_whitespace();
+        let Some(dispositivo) = partes.next() else { continue };
+        let mut io = IoDispositivo { dispositivo: dispositivo.to_string(), ..Default::default() };
+        for parte in partes {
+            let Some((clave, Ok(valor))) = parte.split_once('=').map(|(c, v)| (c, v.parse::<u64>())) else {
+                return Err(error_formato(ruta, i + 1, linea));
+            };
+            match clave {
+                "rbytes" => io.bytes_leidos = valor,
+                "wbytes" => io.bytes_escritos = valor,
+                "rios" => io.operaciones_lectura = valor,
+                "wios" => io.operaciones_escritura = valor,
+                _ => {}
+            }
+        }
+        dispositivos.push(io);
+    }
+    Ok(dispositivos)
+}
+
+/// Devuelve la ruta cgroup v2 de un proceso leyendo `/proc/<pid>/cgroup`.
+///
+/// # Retorno
+/// La ruta de la línea `0::<ruta>`, o `None` si no existe o no se puede leer.
+pub fn cgroup_de_pid(pid: i32) -> Option<String> {
+    let contenido = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    contenido
+        .lines()
+        .find_map(|linea| linea.strip_prefix("0::"))
+        .map(|ruta| ruta.to_string())
+}
+
+pub fn obtener_info_cgroups() -> Resultado<CgroupsInfo> {
+    CgroupsInfo::new()
+}
+
+impl fmt::Display for CgroupsInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Raíz cgroup v2: {}", self.raiz)?;
+        writeln!(f, "Cantidad de cgroups: {}", self.cantidad_cgroups)?;
+        writeln!(f, "Cantidad de contenedores: {}", self.cantidad_contenedores)?;
+        for cgroup in &self.cgroups {
+            write!(f, "{}", cgroup)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CgroupInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cgroup: {}", self.ruta)?;
+        writeln!(f, "Tipo: {}", self.tipo)?;
+        writeln!(f, "Uso de CPU: {:.2}% ({} usec acumulados)", self.uso_cpu, self.uso_cpu_usec)?;
+        match self.memoria_max {
+            Some(max) => writeln!(f, "Memoria: {} MB / {} MB", self.memoria_actual, max)?,
+            None => writeln!(f, "Memoria: {} MB (sin límite)", self.memoria_actual)?,
+        }
+        writeln!(f, "Eventos de memoria: oom {} oom_kill {} max {} high {}",
+            self.eventos_memoria.oom, self.eventos_memoria.oom_kill, self.eventos_memoria.max, self.eventos_memoria.high)?;
+        for io in &self.io {
+            writeln!(f, "E/S {}: leídos {} bytes, escritos {} bytes", io.dispositivo, io.bytes_leidos, io.bytes_escritos)?;
+        }
+        writeln!(f, "PIDs: {}", self.cantidad_pids)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ruta() -> &'static Path {
+        Path::new("/sys/fs/cgroup/prueba/memory.stat")
+    }
+
+    fn detalle_formato<T: fmt::Debug>(resultado: Resultado<T>) -> String {
+        match resultado {
+            Err(ErrorInstrumentos::Formato { ruta, detalle }) => {
+                assert_eq!(ruta, "/sys/fs/cgroup/prueba/memory.stat");
+                detalle
+            }
+            otro => panic!("se esperaba un error de formato: {:?}", otro),
+        }
+    }
+
+    #[test]
+    fn pares_de_memory_stat() {
+        let contenido = "anon 1048576\nfile 4096\n\nkernel_stack 16384\n";
+        let pares = analizar_pares(contenido, ruta()).unwrap();
+        assert_eq!(
+            pares,
+            vec![("anon".to_string(), 1_048_576), ("file".to_string(), 4096), ("kernel_stack".to_string(), 16_384)]
+        );
+    }
+
+    #[test]
+    fn pares_vacios() {
+        assert!(analizar_pares("", ruta()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn pares_con_linea_mal_formada_indican_el_numero_de_linea() {
+        assert!(detalle_formato(analizar_pares("low 0\nhigh\nmax 0\n", ruta())).starts_with("línea 2:"));
+        assert!(detalle_formato(analizar_pares("low 0\nhigh 0\nmax -1\n", ruta())).starts_with("línea 3:"));
+        assert!(detalle_formato(analizar_pares("oom 1 2\n", ruta())).starts_with("línea 1:"));
+    }
+
+    #[test]
+    fn io_stat_con_varios_dispositivos() {
+        let contenido = "8:0 rbytes=1024 wbytes=2048 rios=3 wios=4 dbytes=0 dios=0\n259:0 rbytes=5 wbytes=6 rios=7 wios=8\n";
+        let io = analizar_io(contenido, ruta()).unwrap();
+        assert_eq!(io.len(), 2);
+        assert_eq!(io[0].dispositivo, "8:0");
+        assert_eq!(
+            (io[0].bytes_leidos, io[0].bytes_escritos, io[0].operaciones_lectura, io[0].operaciones_escritura),
+            (1024, 2048, 3, 4)
+        );
+        assert_eq!(io[1].dispositivo, "259:0");
+        assert_eq!(io[1].operaciones_escritura, 8);
+    }
+
+    #[test]
+    fn io_stat_ignora_claves_desconocidas_y_lineas_vacias() {
+        let io = analizar_io("\n8:16 rbytes=10 nueva=99\n", ruta()).unwrap();
+        assert_eq!(io.len(), 1);
+        assert_eq!((io[0].bytes_leidos, io[0].bytes_escritos), (10, 0));
+    }
+
+    #[test]
+    fn io_stat_con_valor_no_numerico_es_error_de_formato() {
+        let contenido = "8:0 rbytes=1 wbytes=2\n8:16 rbytes=abc\n";
+        assert!(detalle_formato(analizar_io(contenido, ruta())).starts_with("línea 2:"));
+        assert!(detalle_formato(analizar_io("8:0 rbytes\n", ruta())).starts_with("línea 1:"));
+    }
+}
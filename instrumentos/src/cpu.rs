@@ -27,6 +27,8 @@
 /// - `get_frecuencia`: Devuelve la frecuencia del CPU en MHz.
 /// - `get_uso_nucleos`: Devuelve un vector con el uso de cada núcleo en porcentaje.
 /// - `new`: Constructor que crea una nueva instancia de `CPUInfo` obteniendo la información del sistema.
+/// - `desde_sistema`: Construye `CPUInfo` desde una instancia de `System` ya refrescada.
+///   Ambos devuelven `ErrorInstrumentos::SinDatos` si el sistema no reporta ningún CPU.
 ///
 /// ### Función principal
 /// - `obtener_info_cpu`: Devuelve una instancia de `CPUInfo` con la información actual del CPU del sistema.
@@ -38,12 +40,17 @@
 /// - 2025-05-05: Creación del módulo y definición de la estructura `CPUInfo`.
 /// - 2025-05-06: Implementacion de metodo get_info() para obtener la información del CPU.
 /// - 2025-05-09: implementacion de funcion apra detectar temperatura pr4ocesadores Intel en Linux
+/// - 2026-10-18: se separa `desde_sistema` para reutilizar un `System` de larga vida (ver `muestreador`).
+/// - 2026-10-18: `new` y `desde_sistema` devuelven `Resultado` en lugar de indexar `cpus()[0]`.
+/// - 2026-10-18: `mostrar_info` imprime la implementación de `Display`, que usa también la CLI.
 
-use serde::Serialize;   
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::process::Command; // Para ejecutar el comando `sensors`
 use sysinfo::{System, RefreshKind, CpuRefreshKind};
+use crate::error::{ErrorInstrumentos, Resultado};
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CPUInfo {
     brand: String,
     cantidad_nucleos: usize,
@@ -85,33 +92,31 @@ impl CPUInfo {
     // Constructor
     // Crea una nueva instancia de CPUInfo
     // Retorno
-    // una nueva instancia de CPUInfo
-    pub fn new() -> Self {
+    // una nueva instancia de CPUInfo, o error si el sistema no reporta CPUs
+    pub fn new() -> Resultado<Self> {
         let mut s = System::new_with_specifics(RefreshKind::nothing().with_cpu(CpuRefreshKind::everything())); //unicamnete refrescar la CPU
         std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL); // MINIMUM_CPU_UPDATE_INTERVAL es definido en sysinfo valor por defecto 100ms
         s.refresh_cpu_all();
-        Self {
-            brand: s.cpus()[0].brand().to_string(),
+        Self::desde_sistema(&s)
+    }
+
+    // Crea una instancia de CPUInfo a partir de un System cuya CPU ya fue refrescada
+    // (el uso por núcleo es relativo al refresco anterior de ese mismo System)
+    pub fn desde_sistema(s: &System) -> Resultado<Self> {
+        // en algunos contenedores /proc/cpuinfo no es visible y la lista llega vacía
+        let primero = s.cpus().first().ok_or(ErrorInstrumentos::SinDatos("cpu"))?;
+        Ok(Self {
+            brand: primero.brand().to_string(),
             cantidad_nucleos: s.cpus().len(),
             //cantidad_nucleos_e: s.cpus().iter().filter(|cpu| cpu.is_stepping()).count(),
             //cantidad_nucleos_p: s.cpus().iter().filter(|cpu| cpu.is_pstate()).count(),
             //nucleos_logicos: s.cpus().iter().map(|cpu| cpu.logical_count()).sum(),
-            frecuencia: s.cpus()[0].frequency(),
+            frecuencia: primero.frequency(),
             uso_nucleos: s.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
-        }
+        })
     }
     pub fn mostrar_info(&self) {
-        println!("Marca del CPU: {}", self.get_brand());
-        println!("Temperatura del CPU:");
-        println!("Cantidad de núcleos: {}", self.get_cantidad_nucleos());
-        //println!("Cantidad de núcleos E: {}", self.get_cantidad_nucleos_e());
-        //println!("Cantidad de núcleos P: {}", self.get_cantidad_nucleos_p());
-        //println!("Núcleos lógicos: {}", self.get_nucleos_logicos());
-        println!("Frecuencia del CPU: {} MHz", self.get_frecuencia());
-        println!("Uso de los núcleos:");
-        for (i, uso) in self.uso_nucleos.iter().enumerate() {
-            println!("Núcleo {}: {:.2} %", i, uso);
-        }
+        print!("{}", self);
     }
 
     fn obtener_temperaturas() -> Result<Vec<String>, String> {
@@ -162,6 +167,23 @@ impl CPUInfo {
 
 
 // Función auxiliar para obtener la información
-pub fn obtener_info_cpu() -> CPUInfo {
+pub fn obtener_info_cpu() -> Resultado<CPUInfo> {
     CPUInfo::new()
 }
+
+impl fmt::Display for CPUInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Marca del CPU: {}", self.get_brand())?;
+        writeln!(f, "Temperatura del CPU:")?;
+        writeln!(f, "Cantidad de núcleos: {}", self.get_cantidad_nucleos())?;
+        //writeln!(f, "Cantidad de núcleos E: {}", self.get_cantidad_nucleos_e());
+        //writeln!(f, "Cantidad de núcleos P: {}", self.get_cantidad_nucleos_p())?;
+        //writeln!(f, "Núcleos lógicos: {}", self.get_nucleos_logicos());
+        writeln!(f, "Frecuencia del CPU: {} MHz", self.get_frecuencia())?;
+        writeln!(f, "Uso de los núcleos:")?;
+        for (i, uso) in self.uso_nucleos.iter().enumerate() {
+            writeln!(f, "Núcleo {}: {:.2} %", i, uso)?;
+        }
+        Ok(())
+    }
+}
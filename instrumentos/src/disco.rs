@@ -25,8 +25,10 @@
 /// - `get_espacio_libre`: Devuelve el espacio libre de los discos.
 /// - `get_espacio_usado`: Devuelve el espacio usado de los discos.
 /// - `get_discos`: Devuelve la lista de discos.
-/// - `get_disco`: Devuelve la información de un disco específico por índice.
+/// - `get_disco`: Devuelve la información de un disco específico por índice (`None` si no existe).
 /// - `new`: Constructor que crea una nueva instancia de `DiscosInfo` obteniendo la información del sistema.
+/// - `desde_discos`: Construye `DiscosInfo` desde una lista `Disks` ya refrescada.
+///   Ambos devuelven `ErrorInstrumentos::SinDatos` si el sistema no reporta ningún disco.
 ///
 /// ### `DiscoInfo`
 /// Representa la información detallada de un disco específico.
@@ -48,6 +50,7 @@
 /// - `get_ruta`: Devuelve la ruta de montaje del disco.
 /// - `get_removible`: Indica si el disco es removible.
 /// - `get_solo_lectura`: Indica si el disco es de solo lectura.
+/// - `get_bytes_total`, `get_bytes_libres`, `get_bytes_usados`: El espacio en bytes, sin redondear a GB.
 /// - `new`: Constructor que crea una nueva instancia de `DiscoInfo` con los datos proporcionados.
 ///
 /// ### Constantes
@@ -59,13 +62,20 @@
 /// historial de cambios
 /// - 2025-05-05: Creación del módulo y definición de las estructuras `DiscosInfo` y `DiscoInfo`.
 /// - 2025-05-06: Implementación de métodos para obtener información de los discos.
+/// - 2026-10-18: Se separa `desde_discos` para reutilizar una lista `Disks` de larga vida.
+/// - 2026-10-18: `get_disco` devuelve `Option` en lugar de entrar en pánico.
+/// - 2026-10-18: `mostrar_info` imprime la implementación de `Display`, que usa también la CLI.
+/// - 2026-10-18: `DiscoInfo` guarda también el espacio en bytes para exportarlo sin pérdida.
+/// - 2026-10-18: `new`, `desde_discos` y `obtener_info_disco` devuelven `Resultado`.
 
 /// TODO: obtener información de velocidad y tiempo de respuesta
 /// TODO: espacio usado y libre en porcentaje
-use serde::{Serialize};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use sysinfo::{Disks};
+use crate::error::{ErrorInstrumentos, Resultado};
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DiscosInfo {
     cantidad_discos: usize,
     espacio_total: f64,
@@ -73,7 +83,7 @@ pub struct DiscosInfo {
     espacio_usado: f64,
     discos: Vec<DiscoInfo>,
 }
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DiscoInfo {
     nombre: String,
     sistema_archivos: String,
@@ -83,6 +93,11 @@ pub struct DiscoInfo {
     ruta: String,
     removible: bool,
     solo_lectura: bool,
+    // el espacio en bytes; falta en snapshots de versiones anteriores
+    #[serde(default)]
+    bytes_total: u64,
+    #[serde(default)]
+    bytes_libres: u64,
     //velocidad: u64,
     //tiempo_respuesta: u64,
 }
@@ -113,19 +128,25 @@ impl DiscosInfo {
     pub fn get_discos(&self) -> &Vec<DiscoInfo> { &self.discos }
     // Devuelve el disco en la posicion index
     // Retorno
-    // el disco en la posicion index como un DiscoInfo
-    pub fn get_disco(&self, index: usize) -> &DiscoInfo {
-        if index < self.discos.len() {
-            &self.discos[index]
-        } else {
-            panic!("Índice fuera de rango");
-        }
+    // el disco en la posicion index como un DiscoInfo, o None si el índice está fuera de rango
+    pub fn get_disco(&self, index: usize) -> Option<&DiscoInfo> {
+        self.discos.get(index)
     }
     // Constructor
     // Crea una nueva instancia de DiscosInfo
     // Retorno
-    pub fn new() -> Self {
+    // una nueva instancia de DiscosInfo, o error si el sistema no reporta discos
+    pub fn new() -> Resultado<Self> {
         let disks = Disks::new_with_refreshed_list();
+        Self::desde_discos(&disks)
+    }
+
+    // Crea una instancia de DiscosInfo a partir de una lista de discos ya refrescada
+    pub fn desde_discos(disks: &Disks) -> Resultado<Self> {
+        // sin /proc/mounts visible la lista llega vacía
+        if disks.list().is_empty() {
+            return Err(ErrorInstrumentos::SinDatos("disco"));
+        }
         let mut discos: Vec<DiscoInfo> = Vec::new();
         let mut total = 0.0;
         let mut cantidad = 0;
@@ -140,42 +161,33 @@ impl DiscosInfo {
             let l = disk.available_space() / B_TO_GB;
             total += t as f64;
             libre += l as f64;
-            discos.push(DiscoInfo::new(
-                disk.name().to_string_lossy().to_string(),
-                disk.file_system().to_string_lossy().to_string(),
-                t as f64,
-                l as f64,
-                (t - l) as f64,
-                disk.mount_point().to_string_lossy().to_string(),
-                disk.is_removable(),
-                disk.is_read_only(),
-            ));
+            discos.push(DiscoInfo {
+                bytes_total: disk.total_space(),
+                bytes_libres: disk.available_space(),
+                ..DiscoInfo::new(
+                    disk.name().to_string_lossy().to_string(),
+                    disk.file_system().to_string_lossy().to_string(),
+                    t as f64,
+                    l as f64,
+                    (t - l) as f64,
+                    disk.mount_point().to_string_lossy().to_string(),
+                    disk.is_removable(),
+                    disk.is_read_only(),
+                )
+            });
         }
         
 
-        DiscosInfo {
+        Ok(DiscosInfo {
             cantidad_discos: cantidad,
             espacio_total: total,
             espacio_libre: libre,
             espacio_usado: total - libre,
             discos,
-        }
+        })
     }
     pub fn mostrar_info(&self) {
-        println!("Cantidad de discos: {}", self.cantidad_discos);
-        println!("Espacio total: {} GB", self.espacio_total);
-        println!("Espacio libre: {} GB", self.espacio_libre);
-        println!("Espacio usado: {} GB", self.espacio_usado);
-        for disco in &self.discos {
-            println!("Disco: {}", disco.get_nombre());
-            println!("Sistema de archivos: {}", disco.get_sistema_archivos());
-            println!("Espacio total: {} GB", disco.get_espacio_total());
-            println!("Espacio libre: {} GB", disco.get_espacio_libre());
-            println!("Espacio usado: {} GB", disco.get_espacio_usado());
-            println!("Ruta: {}", disco.get_ruta());
-            println!("Removible: {}", disco.get_removible());
-            println!("Solo lectura: {}", disco.get_solo_lectura());
-        }
+        print!("{}", self);
     }
 }
 
@@ -260,10 +272,40 @@ impl DiscoInfo {
             ruta,
             removible,
             solo_lectura,
+            bytes_total: 0,
+            bytes_libres: 0,
         }
     }
-    
+
+    /// Devuelve el espacio total del disco en bytes.
+    pub fn get_bytes_total(&self) -> u64 { self.bytes_total }
+
+    /// Devuelve el espacio disponible del disco en bytes.
+    pub fn get_bytes_libres(&self) -> u64 { self.bytes_libres }
+
+    /// Devuelve el espacio usado del disco en bytes.
+    pub fn get_bytes_usados(&self) -> u64 { self.bytes_total.saturating_sub(self.bytes_libres) }
 }
-pub fn obtener_info_disco() -> DiscosInfo {
+pub fn obtener_info_disco() -> Resultado<DiscosInfo> {
     DiscosInfo::new()
 }
+
+impl fmt::Display for DiscosInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cantidad de discos: {}", self.cantidad_discos)?;
+        writeln!(f, "Espacio total: {} GB", self.espacio_total)?;
+        writeln!(f, "Espacio libre: {} GB", self.espacio_libre)?;
+        writeln!(f, "Espacio usado: {} GB", self.espacio_usado)?;
+        for disco in &self.discos {
+            writeln!(f, "Disco: {}", disco.get_nombre())?;
+            writeln!(f, "Sistema de archivos: {}", disco.get_sistema_archivos())?;
+            writeln!(f, "Espacio total: {} GB", disco.get_espacio_total())?;
+            writeln!(f, "Espacio libre: {} GB", disco.get_espacio_libre())?;
+            writeln!(f, "Espacio usado: {} GB", disco.get_espacio_usado())?;
+            writeln!(f, "Ruta: {}", disco.get_ruta())?;
+            writeln!(f, "Removible: {}", disco.get_removible())?;
+            writeln!(f, "Solo lectura: {}", disco.get_solo_lectura())?;
+        }
+        Ok(())
+    }
+}
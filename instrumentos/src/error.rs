@@ -0,0 +1,61 @@
+//! Módulo que define el tipo de error del crate.
+//!
+//! Las fuentes que pueden fallar (archivos de `/proc` y `/sys` ilegibles, un
+//! sistema que no reporta CPUs, etc.) devuelven `Resultado<T>` en lugar de
+//! entrar en pánico, para que el ciclo de monitoreo pueda conservar la última
+//! lectura válida de esa fuente y seguir con las demás.
+//!
+//! ### `ErrorInstrumentos`
+//! - `Lectura`: No se pudo leer un archivo (incluye la ruta y el error de E/S).
+//! - `Formato`: El contenido leído no tiene el formato esperado.
+//! - `SinDatos`: El sistema no reportó datos para la fuente.
+//! - `Panico`: La fuente entró en pánico durante el muestreo.
+//! - `Senal`: No se pudo enviar una señal a un proceso.
+//!
+//! ### `Resultado<T>`
+//! Alias de `Result<T, ErrorInstrumentos>`.
+//!
+//! Historial de cambios
+//! - 2026-10-18: Creación del módulo.
+//! - 2026-10-18: Se agrega `Senal` para `procesos::enviar_senal`.
+
+use std::{error, fmt, io};
+
+#[derive(Debug)]
+pub enum ErrorInstrumentos {
+    Lectura { ruta: String, fuente: io::Error },
+    Formato { ruta: String, detalle: String },
+    SinDatos(&'static str),
+    Panico { fuente: String, mensaje: String },
+    Senal { pid: i32, detalle: String },
+}
+
+pub type Resultado<T> = Result<T, ErrorInstrumentos>;
+
+impl ErrorInstrumentos {
+    /// Construye un error de lectura para la ruta indicada.
+    pub fn lectura(ruta: impl Into<String>, fuente: io::Error) -> Self {
+        ErrorInstrumentos::Lectura { ruta: ruta.into(), fuente }
+    }
+}
+
+impl fmt::Display for ErrorInstrumentos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorInstrumentos::Lectura { ruta, fuente } => write!(f, "no se pudo leer {}: {}", ruta, fuente),
+            ErrorInstrumentos::Formato { ruta, detalle } => write!(f, "formato inesperado en {}: {}", ruta, detalle),
+            ErrorInstrumentos::SinDatos(fuente) => write!(f, "el sistema no reportó datos de {}", fuente),
+            ErrorInstrumentos::Panico { fuente, mensaje } => write!(f, "la fuente {} falló: {}", fuente, mensaje),
+            ErrorInstrumentos::Senal { pid, detalle } => write!(f, "no se pudo enviar la señal al proceso {}: {}", pid, detalle),
+        }
+    }
+}
+
+impl error::Error for ErrorInstrumentos {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ErrorInstrumentos::Lectura { fuente, .. } => Some(fuente),
+            _ => None,
+        }
+    }
+}
@@ -24,6 +24,7 @@
 /// #### Métodos de `InterfacesInfo`
 /// - `new`: Constructor principal que obtiene la información desde el sistema.
 /// - `desde_sistema`: Crea una instancia a partir de una referencia a `Networks`.
+///   Ambos devuelven `ErrorInstrumentos::SinDatos` si el sistema no reporta ninguna interfaz.
 /// - Métodos *getter* para acceder a cada uno de los campos anteriores.
 ///
 /// ### `InterfaceInfo`
@@ -47,12 +48,17 @@
 ///
 /// historial de cambios
 /// - 2025-04-06: Creación del módulo y definición de estructuras `InterfacesInfo` y `InterfaceInfo`, junto con sus métodos y metodo para mostrar informacion.
+/// - 2026-10-18: `get_interfaz` devuelve `Option` en lugar de entrar en pánico.
+/// - 2026-10-18: `mostrar_info` imprime la implementación de `Display`, que usa también la CLI.
+/// - 2026-10-18: `new`, `desde_sistema` y `obtener_info_interfaces` devuelven `Resultado`.
 
 use sysinfo::{Networks, IpNetwork};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::error::{ErrorInstrumentos, Resultado};
 
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InterfacesInfo {
     cantidad_interfaces: u64,
     interfaces: Vec<InterfaceInfo>,
@@ -66,7 +72,7 @@ pub struct InterfacesInfo {
     total_mtu: u64,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InterfaceInfo {
     nombre: String,
     bytes_recibidos: u64,
@@ -139,17 +145,13 @@ impl InterfacesInfo {
     /// - `index`: El índice de la interfaz en el vector.
     ///
     /// # Retorno
-    /// Una referencia a la información de la interfaz como un `InterfaceInfo`.
-    pub fn get_interfaz(&self, index: u64) -> &InterfaceInfo {
-        if index < self.interfaces.len().try_into().unwrap() {
-            &self.interfaces[index as usize]
-        } else {
-            panic!("Índice fuera de rango");
-        }
+    /// Una referencia a la información de la interfaz, o `None` si el índice está fuera de rango.
+    pub fn get_interfaz(&self, index: u64) -> Option<&InterfaceInfo> {
+        self.interfaces.get(usize::try_from(index).ok()?)
     }
 
     /// Crea una nueva instancia de `InterfacesInfo` con datos del sistema.
-    pub fn new() -> Self {
+    pub fn new() -> Resultado<Self> {
         let interfaces = Networks::new_with_refreshed_list();
         Self::desde_sistema(&interfaces)
     }
@@ -160,8 +162,12 @@ impl InterfacesInfo {
     /// - `interfaces`: Referencia a las interfaces de red del sistema.
     ///
     /// # Retorno
-    /// Una nueva instancia de `InterfacesInfo`.
-    pub fn desde_sistema(interfaces: &Networks) -> Self {
+    /// Una nueva instancia de `InterfacesInfo`, o error si la lista está vacía
+    /// (sin `/proc/net/dev` visible no aparece ni siquiera `lo`).
+    pub fn desde_sistema(interfaces: &Networks) -> Resultado<Self> {
+        if interfaces.is_empty() {
+            return Err(ErrorInstrumentos::SinDatos("red"));
+        }
         let mut cantidad_interfaces = 0;
         let mut total_errores = 0;
         let mut total_bytes_recibidos = 0;
@@ -207,7 +213,7 @@ impl InterfacesInfo {
             interfaces_vec.push(interface);
                     }
 
-        InterfacesInfo {
+        Ok(InterfacesInfo {
             cantidad_interfaces,
             interfaces: interfaces_vec,
             total_errores,
@@ -218,22 +224,10 @@ impl InterfacesInfo {
             total_direcciones_ip,
             total_direcciones_mac,
             total_mtu,
-        }
+        })
     }
     pub fn mostrar_info(&self) {
-        println!("Cantidad de interfaces: {}", self.cantidad_interfaces);
-        println!("Total de errores: {}", self.total_errores);
-        println!("Bytes recibidos: {}", self.total_bytes_recibidos);
-        println!("Bytes enviados: {}", self.total_bytes_enviados);
-        println!("Paquetes recibidos: {}", self.total_paquetes_recibidos);
-        println!("Paquetes enviados: {}", self.total_paquetes_enviados);
-        println!("Direcciones IP: {}", self.total_direcciones_ip);
-        println!("Direcciones MAC: {}", self.total_direcciones_mac);
-        println!("MTU total: {}", self.total_mtu);
-        for interface in &self.interfaces {
-            interface.mostrar_info();
-        }
-        println!("-----------------------------------");
+        print!("{}", self);
     }
 }
 
@@ -365,24 +359,50 @@ impl InterfaceInfo {
         }
     }
     pub fn mostrar_info(&self) {
-        println!("Nombre: {}", self.nombre);
-        println!("Bytes recibidos: {}", self.bytes_recibidos);
-        println!("Bytes enviados: {}", self.bytes_enviados);
-        println!("Paquetes recibidos: {}", self.numero_paquetes_recibidos);
-        println!("Paquetes enviados: {}", self.numero_paquetes_enviados);
-        println!("Total de errores: {}", self.total_errores);
-        println!("Errores recibidos: {}", self.total_errores_recibidos);
-        println!("Errores enviados: {}", self.total_errores_enviados);
-        println!("Direcciones IP: {:?}", self.direccion_ip);
-        println!("Dirección MAC: {}", self.direccion_mac);
-        println!("MTU: {}", self.mtu);
+        print!("{}", self);
     }
 }
 
 /// Obtiene información sobre las interfaces de red del sistema.
 ///
 /// # Retorno
-/// Una instancia de `InterfacesInfo` con los datos de las interfaces.
-pub fn obtener_info_interfaces() -> InterfacesInfo {
+/// Una instancia de `InterfacesInfo` con los datos de las interfaces, o error si no hay ninguna.
+pub fn obtener_info_interfaces() -> Resultado<InterfacesInfo> {
     InterfacesInfo::new()
 }
+
+impl fmt::Display for InterfacesInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cantidad de interfaces: {}", self.cantidad_interfaces)?;
+        writeln!(f, "Total de errores: {}", self.total_errores)?;
+        writeln!(f, "Bytes recibidos: {}", self.total_bytes_recibidos)?;
+        writeln!(f, "Bytes enviados: {}", self.total_bytes_enviados)?;
+        writeln!(f, "Paquetes recibidos: {}", self.total_paquetes_recibidos)?;
+        writeln!(f, "Paquetes enviados: {}", self.total_paquetes_enviados)?;
+        writeln!(f, "Direcciones IP: {}", self.total_direcciones_ip)?;
+        writeln!(f, "Direcciones MAC: {}", self.total_direcciones_mac)?;
+        writeln!(f, "MTU total: {}", self.total_mtu)?;
+        for interface in &self.interfaces {
+            write!(f, "{}", interface)?;
+        }
+        writeln!(f, "-----------------------------------")?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for InterfaceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Nombre: {}", self.nombre)?;
+        writeln!(f, "Bytes recibidos: {}", self.bytes_recibidos)?;
+        writeln!(f, "Bytes enviados: {}", self.bytes_enviados)?;
+        writeln!(f, "Paquetes recibidos: {}", self.numero_paquetes_recibidos)?;
+        writeln!(f, "Paquetes enviados: {}", self.numero_paquetes_enviados)?;
+        writeln!(f, "Total de errores: {}", self.total_errores)?;
+        writeln!(f, "Errores recibidos: {}", self.total_errores_recibidos)?;
+        writeln!(f, "Errores enviados: {}", self.total_errores_enviados)?;
+        writeln!(f, "Direcciones IP: {:?}", self.direccion_ip)?;
+        writeln!(f, "Dirección MAC: {}", self.direccion_mac)?;
+        writeln!(f, "MTU: {}", self.mtu)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+//! Módulo que implementa la obtención del inventario del equipo: datos que casi
+//! nunca cambian y que por eso se muestrean con poca frecuencia (cada hora).
+//! - Nombre del equipo.
+//! - Sistema operativo, versión y kernel.
+//! - Arquitectura y modelo del CPU.
+//! - Núcleos físicos y lógicos.
+//! - Memoria RAM y swap instaladas.
+//! - Hora de arranque.
+//!
+//! ### `InventarioInfo`
+//! - `nombre_equipo`: Nombre de host.
+//! - `sistema_operativo`: Nombre largo del sistema operativo.
+//! - `version_kernel`: Versión del kernel.
+//! - `arquitectura`: Arquitectura del CPU.
+//! - `modelo_cpu`: Marca y modelo del CPU.
+//! - `nucleos_fisicos`: Núcleos físicos (0 si no se pudo determinar).
+//! - `nucleos_logicos`: Núcleos lógicos.
+//! - `memoria_total`: Memoria RAM instalada en MB.
+//! - `swap_total`: Swap configurada en MB.
+//! - `arranque`: Hora de arranque en segundos desde la época UNIX.
+//!
+//! #### Métodos de `InventarioInfo`
+//! - `get_*`: Devuelven cada uno de los campos.
+//! - `desde_sistema`: Construye el inventario desde una instancia de `System` con CPU y memoria cargadas.
+//!   Devuelve `ErrorInstrumentos::SinDatos` si el sistema no reporta ni CPUs ni memoria.
+//! - `mostrar_info`: Imprime el inventario (ver la implementación de `Display`).
+//!
+//! Historial de cambios
+//! - 2026-10-18: Creación del módulo.
+//! - 2026-10-18: `desde_sistema` devuelve `Resultado`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use sysinfo::System;
+use crate::error::{ErrorInstrumentos, Resultado};
+
+const B_TO_MB: u64 = 1024 * 1024;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InventarioInfo {
+    nombre_equipo: String,
+    sistema_operativo: String,
+    version_kernel: String,
+    arquitectura: String,
+    modelo_cpu: String,
+    nucleos_fisicos: usize,
+    nucleos_logicos: usize,
+    memoria_total: u64,
+    swap_total: u64,
+    arranque: u64,
+}
+
+impl InventarioInfo {
+    pub fn get_nombre_equipo(&self) -> &str {&self.nombre_equipo}
+    pub fn get_sistema_operativo(&self) -> &str {&self.sistema_operativo}
+    pub fn get_version_kernel(&self) -> &str {&self.version_kernel}
+    pub fn get_arquitectura(&self) -> &str {&self.arquitectura}
+    pub fn get_modelo_cpu(&self) -> &str {&self.modelo_cpu}
+    pub fn get_nucleos_fisicos(&self) -> usize {self.nucleos_fisicos}
+    pub fn get_nucleos_logicos(&self) -> usize {self.nucleos_logicos}
+    pub fn get_memoria_total(&self) -> u64 {self.memoria_total}
+    pub fn get_swap_total(&self) -> u64 {self.swap_total}
+    pub fn get_arranque(&self) -> u64 {self.arranque}
+
+    /// Construye el inventario a partir de un `System` con CPU y memoria ya refrescados.
+    ///
+    /// Los datos que falten quedan como "desconocido" o 0; solo es un error que
+    /// no haya ni CPUs ni memoria, porque entonces `/proc` no es visible.
+    pub fn desde_sistema(sistema: &System) -> Resultado<Self> {
+        if sistema.cpus().is_empty() && sistema.total_memory() == 0 {
+            return Err(ErrorInstrumentos::SinDatos("inventario"));
+        }
+        let desconocido = || "desconocido".to_string();
+        Ok(InventarioInfo {
+            nombre_equipo: System::host_name().unwrap_or_else(desconocido),
+            sistema_operativo: System::long_os_version().unwrap_or_else(desconocido),
+            version_kernel: System::kernel_version().unwrap_or_else(desconocido),
+            arquitectura: System::cpu_arch(),
+            modelo_cpu: sistema
+                .cpus()
+                .first()
+                .map(|cpu| cpu.brand().trim().to_string())
+                .unwrap_or_else(desconocido),
+            nucleos_fisicos: System::physical_core_count().unwrap_or(0),
+            nucleos_logicos: sistema.cpus().len(),
+            memoria_total: sistema.total_memory() / B_TO_MB,
+            swap_total: sistema.total_swap() / B_TO_MB,
+            arranque: System::boot_time(),
+        })
+    }
+    pub fn mostrar_info(&self) {
+        print!("{}", self);
+    }
+}
+
+impl fmt::Display for InventarioInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Equipo: {}", self.nombre_equipo)?;
+        writeln!(f, "Sistema operativo: {}", self.sistema_operativo)?;
+        writeln!(f, "Kernel: {}", self.version_kernel)?;
+        writeln!(f, "Arquitectura: {}", self.arquitectura)?;
+        writeln!(f, "CPU: {}", self.modelo_cpu)?;
+        writeln!(f, "Núcleos: {} físicos, {} lógicos", self.nucleos_fisicos, self.nucleos_logicos)?;
+        writeln!(f, "Memoria: {} MB, swap: {} MB", self.memoria_total, self.swap_total)?;
+        writeln!(f, "Arranque: {} (segundos UNIX)", self.arranque)
+    }
+}
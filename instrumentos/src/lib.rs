@@ -1,9 +1,14 @@
 
+pub mod cgroups;
 pub mod cpu;
 pub mod disco;
+pub mod error;
 pub mod interfaces;
+pub mod inventario;
 pub mod memoria;
+pub mod muestreador;
 pub mod procesos;
+pub mod recolector;
 
 
 pub fn add(left: u64, right: u64) -> u64 {
@@ -15,6 +15,7 @@
 /// - `swap_total`: Memoria swap total en MB.
 /// - `swap_libre`: Memoria swap libre en MB.
 /// - `swap_usada`: Memoria swap usada en MB.
+/// - `*_bytes`: Los mismos valores de RAM y swap en bytes, sin redondear a MB.
 ///
 /// #### Métodos de `MemoriaInfo`
 /// - `get_memoria_total`: Devuelve la memoria RAM total del sistema.
@@ -27,8 +28,11 @@
 /// - `get_swap_libre`: Devuelve la memoria swap libre del sistema.
 /// - `get_swap_usada`: Devuelve la memoria swap usada del sistema.
 /// - `get_memoria_total_sistema`: Devuelve la suma de RAM y swap totales.
+/// - `get_total_ram_bytes`, `get_libre_ram_bytes`, `get_usada_ram_bytes`, `get_swap_total_bytes`,
+///   `get_swap_libre_bytes`, `get_swap_usada_bytes`: Los mismos valores en bytes.
 /// - `new`: Constructor que crea una nueva instancia de `MemoriaInfo`.
 /// - `desde_sistema`: Método auxiliar para construir `MemoriaInfo` desde una instancia de `System`.
+///   Ambos devuelven `ErrorInstrumentos::SinDatos` si el sistema reporta 0 bytes de RAM.
 ///
 /// ### Constantes
 /// - `B_TO_MB`: Constante para la conversión de bytes a megabytes.
@@ -45,14 +49,19 @@
  - `2025-05-05`: Creación del módulo y definición de la estructura `MemoriaInfo`.
     - `2025-05-06`: Implementación de métodos para obtener información de memoria.
     - '2025-05-08': se agrego modulos de memoria total, total libre y total usada
+    - `2026-10-18`: `mostrar_info` imprime la implementación de `Display`, que usa también la CLI.
+    - `2026-10-18`: se guardan también los valores en bytes (`get_*_bytes`) para exportarlos sin pérdida.
+    - `2026-10-18`: `new`, `desde_sistema` y `obtener_info_memoria` devuelven `Resultado`.
 */
 use sysinfo::{System, RefreshKind};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use crate::error::{ErrorInstrumentos, Resultado};
 
 //conversiones de bytes a MB y GB
 const B_TO_MB: u64 = 1024 * 1024;
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MemoriaInfo {
     total: u64,
     libre: u64,
@@ -63,6 +72,19 @@ pub struct MemoriaInfo {
     swap_total: u64,
     swap_libre: u64,
     swap_usada: u64,
+    // los mismos valores en bytes; faltan en snapshots de versiones anteriores
+    #[serde(default)]
+    total_ram_bytes: u64,
+    #[serde(default)]
+    libre_ram_bytes: u64,
+    #[serde(default)]
+    usada_ram_bytes: u64,
+    #[serde(default)]
+    swap_total_bytes: u64,
+    #[serde(default)]
+    swap_libre_bytes: u64,
+    #[serde(default)]
+    swap_usada_bytes: u64,
     //cache_total: u64,
     //cache_libre: u64,
     //cache_usada: u64,
@@ -144,20 +166,49 @@ impl MemoriaInfo {
     pub fn get_memoria_total_sistema(&self) -> u64 {
         self.total + self.swap_total
     }
+    /// Como `get_total_ram`, pero en bytes.
+    pub fn get_total_ram_bytes(&self) -> u64 {
+        self.total_ram_bytes
+    }
+    /// Como `get_libre_ram`, pero en bytes.
+    pub fn get_libre_ram_bytes(&self) -> u64 {
+        self.libre_ram_bytes
+    }
+    /// Como `get_usada_ram`, pero en bytes.
+    pub fn get_usada_ram_bytes(&self) -> u64 {
+        self.usada_ram_bytes
+    }
+    /// Como `get_swap_total`, pero en bytes.
+    pub fn get_swap_total_bytes(&self) -> u64 {
+        self.swap_total_bytes
+    }
+    /// Como `get_swap_libre`, pero en bytes.
+    pub fn get_swap_libre_bytes(&self) -> u64 {
+        self.swap_libre_bytes
+    }
+    /// Como `get_swap_usada`, pero en bytes.
+    pub fn get_swap_usada_bytes(&self) -> u64 {
+        self.swap_usada_bytes
+    }
 
     // Crear una nueva instancia
     /// Crea una nueva instancia de MemoriaInfo.
     ///
     /// # Retorno
-    /// Una nueva instancia de MemoriaInfo con la información de memoria del sistema.
-    pub fn new() -> Self {
+    /// Una nueva instancia de MemoriaInfo con la información de memoria del sistema,
+    /// o error si el sistema no reporta memoria.
+    pub fn new() -> Resultado<Self> {
         let mut s = System::new_with_specifics(RefreshKind::everything()); // Obtener únicamente información de memoria
         s.refresh_memory(); // Actualizar la memoria para obtener la información más reciente
         Self::desde_sistema(&s)
     }
 
-    pub fn desde_sistema(s: &System) -> Self {
-        MemoriaInfo {
+    pub fn desde_sistema(s: &System) -> Resultado<Self> {
+        // sin /proc/meminfo visible sysinfo reporta todo en cero
+        if s.total_memory() == 0 {
+            return Err(ErrorInstrumentos::SinDatos("memoria"));
+        }
+        Ok(MemoriaInfo {
             total: s.total_memory() / B_TO_MB + s.total_swap() / B_TO_MB,
             usada: s.used_memory() / B_TO_MB + s.used_swap() / B_TO_MB,
             libre: (s.total_memory() - s.used_memory()) / B_TO_MB + (s.total_swap() - s.used_swap()) / B_TO_MB,
@@ -167,21 +218,34 @@ impl MemoriaInfo {
             swap_total: s.total_swap() / B_TO_MB,
             swap_libre: s.free_swap() / B_TO_MB,
             swap_usada: s.used_swap() / B_TO_MB,
-        }
+            total_ram_bytes: s.total_memory(),
+            libre_ram_bytes: s.free_memory(),
+            usada_ram_bytes: s.used_memory(),
+            swap_total_bytes: s.total_swap(),
+            swap_libre_bytes: s.free_swap(),
+            swap_usada_bytes: s.used_swap(),
+        })
     }
     pub fn mostrar_info(&self) {
-        println!("Memoria total: {} MB", self.get_memoria_total());
-        println!("Memoria libre: {} MB", self.get_memoria_libre());
-        println!("Memoria usada: {} MB", self.get_memoria_usada());
-        println!("Memoria RAM total: {} MB", self.get_total_ram());
-        println!("Memoria RAM libre: {} MB", self.get_libre_ram());
-        println!("Memoria RAM usada: {} MB", self.get_usada_ram());
-        println!("Swap total: {} MB", self.get_swap_total());
-        println!("Swap libre: {} MB", self.get_swap_libre());
-        println!("Swap usada: {} MB", self.get_swap_usada());
+        print!("{}", self);
     }
 }
 
-pub fn obtener_info_memoria() -> MemoriaInfo {
+pub fn obtener_info_memoria() -> Resultado<MemoriaInfo> {
     MemoriaInfo::new()
-}
\ No newline at end of file
+}
+
+impl fmt::Display for MemoriaInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Memoria total: {} MB", self.get_memoria_total())?;
+        writeln!(f, "Memoria libre: {} MB", self.get_memoria_libre())?;
+        writeln!(f, "Memoria usada: {} MB", self.get_memoria_usada())?;
+        writeln!(f, "Memoria RAM total: {} MB", self.get_total_ram())?;
+        writeln!(f, "Memoria RAM libre: {} MB", self.get_libre_ram())?;
+        writeln!(f, "Memoria RAM usada: {} MB", self.get_usada_ram())?;
+        writeln!(f, "Swap total: {} MB", self.get_swap_total())?;
+        writeln!(f, "Swap libre: {} MB", self.get_swap_libre())?;
+        writeln!(f, "Swap usada: {} MB", self.get_swap_usada())?;
+        Ok(())
+    }
+}
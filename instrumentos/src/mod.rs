@@ -1,5 +1,10 @@
 pub mod cpu;
 pub mod memoria;
 pub mod disco;
+pub mod error;
 pub mod interfaces;
-pub mod procesos;
\ No newline at end of file
+pub mod inventario;
+pub mod procesos;
+pub mod cgroups;
+pub mod muestreador;
+pub mod recolector;
\ No newline at end of file
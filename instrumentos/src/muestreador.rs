@@ -0,0 +1,139 @@
+//! Módulo que implementa un muestreador con estado que reutiliza los manejadores
+//! de `sysinfo` entre muestras.
+//!
+//! Los constructores `new()` de cada módulo crean un `System`, `Disks` o `Networks`
+//! nuevo y duermen para poder calcular deltas (100 ms para CPU, 5 s para procesos).
+//! `Muestreador` mantiene esos manejadores vivos y los refresca de forma incremental:
+//! el uso de CPU (total y por proceso) y el de los cgroups se calcula respecto a la
+//! muestra anterior, sin dormir dentro de la llamada.
+//!
+//! ### `Muestreador`
+//! - `sistema`: `System` compartido para CPU, memoria y procesos.
+//! - `usuarios`: Lista de usuarios para resolver el propietario de cada proceso.
+//! - `discos`: Lista de discos.
+//! - `redes`: Lista de interfaces de red.
+//! - `cgroups_anterior`: Última lectura de cgroups e instante en que se tomó.
+//! - `cache_procesos`: Cgroup y seguridad de cada proceso (`CacheProcesos`), que no se releen en cada muestra.
+//!
+//! #### Métodos de `Muestreador`
+//! - `new`: Crea los manejadores y hace un primer refresco de CPU y procesos.
+//! - `cpu`, `memoria`, `procesos`, `discos`, `red`, `cgroups`: Refrescan solo su parte y devuelven la estructura correspondiente.
+//! - `inventario`: Devuelve los datos fijos del equipo (no necesita refresco).
+//!
+//! Todos devuelven `Resultado`: el error de una fuente no afecta a las demás.
+//!
+//! La primera llamada a `cpu` y `procesos` debe hacerse al menos
+//! `INTERVALO_MINIMO_CPU` después de `new` para obtener valores significativos.
+//!
+//! Historial de cambios
+//! - 2026-10-18: Creación del módulo.
+//! - 2026-10-18: Se agrega `inventario`.
+//! - 2026-10-18: `cpu` devuelve `Resultado`.
+//! - 2026-10-18: Todas las fuentes devuelven `Resultado`.
+//! - 2026-10-18: `procesos` reutiliza el cgroup y la seguridad de cada proceso entre muestras.
+
+use std::time::{Duration, Instant};
+use sysinfo::{
+    CpuRefreshKind, Disks, MemoryRefreshKind, Networks, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System,
+    UpdateKind, Users,
+};
+use crate::cgroups::CgroupsInfo;
+use crate::cpu::CPUInfo;
+use crate::error::Resultado;
+use crate::disco::DiscosInfo;
+use crate::interfaces::InterfacesInfo;
+use crate::inventario::InventarioInfo;
+use crate::memoria::MemoriaInfo;
+use crate::procesos::{CacheProcesos, ProcesosInfo};
+
+/// Tiempo mínimo entre dos muestras de CPU para que el delta sea significativo.
+pub const INTERVALO_MINIMO_CPU: Duration = sysinfo::MINIMUM_CPU_UPDATE_INTERVAL;
+
+pub struct Muestreador {
+    sistema: System,
+    usuarios: Users,
+    discos: Disks,
+    redes: Networks,
+    cgroups_anterior: Option<(CgroupsInfo, Instant)>,
+    cache_procesos: CacheProcesos,
+}
+
+impl Muestreador {
+    /// Crea un nuevo muestreador y deja cargada la primera referencia para los deltas.
+    pub fn new() -> Self {
+        let mut sistema = System::new_with_specifics(
+            RefreshKind::nothing().with_cpu(CpuRefreshKind::everything()).with_memory(MemoryRefreshKind::everything()),
+        );
+        sistema.refresh_processes_specifics(ProcessesToUpdate::All, true, Self::refresco_procesos());
+        Muestreador {
+            sistema,
+            usuarios: Users::new_with_refreshed_list(),
+            discos: Disks::new_with_refreshed_list(),
+            redes: Networks::new_with_refreshed_list(),
+            cgroups_anterior: CgroupsInfo::leer().ok().map(|c| (c, Instant::now())),
+            cache_procesos: CacheProcesos::default(),
+        }
+    }
+
+    // Solo lo que se muestra: CPU, memoria y usuario (este último una sola vez por proceso)
+    fn refresco_procesos() -> ProcessRefreshKind {
+        ProcessRefreshKind::nothing()
+            .with_cpu()
+            .with_memory()
+            .with_user(UpdateKind::OnlyIfNotSet)
+            .without_tasks()
+    }
+
+    /// Devuelve el uso de CPU desde la muestra anterior.
+    pub fn cpu(&mut self) -> Resultado<CPUInfo> {
+        self.sistema.refresh_cpu_all();
+        CPUInfo::desde_sistema(&self.sistema)
+    }
+
+    /// Devuelve el estado actual de la memoria.
+    pub fn memoria(&mut self) -> Resultado<MemoriaInfo> {
+        self.sistema.refresh_memory();
+        MemoriaInfo::desde_sistema(&self.sistema)
+    }
+
+    /// Devuelve los procesos con su uso de CPU desde la muestra anterior.
+    pub fn procesos(&mut self) -> Resultado<ProcesosInfo> {
+        self.sistema.refresh_processes_specifics(ProcessesToUpdate::All, true, Self::refresco_procesos());
+        self.usuarios.refresh();
+        ProcesosInfo::desde_sistema(&self.sistema, &self.usuarios, &mut self.cache_procesos)
+    }
+
+    /// Devuelve el espacio de los discos (la lista se actualiza si cambian los montajes).
+    pub fn discos(&mut self) -> Resultado<DiscosInfo> {
+        self.discos.refresh(true);
+        DiscosInfo::desde_discos(&self.discos)
+    }
+
+    /// Devuelve los contadores de las interfaces de red.
+    pub fn red(&mut self) -> Resultado<InterfacesInfo> {
+        self.redes.refresh(true);
+        InterfacesInfo::desde_sistema(&self.redes)
+    }
+
+    /// Devuelve los cgroups con su uso de CPU desde la muestra anterior.
+    pub fn cgroups(&mut self) -> Resultado<CgroupsInfo> {
+        let mut actual = CgroupsInfo::leer()?;
+        let ahora = Instant::now();
+        if let Some((anterior, instante)) = &self.cgroups_anterior {
+            actual.calcular_uso_cpu(anterior, ahora.duration_since(*instante));
+        }
+        self.cgroups_anterior = Some((actual.clone(), ahora));
+        Ok(actual)
+    }
+
+    /// Devuelve el inventario del equipo; CPU y memoria ya están cargados desde `new`.
+    pub fn inventario(&mut self) -> Resultado<InventarioInfo> {
+        InventarioInfo::desde_sistema(&self.sistema)
+    }
+}
+
+impl Default for Muestreador {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,12 +1,16 @@
 /// Módulo que implementa la obtención y análisis de los procesos del sistema.
 /// Proporciona información detallada de cada proceso, incluyendo:
 /// - PID.
+/// - PID del proceso padre.
 /// - Nombre del proceso.
+/// - Usuario propietario del proceso.
 /// - Tiempo total de ejecución.
 /// - Tiempo acumulado en CPU.
 /// - Uso actual de CPU y memoria.
 /// - Uso de memoria virtual.
 /// - Estado del proceso.
+/// - Ruta del cgroup (v2) al que pertenece.
+/// - Namespaces, capacidades, `NoNewPrivs` y modo seccomp.
 ///
 /// Este módulo define dos estructuras principales:
 ///
@@ -18,49 +22,131 @@
 /// - `top_procesos_uso_memoria`: Top 5 procesos que están usando más memoria física.
 /// - `top_procesos_tiempo_cpu`: Top 5 procesos con más tiempo acumulado en CPU.
 /// - `top_procesos_tiempo_ejecucion`: Top 5 procesos con mayor tiempo de ejecución.
+/// - `namespaces_host_ilegibles`: No se pudieron leer los namespaces pid, net o user de PID 1,
+///   así que la detección de procesos fuera del host está desactivada o incompleta.
 ///
 /// #### Métodos de `ProcesosInfo`
 /// - `new`: Constructor que obtiene la información directamente del sistema.
+/// - `desde_sistema`: Construye `ProcesosInfo` desde un `System` cuyos procesos ya fueron refrescados.
+///   Ambos devuelven `ErrorInstrumentos::SinDatos` si el sistema no reporta ningún proceso.
+///   El cgroup y la seguridad de cada proceso se toman de un `CacheProcesos`.
 /// - Métodos *getter* para acceder a los campos anteriores.
 ///
 /// ### `ProcesoInfo`
 /// Representa la información específica de un solo proceso del sistema.
 /// - `pid`: Identificador único del proceso.
+/// - `ppid`: Identificador del proceso padre, si existe.
 /// - `nombre`: Nombre del proceso.
+/// - `usuario`: Nombre del usuario propietario (o su UID si no se puede resolver).
 /// - `tiempo_ejecucion`: Tiempo total que ha estado en ejecución.
 /// - `tiempo_en_cpu`: Tiempo acumulado que ha estado en CPU.
 /// - `uso_cpu`: Porcentaje de CPU usado actualmente.
 /// - `uso_memoria`: Memoria física usada (en bytes).
 /// - `uso_memoria_virtual`: Memoria virtual usada (en bytes).
 /// - `estado`: Estado actual del proceso.
+/// - `cgroup`: Ruta cgroup v2 del proceso, si se pudo leer.
+/// - `seguridad`: Contexto de seguridad del proceso (`SeguridadProceso`), si se pudo leer.
 ///
 /// #### Métodos de `ProcesoInfo`
 /// - `get_pid`: Devuelve el PID del proceso.
+/// - `get_ppid`: Devuelve el PID del proceso padre.
 /// - `get_nombre`: Devuelve el nombre del proceso.
+/// - `get_usuario`: Devuelve el usuario propietario del proceso.
 /// - `get_tiempo_ejecucion`: Devuelve el tiempo total de ejecución.
 /// - `get_tiempo_en_cpu`: Devuelve el tiempo acumulado en CPU.
 /// - `get_uso_cpu`: Devuelve el porcentaje de CPU usado.
 /// - `get_uso_memoria`: Devuelve el uso de memoria física.
 /// - `get_uso_memoria_virtual`: Devuelve el uso de memoria virtual.
 /// - `get_estado`: Devuelve el estado del proceso.
+/// - `get_cgroup`: Devuelve la ruta cgroup v2 del proceso.
+/// - `get_seguridad`: Devuelve el contexto de seguridad del proceso.
+///
+/// ### `SeguridadProceso`
+/// Contexto de seguridad leído de `/proc/<pid>/status` y `/proc/<pid>/ns`.
+/// - `namespaces`: Inodos de los namespaces pid, net, mnt y user (`Namespaces`).
+/// - `fuera_del_host`: Indica si el namespace pid, net o user difiere del de PID 1 (el host).
+///   Un mnt distinto solo no cuenta: lo tienen los servicios con `PrivateTmp` o `ProtectSystem`.
+/// - `capacidades_efectivas` / `capacidades_permitidas`: Máscaras `CapEff` y `CapPrm`;
+///   `None` si faltan o no se pueden interpretar.
+/// - `no_new_privs`: Valor de `NoNewPrivs`.
+/// - `seccomp`: Modo seccomp (`ModoSeccomp`).
+/// - `capacidades_peligrosas`: Capacidades efectivas consideradas peligrosas (p. ej. `CAP_SYS_ADMIN`).
+/// - `sospechoso`: Tiene capacidades peligrosas y corre fuera de los namespaces del host,
+///   patrón típico de un contenedor privilegiado desde el que se puede escapar. Un proceso
+///   fuera del host cuyo `CapEff` no se pudo interpretar también cuenta como sospechoso.
+///
+/// ### `CacheProcesos`
+/// Cgroup y `SeguridadProceso` de cada proceso, por PID y hora de inicio, para no releer
+/// `status`, `ns` y `cgroup` de todo `/proc` en cada muestreo. Una entrada se vuelve a leer
+/// pasado `VIGENCIA_CACHE`, así que un cambio de capacidades o namespaces se ve con ese retraso.
+///
+/// ### Consultas bajo demanda
+/// Estas consultas no forman parte del muestreo periódico porque son costosas;
+/// se hacen solo cuando se inspecciona un proceso concreto.
+/// - `archivos_abiertos`: Descriptores de `/proc/<pid>/fd` clasificados (`ArchivoAbierto`).
+/// - `mapas_memoria`: Regiones de `/proc/<pid>/maps` y totales de `smaps_rollup` (`MapasMemoria`),
+///   incluyendo PSS y USS, bibliotecas cargadas y regiones ejecutables sospechosas.
+///   Las líneas de `maps` que no se pueden interpretar quedan en `lineas_invalidas`.
 ///
 /// ### Función principal
 /// - `obtener_info_procesos`: Devuelve una instancia de `ProcesosInfo` con la información actual del sistema.
+/// - `nombres_capacidades`: Traduce una máscara de capacidades a sus nombres `CAP_*`.
 /// ### TODO
 /// corregir lectura de uso cpu
 /// #### Historial de cambios
 /// - 2025-04-06: Creación del módulo y definición de estructuras `ProcesosInfo` y `ProcesoInfo`, y metodos para mostrar informacion.
+/// - 2026-10-18: Se agrega el usuario propietario de cada proceso para la tabla de procesos de la GUI.
+/// - 2026-10-18: Se agrega el PID del proceso padre para construir el árbol de procesos.
+/// - 2026-10-18: Se agrega la ruta cgroup de cada proceso para relacionarlo con `CgroupsInfo`.
+/// - 2026-10-18: Se agrega `SeguridadProceso` (namespaces, capacidades, seccomp) y la detección de procesos sospechosos.
+/// - 2026-10-18: Se agregan las consultas bajo demanda de archivos abiertos y mapas de memoria.
+/// - 2026-10-18: Se separa `desde_sistema` para que el uso de CPU venga del refresco anterior de un `System` de larga vida.
+/// - 2026-10-18: `get_proceso` devuelve `Option`, el ordenamiento tolera NaN y las consultas bajo demanda devuelven `Resultado`.
+/// - 2026-10-18: `mostrar_info` imprime la implementación de `Display`, que usa también la CLI.
+/// - 2026-10-18: Se agrega `enviar_senal` para terminar procesos desde la API con alcance de control.
+/// - 2026-10-18: `new`, `desde_sistema` y `obtener_info_procesos` devuelven `Resultado`; `mapas_memoria` informa las líneas con formato inesperado.
+/// - 2026-10-18: `fuera_del_host` ignora el namespace mnt y se informa si no se pudieron leer los namespaces de PID 1.
+/// - 2026-10-18: Se agrega `CacheProcesos` para no releer `status`, `ns` y `cgroup` en cada muestreo.
+/// - 2026-10-18: Las máscaras de capacidades ilegibles quedan en `None` en lugar de cero.
+/// - 2026-10-18: `mapas_memoria` descarta las líneas de `maps` ilegibles en lugar de fallar (`MapasMemoria::desde_texto`).
 
-use sysinfo::{ProcessesToUpdate, ProcessRefreshKind, System, Pid};
+use sysinfo::{Pid, ProcessesToUpdate, ProcessRefreshKind, Signal, System, Users};
+use std::collections::HashMap;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use std::cmp::Reverse;
 use std::fmt;
-use serde::Serialize;
+use std::fs;
+use crate::error::{ErrorInstrumentos, Resultado};
+use serde::{Deserialize, Serialize};
+use crate::cgroups::cgroup_de_pid;
 
 /// Constante para la conversión de bytes a megabytes.
 const B_TO_MB: u64 = 1024 * 1024;
 
-#[derive(Clone, Debug, Serialize)]
+/// Tiempo tras el que `CacheProcesos` vuelve a leer el cgroup y la seguridad de un proceso.
+pub const VIGENCIA_CACHE: Duration = Duration::from_secs(60);
+
+/// Nombres de las capacidades de Linux, indexados por número de bit.
+const CAPACIDADES: [&str; 41] = [
+    "CAP_CHOWN", "CAP_DAC_OVERRIDE", "CAP_DAC_READ_SEARCH", "CAP_FOWNER", "CAP_FSETID",
+    "CAP_KILL", "CAP_SETGID", "CAP_SETUID", "CAP_SETPCAP", "CAP_LINUX_IMMUTABLE",
+    "CAP_NET_BIND_SERVICE", "CAP_NET_BROADCAST", "CAP_NET_ADMIN", "CAP_NET_RAW", "CAP_IPC_LOCK",
+    "CAP_IPC_OWNER", "CAP_SYS_MODULE", "CAP_SYS_RAWIO", "CAP_SYS_CHROOT", "CAP_SYS_PTRACE",
+    "CAP_SYS_PACCT", "CAP_SYS_ADMIN", "CAP_SYS_BOOT", "CAP_SYS_NICE", "CAP_SYS_RESOURCE",
+    "CAP_SYS_TIME", "CAP_SYS_TTY_CONFIG", "CAP_MKNOD", "CAP_LEASE", "CAP_AUDIT_WRITE",
+    "CAP_AUDIT_CONTROL", "CAP_SETFCAP", "CAP_MAC_OVERRIDE", "CAP_MAC_ADMIN", "CAP_SYSLOG",
+    "CAP_WAKE_ALARM", "CAP_BLOCK_SUSPEND", "CAP_AUDIT_READ", "CAP_PERFMON", "CAP_BPF",
+    "CAP_CHECKPOINT_RESTORE",
+];
+
+/// Capacidades que permiten salir de un contenedor o tomar control del host.
+const CAPACIDADES_PELIGROSAS: [&str; 8] = [
+    "CAP_SYS_ADMIN", "CAP_SYS_MODULE", "CAP_SYS_PTRACE", "CAP_SYS_RAWIO",
+    "CAP_DAC_READ_SEARCH", "CAP_NET_ADMIN", "CAP_BPF", "CAP_SYS_BOOT",
+];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProcesosInfo {
     cantidad_procesos: usize,
     procesos: Vec<ProcesoInfo>,
@@ -68,18 +154,103 @@ pub struct ProcesosInfo {
     top_procesos_uso_memoria: Vec<ProcesoInfo>,
     top_procesos_tiempo_cpu: Vec<ProcesoInfo>,
     top_procesos_tiempo_ejecucion: Vec<ProcesoInfo>,
+    #[serde(default)]
+    namespaces_host_ilegibles: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProcesoInfo{
     pid: i32,
+    ppid: Option<i32>,
     nombre: String,
+    usuario: String,
     tiempo_ejecucion: u64,
     tiempo_en_cpu: u64,
     uso_cpu: f32,
     uso_memoria: u64,
     uso_memoria_virtual: u64,
     estado: String,
+    cgroup: Option<String>,
+    seguridad: Option<SeguridadProceso>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Namespaces {
+    pub pid: Option<u64>,
+    pub net: Option<u64>,
+    pub mnt: Option<u64>,
+    pub user: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModoSeccomp {
+    Deshabilitado,
+    Estricto,
+    Filtro,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SeguridadProceso {
+    namespaces: Namespaces,
+    fuera_del_host: bool,
+    capacidades_efectivas: Option<u64>,
+    capacidades_permitidas: Option<u64>,
+    no_new_privs: bool,
+    seccomp: Option<ModoSeccomp>,
+    capacidades_peligrosas: Vec<String>,
+    sospechoso: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct CacheProcesos {
+    host: Namespaces,
+    entradas: HashMap<i32, EntradaCache>,
+}
+
+#[derive(Debug)]
+struct EntradaCache {
+    inicio: u64, // segundos desde la época; distingue un PID reutilizado
+    leida: Instant,
+    cgroup: Option<String>,
+    seguridad: Option<SeguridadProceso>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TipoDescriptor {
+    Archivo,
+    Socket,
+    Tuberia,
+    AnonInode,
+    Otro,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchivoAbierto {
+    pub fd: i32,
+    pub tipo: TipoDescriptor,
+    pub destino: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegionMemoria {
+    pub inicio: u64,
+    pub fin: u64,
+    pub permisos: String,
+    pub desplazamiento: u64,
+    pub dispositivo: String,
+    pub inodo: u64,
+    pub ruta: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MapasMemoria {
+    pub regiones: Vec<RegionMemoria>,
+    pub rss_kb: u64,
+    pub pss_kb: u64,
+    pub uss_kb: u64, // Private_Clean + Private_Dirty
+    pub swap_kb: u64,
+    #[serde(default)]
+    pub lineas_invalidas: Vec<String>, // "línea N: ..." de maps que no se pudieron interpretar
 }
 
 impl ProcesosInfo{
@@ -98,13 +269,9 @@ impl ProcesosInfo{
     }
     // Devuelve el proceso en la posicion index
     // Retorno
-    // el proceso en la posicion index como un ProcesoInfo
-    pub fn get_proceso(&self, index: usize) -> &ProcesoInfo {
-        if index < self.procesos.len() {
-            &self.procesos[index]
-        } else {
-            panic!("Índice fuera de rango");
-        }
+    // el proceso en la posicion index como un ProcesoInfo, o None si el índice está fuera de rango
+    pub fn get_proceso(&self, index: usize) -> Option<&ProcesoInfo> {
+        self.procesos.get(index)
     }
     // Devuelve la lista de procesos que consumen más CPU
     // Retorno
@@ -131,10 +298,27 @@ impl ProcesosInfo{
     pub fn get_top_procesos_tiempo_ejecucion(&self) -> &Vec<ProcesoInfo> {
         &self.top_procesos_tiempo_ejecucion
     }
+    // Devuelve los procesos marcados como sospechosos
+    // Retorno
+    // los procesos con capacidades peligrosas fuera de los namespaces del host
+    pub fn get_procesos_sospechosos(&self) -> Vec<&ProcesoInfo> {
+        self.procesos
+            .iter()
+            .filter(|p| p.seguridad.as_ref().is_some_and(|s| s.sospechoso))
+            .collect()
+    }
+    // Indica si no se pudieron leer los namespaces de PID 1
+    // Retorno
+    // true si la detección de procesos fuera del host está desactivada o incompleta
+    pub fn get_namespaces_host_ilegibles(&self) -> bool {
+        self.namespaces_host_ilegibles
+    }
     
     // Constructor
     // Crea una nueva instancia de ProcesosInfo
-    pub fn new() -> ProcesosInfo {
+    // Retorno
+    // una nueva instancia de ProcesosInfo, o error si el sistema no reporta procesos
+    pub fn new() -> Resultado<ProcesosInfo> {
         let mut s = System::new_all();
         s.refresh_processes_specifics(
             ProcessesToUpdate::All,
@@ -147,55 +331,67 @@ impl ProcesosInfo{
             true,
             ProcessRefreshKind::everything(),
         );
+        let usuarios = Users::new_with_refreshed_list();
+        Self::desde_sistema(&s, &usuarios, &mut CacheProcesos::default())
+    }
+
+    // Crea una instancia de ProcesosInfo a partir de un System cuyos procesos ya fueron refrescados
+    // El uso de CPU de cada proceso es relativo al refresco anterior del mismo System;
+    // el cgroup y la seguridad se leen de /proc solo si no están vigentes en `cache`
+    pub fn desde_sistema(s: &System, usuarios: &Users, cache: &mut CacheProcesos) -> Resultado<ProcesosInfo> {
+        // sin /proc visible la lista llega vacía; siempre existe al menos el proceso actual
+        if s.processes().is_empty() {
+            return Err(ErrorInstrumentos::SinDatos("procesos"));
+        }
+        cache.preparar(s);
+        let namespaces_host = cache.host.clone();
         let mut procesos = Vec::<ProcesoInfo>::new();
         for (pid, process) in s.processes(){
+            let usuario = match process.user_id() {
+                Some(uid) => usuarios
+                    .get_user_by_id(uid)
+                    .map(|u| u.name().to_string())
+                    .unwrap_or_else(|| uid.to_string()),
+                None => String::new(),
+            };
+            let (cgroup, seguridad) = cache.consultar(pid.as_u32() as i32, process.start_time());
             let proceso = ProcesoInfo {
                 pid: pid.as_u32() as i32,
+                ppid: process.parent().map(|p| p.as_u32() as i32),
                 nombre: process.name().to_string_lossy().into_owned(),
+                usuario,
                 tiempo_ejecucion: process.run_time(),
                 tiempo_en_cpu: process.accumulated_cpu_time(),
                 uso_cpu: process.cpu_usage(),
                 uso_memoria: process.memory() / B_TO_MB,
                 uso_memoria_virtual: process.virtual_memory() / B_TO_MB, //falla en macOs
                 estado: process.status().to_string(),
+                cgroup,
+                seguridad,
             };
             procesos.push(proceso);
         }
-        procesos.sort_by(|a, b| b.uso_cpu.partial_cmp(&a.uso_cpu).unwrap());
+        // total_cmp no entra en pánico si algún uso de CPU es NaN
+        procesos.sort_by(|a, b| b.uso_cpu.total_cmp(&a.uso_cpu));
         let top_procesos_uso_cpu = procesos.iter().take(5).cloned().collect();
-        procesos.sort_by(|a, b| b.uso_memoria.partial_cmp(&a.uso_memoria).unwrap());
+        procesos.sort_by_key(|p| Reverse(p.uso_memoria));
         let top_procesos_uso_memoria = procesos.iter().take(5).cloned().collect();
-        procesos.sort_by(|a, b| b.tiempo_en_cpu.partial_cmp(&a.tiempo_en_cpu).unwrap());
+        procesos.sort_by_key(|p| Reverse(p.tiempo_en_cpu));
         let top_procesos_tiempo_cpu = procesos.iter().take(5).cloned().collect();
-        procesos.sort_by(|a, b| b.tiempo_ejecucion.partial_cmp(&a.tiempo_ejecucion).unwrap());
+        procesos.sort_by_key(|p| Reverse(p.tiempo_ejecucion));
         let top_procesos_tiempo_ejecucion = procesos.iter().take(5).cloned().collect();
-        ProcesosInfo {
+        Ok(ProcesosInfo {
             cantidad_procesos: procesos.len(),
             procesos,
             top_procesos_uso_cpu,
             top_procesos_uso_memoria,
             top_procesos_tiempo_cpu,
             top_procesos_tiempo_ejecucion,
-        }
+            namespaces_host_ilegibles: !namespaces_host.completos(),
+        })
     }
     pub fn mostrar_info(&self) {
-        println!("Cantidad de procesos: {}", self.cantidad_procesos);
-        println!("Top 5 procesos que consumen más CPU:");
-        for proceso in &self.top_procesos_uso_cpu {
-            proceso.mostrar_info();
-        }
-        println!("Top 5 procesos que consumen más memoria:");
-        for proceso in &self.top_procesos_uso_memoria {
-            proceso.mostrar_info();
-        }
-        println!("Top 5 procesos que más tiempo han estado en CPU:");
-        for proceso in &self.top_procesos_tiempo_cpu {
-            proceso.mostrar_info();
-        }
-        println!("Top 5 procesos que más tiempo han estado en ejecución:");
-        for proceso in &self.top_procesos_tiempo_ejecucion {
-            proceso.mostrar_info();
-        }
+        print!("{}", self);
     }
 }
 
@@ -207,12 +403,24 @@ impl ProcesoInfo{
     pub fn get_pid(&self) -> i32 {
         self.pid
     }
+    // Devuelve el pid del proceso padre
+    // Retorno
+    // el pid del padre, o None si el proceso no tiene padre conocido
+    pub fn get_ppid(&self) -> Option<i32> {
+        self.ppid
+    }
     // Devuelve el nombre
     // Retorno
     // el nombre como un string
     pub fn get_nombre(&self) -> &String {
         &self.nombre
     }
+    // Devuelve el usuario propietario
+    // Retorno
+    // el nombre del usuario como un string (vacio si no se conoce)
+    pub fn get_usuario(&self) -> &String {
+        &self.usuario
+    }
     // Devuelve el tiempo de ejecucion
     // Retorno
     // el tiempo de ejecucion como un entero
@@ -249,6 +457,26 @@ impl ProcesoInfo{
     pub fn get_estado(&self) -> &String {
         &self.estado
     }
+    // Devuelve la ruta cgroup
+    // Retorno
+    // la ruta cgroup v2 del proceso, o None si no se pudo leer
+    pub fn get_cgroup(&self) -> Option<&str> {
+        self.cgroup.as_deref()
+    }
+    // Devuelve el contexto de seguridad
+    // Retorno
+    // namespaces, capacidades y seccomp del proceso, o None si no se pudo leer
+    pub fn get_seguridad(&self) -> Option<&SeguridadProceso> {
+        self.seguridad.as_ref()
+    }
+    // Consulta bajo demanda de los descriptores abiertos del proceso
+    pub fn archivos_abiertos(&self) -> Resultado<Vec<ArchivoAbierto>> {
+        archivos_abiertos(self.pid)
+    }
+    // Consulta bajo demanda de los mapas de memoria del proceso
+    pub fn mapas_memoria(&self) -> Resultado<MapasMemoria> {
+        mapas_memoria(self.pid)
+    }
     pub fn formatear_tiempo(segundos: u64) -> String {
         let dias = segundos / 86400;
         let horas = (segundos % 86400) / 3600;
@@ -261,25 +489,334 @@ impl ProcesoInfo{
         )
     }   
     pub fn mostrar_info(&self) {
-        println!("PID: {}", self.pid);
-        println!("Nombre: {}", self.nombre);
-        println!("Tiempo de ejecución: {} segundos", self.tiempo_ejecucion);
-        println!("Tiempo en CPU: {} segundos", self.tiempo_en_cpu);
-        println!("Uso de CPU: {:.2}%", self.uso_cpu);
-        println!("Uso de memoria: {} MB", self.uso_memoria);
-        println!("Uso de memoria virtual: {} MB", self.uso_memoria_virtual);
-        println!("Estado: {}", self.estado);
+        println!("{}", self);
     } 
 }
+impl Namespaces {
+    /// Lee los inodos de los namespaces de un proceso desde `/proc/<pid>/ns`.
+    ///
+    /// Los namespaces que no se pueden leer (por permisos) quedan en `None`.
+    pub fn leer(pid: i32) -> Self {
+        let inodo = |nombre: &str| -> Option<u64> {
+            // el enlace tiene la forma "pid:[4026531836]"
+            let destino = fs::read_link(format!("/proc/{}/ns/{}", pid, nombre)).ok()?;
+            let destino = destino.to_string_lossy();
+            destino.split_once('[')?.1.strip_suffix(']')?.parse().ok()
+        };
+        Namespaces {
+            pid: inodo("pid"),
+            net: inodo("net"),
+            mnt: inodo("mnt"),
+            user: inodo("user"),
+        }
+    }
+
+    /// Indica si el namespace pid, net o user, conocido en ambos lados, es distinto.
+    ///
+    /// El mnt no se compara: muchos servicios del host tienen el suyo propio
+    /// (`PrivateTmp`, `ProtectSystem`) sin estar aislados en un contenedor.
+    pub fn difiere_de(&self, otro: &Namespaces) -> bool {
+        let distinto = |a: Option<u64>, b: Option<u64>| matches!((a, b), (Some(a), Some(b)) if a != b);
+        distinto(self.pid, otro.pid) || distinto(self.net, otro.net) || distinto(self.user, otro.user)
+    }
+
+    /// Indica si se leyeron los namespaces que usa `difiere_de`.
+    pub fn completos(&self) -> bool {
+        self.pid.is_some() && self.net.is_some() && self.user.is_some()
+    }
+}
+
+impl CacheProcesos {
+    // Lee los namespaces del host y descarta las entradas de procesos que ya no existen;
+    // si cambiaron los namespaces del host, `fuera_del_host` de todas las entradas es viejo
+    fn preparar(&mut self, s: &System) {
+        let host = Namespaces::leer(1);
+        if host != self.host {
+            self.entradas.clear();
+            self.host = host;
+        }
+        self.entradas.retain(|pid, _| s.process(Pid::from(*pid as usize)).is_some());
+    }
+
+    // Devuelve el cgroup y la seguridad del proceso, releyéndolos si la entrada
+    // no existe, es de otro proceso con el mismo PID o pasó `VIGENCIA_CACHE`
+    fn consultar(&mut self, pid: i32, inicio: u64) -> (Option<String>, Option<SeguridadProceso>) {
+        let vigente = self
+            .entradas
+            .get(&pid)
+            .is_some_and(|e| e.inicio == inicio && e.leida.elapsed() < VIGENCIA_CACHE);
+        if !vigente {
+            let entrada = EntradaCache {
+                inicio,
+                leida: Instant::now(),
+                cgroup: cgroup_de_pid(pid),
+                seguridad: SeguridadProceso::leer(pid, &self.host),
+            };
+            self.entradas.insert(pid, entrada);
+        }
+        let entrada = &self.entradas[&pid];
+        (entrada.cgroup.clone(), entrada.seguridad.clone())
+    }
+}
+
+impl SeguridadProceso {
+    // Getters
+    pub fn get_namespaces(&self) -> &Namespaces { &self.namespaces }
+    pub fn get_fuera_del_host(&self) -> bool { self.fuera_del_host }
+    pub fn get_capacidades_efectivas(&self) -> Option<u64> { self.capacidades_efectivas }
+    pub fn get_capacidades_permitidas(&self) -> Option<u64> { self.capacidades_permitidas }
+    pub fn get_no_new_privs(&self) -> bool { self.no_new_privs }
+    pub fn get_seccomp(&self) -> Option<ModoSeccomp> { self.seccomp }
+    pub fn get_capacidades_peligrosas(&self) -> &Vec<String> { &self.capacidades_peligrosas }
+    pub fn get_sospechoso(&self) -> bool { self.sospechoso }
+
+    /// Lee el contexto de seguridad de un proceso.
+    ///
+    /// # Parámetros
+    /// - `pid`: PID del proceso.
+    /// - `host`: Namespaces de referencia del host (normalmente los de PID 1).
+    ///
+    /// # Retorno
+    /// `None` si no se pudo leer `/proc/<pid>/status`.
+    pub fn leer(pid: i32, host: &Namespaces) -> Option<Self> {
+        let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+        Some(Self::desde_status(&status, Namespaces::leer(pid), host))
+    }
+
+    /// Construye el contexto de seguridad a partir del contenido de `/proc/<pid>/status`.
+    ///
+    /// Una máscara `CapEff` o `CapPrm` ausente o ilegible queda en `None`; si además
+    /// el proceso está fuera del host se marca como sospechoso, porque no se puede
+    /// descartar que tenga capacidades peligrosas.
+    pub fn desde_status(status: &str, namespaces: Namespaces, host: &Namespaces) -> Self {
+        let campo = |nombre: &str| {
+            status
+                .lines()
+                .find_map(|l| l.strip_prefix(nombre)?.strip_prefix(':'))
+                .map(|v| v.trim())
+        };
+        let mascara = |nombre: &str| campo(nombre).and_then(|v| u64::from_str_radix(v, 16).ok());
+
+        let capacidades_efectivas = mascara("CapEff");
+        let fuera_del_host = namespaces.difiere_de(host);
+        let capacidades_peligrosas: Vec<String> = nombres_capacidades(capacidades_efectivas.unwrap_or(0))
+            .into_iter()
+            .filter(|c| CAPACIDADES_PELIGROSAS.contains(c))
+            .map(|c| c.to_string())
+            .collect();
+
+        SeguridadProceso {
+            sospechoso: fuera_del_host && (capacidades_efectivas.is_none() || !capacidades_peligrosas.is_empty()),
+            namespaces,
+            fuera_del_host,
+            capacidades_efectivas,
+            capacidades_permitidas: mascara("CapPrm"),
+            no_new_privs: campo("NoNewPrivs") == Some("1"),
+            seccomp: match campo("Seccomp") {
+                Some("0") => Some(ModoSeccomp::Deshabilitado),
+                Some("1") => Some(ModoSeccomp::Estricto),
+                Some("2") => Some(ModoSeccomp::Filtro),
+                _ => None,
+            },
+            capacidades_peligrosas,
+        }
+    }
+
+    pub fn mostrar_info(&self) {
+        print!("{}", self);
+    }
+}
+
+/// Traduce una máscara de capacidades (`CapEff`, `CapPrm`, ...) a sus nombres.
+///
+/// Los bits sin nombre conocido se devuelven como "CAP_DESCONOCIDA".
+pub fn nombres_capacidades(mascara: u64) -> Vec<&'static str> {
+    (0..64)
+        .filter(|bit| mascara & (1u64 << bit) != 0)
+        .map(|bit| CAPACIDADES.get(bit).copied().unwrap_or("CAP_DESCONOCIDA"))
+        .collect()
+}
+
+impl TipoDescriptor {
+    /// Clasifica un descriptor por el destino de su enlace en `/proc/<pid>/fd`.
+    pub fn desde_destino(destino: &str) -> Self {
+        if destino.starts_with("socket:") {
+            TipoDescriptor::Socket
+        } else if destino.starts_with("pipe:") {
+            TipoDescriptor::Tuberia
+        } else if destino.starts_with("anon_inode:") {
+            TipoDescriptor::AnonInode
+        } else if destino.starts_with('/') {
+            TipoDescriptor::Archivo
+        } else {
+            TipoDescriptor::Otro
+        }
+    }
+}
+
+impl RegionMemoria {
+    /// Tamaño de la región en kB (cero si `fin` no es mayor que `inicio`).
+    pub fn tamano_kb(&self) -> u64 {
+        self.fin.saturating_sub(self.inicio) / 1024
+    }
+
+    /// Indica si la región tiene permiso de ejecución.
+    pub fn ejecutable(&self) -> bool {
+        self.permisos.contains('x')
+    }
+
+    /// Indica si es una región ejecutable de origen dudoso: un archivo ya borrado,
+    /// un `memfd` o memoria anónima escribible y ejecutable a la vez.
+    pub fn sospechosa(&self) -> bool {
+        if !self.ejecutable() {
+            return false;
+        }
+        self.ruta.ends_with("(deleted)")
+            || self.ruta.starts_with("/memfd:")
+            || (self.ruta.is_empty() && self.permisos.contains('w'))
+    }
+
+    fn desde_linea(linea: &str) -> Option<Self> {
+        // formato: inicio-fin permisos desplazamiento dispositivo inodo [ruta]
+        let mut partes = linea.splitn(6, char::is_whitespace).filter(|p| !p.is_empty());
+        let (inicio, fin) = partes.next()?.split_once('-')?;
+        let permisos = partes.next()?.to_string();
+        let desplazamiento = partes.next()?;
+        let dispositivo = partes.next()?.to_string();
+        let inodo = partes.next()?.parse().ok()?;
+        Some(RegionMemoria {
+            inicio: u64::from_str_radix(inicio, 16).ok()?,
+            fin: u64::from_str_radix(fin, 16).ok()?,
+            permisos,
+            desplazamiento: u64::from_str_radix(desplazamiento, 16).ok()?,
+            dispositivo,
+            inodo,
+            ruta: partes.next().unwrap_or("").trim().to_string(),
+        })
+    }
+}
+
+impl MapasMemoria {
+    /// Interpreta el contenido de `maps` y, si se pudo leer, el de `smaps_rollup`.
+    ///
+    /// Las líneas de `maps` que no se pueden interpretar se descartan y quedan
+    /// en `lineas_invalidas`; en `smaps_rollup` un valor ilegible cuenta como cero.
+    pub fn desde_texto(maps: &str, smaps_rollup: Option<&str>) -> Self {
+        let mut mapas = MapasMemoria::default();
+        for (i, linea) in maps.lines().enumerate().filter(|(_, linea)| !linea.trim().is_empty()) {
+            match RegionMemoria::desde_linea(linea) {
+                Some(region) => mapas.regiones.push(region),
+                None => mapas.lineas_invalidas.push(format!("línea {}: {:?}", i + 1, linea)),
+            }
+        }
+        for linea in smaps_rollup.unwrap_or("").lines() {
+            let Some((clave, valor)) = linea.split_once(':') else { continue };
+            let kb: u64 = valor.trim().trim_end_matches("kB").trim().parse().unwrap_or(0);
+            match clave {
+                "Rss" => mapas.rss_kb = kb,
+                "Pss" => mapas.pss_kb = kb,
+                "Private_Clean" | "Private_Dirty" => mapas.uss_kb += kb,
+                "Swap" => mapas.swap_kb = kb,
+                _ => {}
+            }
+        }
+        mapas
+    }
+
+    /// Devuelve las bibliotecas compartidas mapeadas, sin repetir.
+    pub fn bibliotecas(&self) -> Vec<&str> {
+        let mut bibliotecas: Vec<&str> = self
+            .regiones
+            .iter()
+            .map(|r| r.ruta.as_str())
+            .filter(|ruta| ruta.starts_with('/') && (ruta.ends_with(".so") || ruta.contains(".so.")))
+            .collect();
+        bibliotecas.sort();
+        bibliotecas.dedup();
+        bibliotecas
+    }
+
+    /// Devuelve las regiones ejecutables sospechosas (ver `RegionMemoria::sospechosa`).
+    pub fn regiones_sospechosas(&self) -> Vec<&RegionMemoria> {
+        self.regiones.iter().filter(|r| r.sospechosa()).collect()
+    }
+}
+
+/// Lista los descriptores abiertos de un proceso desde `/proc/<pid>/fd`.
+///
+/// # Retorno
+/// Los descriptores ordenados por número, o `ErrorInstrumentos::Lectura` si no se
+/// puede leer el directorio (normalmente por permisos o porque el proceso terminó).
+pub fn archivos_abiertos(pid: i32) -> Resultado<Vec<ArchivoAbierto>> {
+    let ruta = format!("/proc/{}/fd", pid);
+    let mut archivos = Vec::new();
+    for entrada in fs::read_dir(&ruta).map_err(|e| ErrorInstrumentos::lectura(&ruta, e))? {
+        let entrada = entrada.map_err(|e| ErrorInstrumentos::lectura(&ruta, e))?;
+        let Ok(fd) = entrada.file_name().to_string_lossy().parse::<i32>() else { continue };
+        // el descriptor puede cerrarse entre read_dir y read_link
+        let Ok(destino) = fs::read_link(entrada.path()) else { continue };
+        let destino = destino.to_string_lossy().into_owned();
+        archivos.push(ArchivoAbierto { fd, tipo: TipoDescriptor::desde_destino(&destino), destino });
+    }
+    archivos.sort_by_key(|a| a.fd);
+    Ok(archivos)
+}
+
+/// Lee los mapas de memoria de un proceso (`/proc/<pid>/maps`) y los totales
+/// de `/proc/<pid>/smaps_rollup`.
+///
+/// Si `smaps_rollup` no existe (kernels anteriores a 4.14) los totales quedan en cero.
+/// Las líneas de `maps` que no se pueden interpretar se descartan (ver `MapasMemoria::desde_texto`).
+///
+/// # Retorno
+/// `ErrorInstrumentos::Lectura` solo si no se puede leer `maps`.
+pub fn mapas_memoria(pid: i32) -> Resultado<MapasMemoria> {
+    let ruta = format!("/proc/{}/maps", pid);
+    let maps = fs::read_to_string(&ruta).map_err(|e| ErrorInstrumentos::lectura(&ruta, e))?;
+    let rollup = fs::read_to_string(format!("/proc/{}/smaps_rollup", pid)).ok();
+    Ok(MapasMemoria::desde_texto(&maps, rollup.as_deref()))
+}
+
+/// Señal que se puede enviar a un proceso con `enviar_senal`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Senal {
+    /// `SIGTERM`: pide al proceso que termine.
+    Terminar,
+    /// `SIGKILL`: lo termina sin darle oportunidad de limpiar.
+    Matar,
+}
+
+/// Envía una señal a un proceso.
+///
+/// # Retorno
+/// `ErrorInstrumentos::SinDatos` si el proceso no existe, o
+/// `ErrorInstrumentos::Senal` si el sistema rechazó la señal (normalmente por permisos).
+pub fn enviar_senal(pid: i32, senal: Senal) -> Resultado<()> {
+    let pid_sistema = Pid::from(pid.max(0) as usize);
+    let mut sistema = System::new();
+    sistema.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid_sistema]), true, ProcessRefreshKind::nothing());
+    let proceso = sistema.process(pid_sistema).ok_or(ErrorInstrumentos::SinDatos("proceso"))?;
+    let senal_sistema = match senal {
+        Senal::Terminar => Signal::Term,
+        Senal::Matar => Signal::Kill,
+    };
+    match proceso.kill_with(senal_sistema) {
+        Some(true) => Ok(()),
+        Some(false) => Err(ErrorInstrumentos::Senal { pid, detalle: "el sistema rechazó la señal".to_string() }),
+        None => Err(ErrorInstrumentos::Senal { pid, detalle: "señal no disponible en esta plataforma".to_string() }),
+    }
+}
+
 /// Implementación del trait Display para la estructura `ProcesoInfo`.
 /// Permite mostrar la información del proceso en un formato legible.
 impl fmt::Display for ProcesoInfo {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "PID: {}\nNombre: {}\nEstado: {}\nTiempo en ejecución: {}s\nTiempo en CPU: {}s\nUso de CPU: {:.2}%\nUso de Memoria: {} MB\nUso de Memoria Virtual: {} MB",
+            "PID: {}\nNombre: {}\nUsuario: {}\nEstado: {}\nTiempo en ejecución: {}s\nTiempo en CPU: {}s\nUso de CPU: {:.2}%\nUso de Memoria: {} MB\nUso de Memoria Virtual: {} MB",
             self.pid,
             self.nombre,
+            self.usuario,
             self.estado,
             self.tiempo_ejecucion,
             //ProcesoInfo::formatear_tiempo(self.tiempo_ejecucion),
@@ -287,10 +824,284 @@ impl fmt::Display for ProcesoInfo {
             self.uso_cpu,
             self.uso_memoria,
             self.uso_memoria_virtual
-        )
+        )?;
+        if let Some(cgroup) = &self.cgroup {
+            write!(f, "\nCgroup: {}", cgroup)?;
+        }
+        if let Some(seguridad) = &self.seguridad {
+            write!(f, "\n{}", seguridad.to_string().trim_end())?;
+        }
+        Ok(())
     }
 }
 
-pub fn obtener_info_procesos() -> ProcesosInfo {
+pub fn obtener_info_procesos() -> Resultado<ProcesosInfo> {
     ProcesosInfo::new()
-}
\ No newline at end of file
+}
+
+impl fmt::Display for ProcesosInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Cantidad de procesos: {}", self.cantidad_procesos)?;
+        writeln!(f, "Top 5 procesos que consumen más CPU:")?;
+        for proceso in &self.top_procesos_uso_cpu {
+            writeln!(f, "{}", proceso)?;
+        }
+        writeln!(f, "Top 5 procesos que consumen más memoria:")?;
+        for proceso in &self.top_procesos_uso_memoria {
+            writeln!(f, "{}", proceso)?;
+        }
+        writeln!(f, "Top 5 procesos que más tiempo han estado en CPU:")?;
+        for proceso in &self.top_procesos_tiempo_cpu {
+            writeln!(f, "{}", proceso)?;
+        }
+        writeln!(f, "Top 5 procesos que más tiempo han estado en ejecución:")?;
+        for proceso in &self.top_procesos_tiempo_ejecucion {
+            writeln!(f, "{}", proceso)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SeguridadProceso {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Namespaces: {:?}", self.namespaces)?;
+        writeln!(f, "Fuera del host: {}", self.fuera_del_host)?;
+        match self.capacidades_efectivas {
+            Some(mascara) => writeln!(f, "Capacidades efectivas: {:016x} {:?}", mascara, nombres_capacidades(mascara))?,
+            None => writeln!(f, "Capacidades efectivas: ilegibles")?,
+        }
+        match self.capacidades_permitidas {
+            Some(mascara) => writeln!(f, "Capacidades permitidas: {:016x}", mascara)?,
+            None => writeln!(f, "Capacidades permitidas: ilegibles")?,
+        }
+        writeln!(f, "NoNewPrivs: {}", self.no_new_privs)?;
+        writeln!(f, "Seccomp: {:?}", self.seccomp)?;
+        if self.sospechoso && self.capacidades_efectivas.is_none() {
+            writeln!(f, "SOSPECHOSO: capacidades ilegibles fuera del host")?;
+        } else if self.sospechoso {
+            writeln!(f, "SOSPECHOSO: capacidades peligrosas {:?} fuera del host", self.capacidades_peligrosas)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAPS: &str = "\
+55d4c8a00000-55d4c8a28000 r--p 00000000 08:01 1835023                    /usr/bin/bash
+55d4c8a28000-55d4c8b05000 r-xp 00028000 08:01 1835023                    /usr/bin/bash
+7f3a1c000000-7f3a1c021000 rw-p 00000000 00:00 0 
+7f3a1d200000-7f3a1d228000 r--p 00000000 08:01 1836290                    /usr/lib/x86_64-linux-gnu/libc.so.6
+7f3a1d228000-7f3a1d3bd000 r-xp 00028000 08:01 1836290                    /usr/lib/x86_64-linux-gnu/libc.so.6
+7f3a1d400000-7f3a1d401000 r-xp 00000000 00:01 2048                       /memfd:carga (deleted)
+7f3a1d500000-7f3a1d501000 rwxp 00000000 00:00 0 
+7ffc2a9f0000-7ffc2aa11000 rw-p 00000000 00:00 0                          [stack]
+";
+
+    const SMAPS_ROLLUP: &str = "\
+55d4c8a00000-7ffc2aa11000 ---p 00000000 00:00 0                          [rollup]
+Rss:                5120 kB
+Pss:                2048 kB
+Pss_Anon:            900 kB
+Shared_Clean:       3000 kB
+Private_Clean:       100 kB
+Private_Dirty:      1000 kB
+Swap:                 64 kB
+";
+
+    const STATUS: &str = "\
+Name:\tcontenedor
+Umask:\t0022
+State:\tS (sleeping)
+Pid:\t4242
+PPid:\t4200
+Uid:\t0\t0\t0\t0
+CapInh:\t0000000000000000
+CapPrm:\t00000000a82425fb
+CapEff:\t00000000a80425fb
+CapBnd:\t00000000a80425fb
+CapAmb:\t0000000000000000
+NoNewPrivs:\t1
+Seccomp:\t2
+Seccomp_filters:\t1
+";
+
+    fn namespaces(pid: u64, net: u64, mnt: u64, user: u64) -> Namespaces {
+        Namespaces { pid: Some(pid), net: Some(net), mnt: Some(mnt), user: Some(user) }
+    }
+
+    // `STATUS` con la línea `campo` reemplazada por `linea` (o quitada si es vacía)
+    fn status_con(campo: &str, linea: &str) -> String {
+        STATUS
+            .lines()
+            .map(|l| if l.starts_with(&format!("{}:", campo)) { linea } else { l })
+            .filter(|l| !l.is_empty())
+            .map(|l| format!("{}\n", l))
+            .collect()
+    }
+
+    #[test]
+    fn seguridad_desde_status() {
+        let host = namespaces(1, 2, 3, 4);
+        let seguridad = SeguridadProceso::desde_status(STATUS, host.clone(), &host);
+        assert_eq!(seguridad.get_capacidades_efectivas(), Some(0xa80425fb));
+        assert_eq!(seguridad.get_capacidades_permitidas(), Some(0xa82425fb));
+        assert!(seguridad.get_no_new_privs());
+        assert_eq!(seguridad.get_seccomp(), Some(ModoSeccomp::Filtro));
+        assert!(!seguridad.get_fuera_del_host());
+        // CAP_SYS_ADMIN no está en el conjunto por defecto de docker
+        assert!(seguridad.get_capacidades_peligrosas().is_empty());
+        assert!(!seguridad.get_sospechoso());
+    }
+
+    #[test]
+    fn capacidades_peligrosas_fuera_del_host_son_sospechosas() {
+        let host = namespaces(1, 2, 3, 4);
+        let status = status_con("CapEff", "CapEff:\t000001ffffffffff");
+        let seguridad = SeguridadProceso::desde_status(&status, namespaces(10, 2, 3, 4), &host);
+        assert!(seguridad.get_fuera_del_host());
+        assert_eq!(seguridad.get_capacidades_peligrosas().len(), CAPACIDADES_PELIGROSAS.len());
+        assert!(seguridad.get_sospechoso());
+
+        // las mismas capacidades dentro del host no son sospechosas
+        assert!(!SeguridadProceso::desde_status(&status, host.clone(), &host).get_sospechoso());
+    }
+
+    #[test]
+    fn capacidades_ilegibles_quedan_sin_valor() {
+        let host = namespaces(1, 2, 3, 4);
+        for linea in ["CapEff:\tzzzz", "CapEff:", ""] {
+            let status = status_con("CapEff", linea);
+            let seguridad = SeguridadProceso::desde_status(&status, host.clone(), &host);
+            assert_eq!(seguridad.get_capacidades_efectivas(), None, "{:?}", linea);
+            assert_eq!(seguridad.get_capacidades_permitidas(), Some(0xa82425fb));
+            assert!(!seguridad.get_sospechoso());
+
+            // fuera del host no se puede descartar que sean peligrosas
+            let seguridad = SeguridadProceso::desde_status(&status, namespaces(1, 20, 3, 4), &host);
+            assert!(seguridad.get_sospechoso(), "{:?}", linea);
+        }
+    }
+
+    #[test]
+    fn seccomp_y_no_new_privs() {
+        let host = Namespaces::default();
+        let modo = |linea: &str| SeguridadProceso::desde_status(&status_con("Seccomp", linea), host.clone(), &host).get_seccomp();
+        assert_eq!(modo("Seccomp:\t0"), Some(ModoSeccomp::Deshabilitado));
+        assert_eq!(modo("Seccomp:\t1"), Some(ModoSeccomp::Estricto));
+        assert_eq!(modo("Seccomp:\t7"), None);
+        assert_eq!(modo(""), None);
+        let seguridad = SeguridadProceso::desde_status(&status_con("NoNewPrivs", "NoNewPrivs:\t0"), host.clone(), &host);
+        assert!(!seguridad.get_no_new_privs());
+    }
+
+    #[test]
+    fn fuera_del_host_ignora_mnt_y_namespaces_desconocidos() {
+        let host = namespaces(1, 2, 3, 4);
+        assert!(!namespaces(1, 2, 30, 4).difiere_de(&host));
+        assert!(namespaces(10, 2, 3, 4).difiere_de(&host));
+        assert!(namespaces(1, 20, 3, 4).difiere_de(&host));
+        assert!(namespaces(1, 2, 3, 40).difiere_de(&host));
+        let desconocido = Namespaces { pid: None, ..namespaces(1, 2, 3, 4) };
+        assert!(!Namespaces { pid: Some(10), ..desconocido.clone() }.difiere_de(&desconocido));
+        assert!(host.completos());
+        assert!(!desconocido.completos());
+    }
+
+    #[test]
+    fn nombres_de_capacidades() {
+        assert_eq!(nombres_capacidades(0), Vec::<&str>::new());
+        assert_eq!(nombres_capacidades(1 | 1 << 21), vec!["CAP_CHOWN", "CAP_SYS_ADMIN"]);
+        assert_eq!(nombres_capacidades(1 << 63), vec!["CAP_DESCONOCIDA"]);
+    }
+
+    #[test]
+    fn cache_reutiliza_la_entrada_del_mismo_proceso() {
+        let pid = std::process::id() as i32;
+        let mut cache = CacheProcesos::default();
+        let (cgroup, seguridad) = cache.consultar(pid, 100);
+        assert!(seguridad.is_some());
+        let leida = cache.entradas[&pid].leida;
+        assert_eq!(cache.consultar(pid, 100).0, cgroup);
+        assert_eq!(cache.entradas[&pid].leida, leida);
+
+        // otra hora de inicio es otro proceso con el mismo PID
+        cache.consultar(pid, 200);
+        assert_eq!(cache.entradas[&pid].inicio, 200);
+        assert!(cache.entradas[&pid].leida > leida);
+
+        // una entrada vencida se vuelve a leer
+        let vencida = Instant::now().checked_sub(VIGENCIA_CACHE).unwrap();
+        cache.entradas.get_mut(&pid).unwrap().leida = vencida;
+        cache.consultar(pid, 200);
+        assert!(cache.entradas[&pid].leida > vencida);
+    }
+
+    #[test]
+    fn region_desde_linea_de_maps() {
+        let region = RegionMemoria::desde_linea(MAPS.lines().nth(1).unwrap()).unwrap();
+        assert_eq!((region.inicio, region.fin), (0x55d4c8a28000, 0x55d4c8b05000));
+        assert_eq!(region.permisos, "r-xp");
+        assert_eq!(region.desplazamiento, 0x28000);
+        assert_eq!((region.dispositivo.as_str(), region.inodo), ("08:01", 1835023));
+        assert_eq!(region.ruta, "/usr/bin/bash");
+        assert_eq!(region.tamano_kb(), (0x55d4c8b05000 - 0x55d4c8a28000) / 1024);
+        assert!(region.ejecutable());
+    }
+
+    #[test]
+    fn region_anonima_sin_ruta() {
+        let region = RegionMemoria::desde_linea("7f3a1c000000-7f3a1c021000 rw-p 00000000 00:00 0 ").unwrap();
+        assert_eq!(region.ruta, "");
+        assert!(!region.ejecutable());
+    }
+
+    #[test]
+    fn region_con_fin_menor_que_inicio_mide_cero() {
+        let region = RegionMemoria::desde_linea("2000-1000 r--p 00000000 00:00 0").unwrap();
+        assert_eq!(region.tamano_kb(), 0);
+    }
+
+    #[test]
+    fn lineas_de_maps_invalidas() {
+        for linea in ["", "basura", "zz-10 r--p 0 00:00 0", "1000-2000 r--p 0 00:00", "1000-2000 r--p 0 00:00 x /a"] {
+            assert!(RegionMemoria::desde_linea(linea).is_none(), "se aceptó {:?}", linea);
+        }
+    }
+
+    #[test]
+    fn mapas_desde_texto() {
+        let mapas = MapasMemoria::desde_texto(MAPS, Some(SMAPS_ROLLUP));
+        assert_eq!(mapas.regiones.len(), 8);
+        assert!(mapas.lineas_invalidas.is_empty());
+        assert_eq!((mapas.rss_kb, mapas.pss_kb, mapas.uss_kb, mapas.swap_kb), (5120, 2048, 1100, 64));
+        assert_eq!(mapas.bibliotecas(), vec!["/usr/lib/x86_64-linux-gnu/libc.so.6"]);
+        let sospechosas: Vec<&str> = mapas.regiones_sospechosas().iter().map(|r| r.permisos.as_str()).collect();
+        assert_eq!(sospechosas, vec!["r-xp", "rwxp"]);
+        assert_eq!(mapas.regiones_sospechosas()[0].ruta, "/memfd:carga (deleted)");
+    }
+
+    #[test]
+    fn mapas_descarta_lineas_ilegibles() {
+        let maps = "\
+55d4c8a00000-55d4c8a28000 r--p 00000000 08:01 1835023                    /usr/bin/bash
+esto no es una región
+
+55d4c8a28000-55d4c8b05000 r-xp 00028000 08:01 1835023                    /usr/bin/bash
+";
+        let mapas = MapasMemoria::desde_texto(maps, None);
+        assert_eq!(mapas.regiones.len(), 2);
+        assert_eq!(mapas.lineas_invalidas, vec!["línea 2: \"esto no es una región\"".to_string()]);
+        // sin smaps_rollup los totales quedan en cero
+        assert_eq!((mapas.rss_kb, mapas.pss_kb, mapas.uss_kb, mapas.swap_kb), (0, 0, 0, 0));
+    }
+
+    #[test]
+    fn smaps_rollup_con_valor_ilegible_cuenta_cero() {
+        let mapas = MapasMemoria::desde_texto("", Some("Rss: mucho kB\nPss: 10 kB\nPrivate_Dirty: 4 kB\n"));
+        assert!(mapas.regiones.is_empty());
+        assert_eq!((mapas.rss_kb, mapas.pss_kb, mapas.uss_kb), (0, 10, 4));
+    }
+}
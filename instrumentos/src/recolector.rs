@@ -0,0 +1,200 @@
+//! Módulo que define las fuentes de métricas como recolectores intercambiables.
+//!
+//! Cada fuente (CPU, memoria, procesos, sensores, conexiones, ...) implementa el
+//! trait `Recolector` y se agrega a un `Registro`. El ciclo de monitoreo recorre
+//! el registro sin conocer las fuentes concretas, de modo que agregar una fuente
+//! nueva no obliga a modificar las estructuras ni los paneles existentes.
+//!
+//! ### `Recolector`
+//! - `nombre`: Nombre único de la fuente (se usa como clave en el snapshot).
+//! - `intervalo`: Cada cuánto debe muestrearse la fuente.
+//! - `muestrear`: Toma una muestra y la devuelve como `Lectura`, o el `ErrorInstrumentos`
+//!   que impidió tomarla (el ciclo de monitoreo conserva entonces la lectura anterior).
+//!
+//! ### `Lectura`
+//! Salida serializable de un recolector. Las fuentes integradas tienen su propia
+//! variante; cualquier otra fuente usa `Lectura::Otro` con un valor JSON.
+//!
+//! ### `Registro`
+//! Lista ordenada de recolectores.
+//! - `new`: Registro vacío.
+//! - `por_defecto`: Registro con las fuentes integradas (cpu, memoria, red, disco, procesos, cgroups, inventario),
+//!   cada una con su propio intervalo: CPU, memoria y red cada segundo, procesos y cgroups cada 5 s,
+//!   discos cada 30 s e inventario cada hora.
+//! - `integrados`: Registro con solo algunas de las fuentes integradas y los intervalos indicados
+//!   (por ejemplo, los que vienen del archivo de configuración).
+//! - `registrar`: Agrega un recolector (reemplaza al que tenga el mismo nombre).
+//! - `quitar`: Elimina un recolector por nombre.
+//! - `nombres`: Devuelve los nombres registrados.
+//! - `recolectores_mut`: Acceso mutable a los recolectores para muestrearlos.
+//!
+//! ### `RecolectorSistema`
+//! Recolector integrado que toma su muestra de un `Muestreador` compartido,
+//! de modo que CPU, memoria y procesos reutilizan el mismo `System`.
+//!
+//! Historial de cambios
+//! - 2026-10-18: Creación del módulo.
+//! - 2026-10-18: Intervalos independientes por recolector y fuente `inventario`.
+//! - 2026-10-18: `muestrear` devuelve `Resultado<Lectura>`.
+//! - 2026-10-18: `INTEGRADOS` y `Registro::integrados` para elegir fuentes e intervalos.
+//! - 2026-10-18: Todas las fuentes integradas propagan el error de su muestreo.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::cgroups::CgroupsInfo;
+use crate::cpu::CPUInfo;
+use crate::disco::DiscosInfo;
+use crate::error::Resultado;
+use crate::interfaces::InterfacesInfo;
+use crate::inventario::InventarioInfo;
+use crate::memoria::MemoriaInfo;
+use crate::muestreador::Muestreador;
+use crate::procesos::ProcesosInfo;
+
+/// Intervalos de los recolectores integrados.
+const INTERVALO_RAPIDO: Duration = Duration::from_secs(1);
+const INTERVALO_PROCESOS: Duration = Duration::from_secs(5);
+const INTERVALO_DISCOS: Duration = Duration::from_secs(30);
+const INTERVALO_INVENTARIO: Duration = Duration::from_secs(60 * 60);
+
+/// Fuentes integradas con su intervalo por defecto, en el orden de `por_defecto`.
+pub const INTEGRADOS: [(&str, Duration); 7] = [
+    ("cpu", INTERVALO_RAPIDO),
+    ("memoria", INTERVALO_RAPIDO),
+    ("red", INTERVALO_RAPIDO),
+    ("disco", INTERVALO_DISCOS),
+    ("procesos", INTERVALO_PROCESOS),
+    ("cgroups", INTERVALO_PROCESOS),
+    ("inventario", INTERVALO_INVENTARIO),
+];
+
+pub trait Recolector: Send {
+    /// Nombre único de la fuente.
+    fn nombre(&self) -> &str;
+    /// Intervalo deseado entre dos muestras.
+    fn intervalo(&self) -> Duration;
+    /// Toma una muestra de la fuente.
+    fn muestrear(&mut self) -> Resultado<Lectura>;
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "tipo", content = "datos", rename_all = "snake_case")]
+pub enum Lectura {
+    Cpu(CPUInfo),
+    Memoria(MemoriaInfo),
+    Red(InterfacesInfo),
+    Disco(DiscosInfo),
+    Procesos(ProcesosInfo),
+    Cgroups(CgroupsInfo),
+    Inventario(InventarioInfo),
+    Otro(serde_json::Value),
+}
+
+impl Lectura {
+    /// Crea una lectura genérica a partir de cualquier valor serializable.
+    ///
+    /// Si el valor no se puede representar en JSON se guarda `null`.
+    pub fn otro<T: Serialize>(valor: &T) -> Self {
+        Lectura::Otro(serde_json::to_value(valor).unwrap_or(serde_json::Value::Null))
+    }
+}
+
+pub struct Registro {
+    recolectores: Vec<Box<dyn Recolector>>,
+}
+
+impl Registro {
+    /// Crea un registro vacío.
+    pub fn new() -> Self {
+        Registro { recolectores: Vec::new() }
+    }
+
+    /// Crea un registro con las fuentes integradas, todas sobre un mismo `Muestreador`.
+    pub fn por_defecto() -> Self {
+        Self::integrados(&INTEGRADOS)
+    }
+
+    /// Crea un registro con las fuentes integradas indicadas, en ese orden y con
+    /// esos intervalos, todas sobre un mismo `Muestreador`.
+    ///
+    /// Los nombres que no corresponden a ninguna fuente integrada se ignoran.
+    pub fn integrados(seleccion: &[(&str, Duration)]) -> Self {
+        let muestreador = Arc::new(Mutex::new(Muestreador::new()));
+        let mut registro = Registro::new();
+        for &(nombre, intervalo) in seleccion {
+            let funcion: fn(&mut Muestreador) -> Resultado<Lectura> = match nombre {
+                "cpu" => |m| m.cpu().map(Lectura::Cpu),
+                "memoria" => |m| m.memoria().map(Lectura::Memoria),
+                "red" => |m| m.red().map(Lectura::Red),
+                "disco" => |m| m.discos().map(Lectura::Disco),
+                "procesos" => |m| m.procesos().map(Lectura::Procesos),
+                "cgroups" => |m| m.cgroups().map(Lectura::Cgroups),
+                "inventario" => |m| m.inventario().map(Lectura::Inventario),
+                _ => continue,
+            };
+            registro.registrar(Box::new(RecolectorSistema {
+                nombre: nombre.to_string(),
+                intervalo,
+                muestreador: Arc::clone(&muestreador),
+                funcion,
+            }));
+        }
+        registro
+    }
+
+    /// Agrega un recolector. Si ya existe uno con el mismo nombre, se reemplaza.
+    pub fn registrar(&mut self, recolector: Box<dyn Recolector>) {
+        self.quitar(recolector.nombre().to_string().as_str());
+        self.recolectores.push(recolector);
+    }
+
+    /// Elimina el recolector con el nombre indicado.
+    ///
+    /// # Retorno
+    /// `true` si existía.
+    pub fn quitar(&mut self, nombre: &str) -> bool {
+        let antes = self.recolectores.len();
+        self.recolectores.retain(|r| r.nombre() != nombre);
+        antes != self.recolectores.len()
+    }
+
+    /// Devuelve los nombres de los recolectores registrados, en orden.
+    pub fn nombres(&self) -> Vec<&str> {
+        self.recolectores.iter().map(|r| r.nombre()).collect()
+    }
+
+    /// Acceso mutable a los recolectores, en el orden en que se registraron.
+    pub fn recolectores_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Recolector>> {
+        self.recolectores.iter_mut()
+    }
+}
+
+impl Default for Registro {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct RecolectorSistema {
+    nombre: String,
+    intervalo: Duration,
+    muestreador: Arc<Mutex<Muestreador>>,
+    funcion: fn(&mut Muestreador) -> Resultado<Lectura>,
+}
+
+impl Recolector for RecolectorSistema {
+    fn nombre(&self) -> &str {
+        &self.nombre
+    }
+
+    fn intervalo(&self) -> Duration {
+        self.intervalo
+    }
+
+    fn muestrear(&mut self) -> Resultado<Lectura> {
+        // un pánico previo no invalida los manejadores de sysinfo
+        let mut muestreador = self.muestreador.lock().unwrap_or_else(|e| e.into_inner());
+        (self.funcion)(&mut muestreador)
+    }
+}
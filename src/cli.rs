@@ -0,0 +1,396 @@
+//! Modo de línea de comandos: toma una muestra y la imprime sin abrir ventana.
+//!
+//! Uso: `sistema_defensa <subcomando> [--json] [--top N]`
+//!
+//! - `snapshot`: Todas las fuentes (con `--json`, el mismo formato que la exportación de la GUI).
+//! - `cpu`, `mem`, `disk`, `net`, `procs`: Una sola fuente.
+//! - `tui`: Interfaz de terminal interactiva (ver `tui`).
+//! - `daemon`: Monitoreo sin ventana con socket de control (ver `gui::daemon`).
+//! - `stop`: Detiene el daemon.
+//! - `agent`: Envía los snapshots a una instancia central (ver `gui::flota`).
+//!
+//! Con `--socket RUTA`, `snapshot` y `tui` leen del daemon en lugar de muestrear.
+//! `--config RUTA` elige el archivo de configuración de la GUI, `tui`, `daemon`
+//! y `agent` (ver `gui::config`).
+//! Con `--influx DESTINO` o `--graphite DESTINO`, el daemon envía cada snapshot
+//! a ese destino (ver `gui::salidas`).
+//! Sin `--tokens`, `--http` solo acepta direcciones de loopback, salvo que se
+//! pase `--sin-autenticacion` (ver `gui::seguridad`).
+//!
+//! El texto legible sale de las implementaciones de `Display` de `instrumentos`
+//! (las mismas que usa `mostrar_info`); las listas se imprimen como tabla.
+
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use gui::config::{self, Configuracion};
+use gui::daemon::{self, Cliente, OpcionesDaemon, Peticion, Respuesta};
+use gui::flota;
+use gui::metrics::Metric;
+use gui::salidas::{Destino, Formato};
+use gui::seguridad::{OpcionesCliente, OpcionesSeguridad};
+use instrumentos::disco::DiscosInfo;
+use instrumentos::interfaces::InterfacesInfo;
+use instrumentos::muestreador::{Muestreador, INTERVALO_MINIMO_CPU};
+use instrumentos::procesos::ProcesosInfo;
+use serde::Serialize;
+use crate::tui;
+
+pub const SUBCOMANDOS: [&str; 10] = ["snapshot", "cpu", "mem", "disk", "net", "procs", "tui", "daemon", "stop", "agent"];
+
+const USO: &str = "Uso: sistema_defensa [snapshot|cpu|mem|disk|net|procs|tui|daemon|stop|agent] [--config RUTA] [--json] [--top N] [--socket RUTA]
+       [--http DIRECCION] [--influx DESTINO] [--graphite DESTINO] [--server DIRECCION] [--name NOMBRE]
+       [--tls-cert PEM --tls-key PEM] [--tls-client-ca PEM] [--tls-ca PEM] [--tokens ARCHIVO] [--token TOKEN]
+       [--sin-autenticacion]
+Sin subcomando se abre la interfaz gráfica.
+
+  snapshot   todas las fuentes
+  cpu        uso por núcleo
+  mem        memoria RAM y swap
+  disk       discos montados
+  net        interfaces de red
+  procs      procesos ordenados por uso de CPU
+  tui        panel interactivo en la terminal
+  daemon     monitoreo en segundo plano con socket de control
+  stop       detiene el daemon
+  agent      envía los snapshots a la vista Flota de otra instancia
+
+  --config   archivo de configuración (por defecto, el primero que exista de
+             $XDG_CONFIG_HOME/sistema_defensa/config.toml y /etc/sistema_defensa/config.toml)
+  --json     imprime JSON en lugar de texto
+  --top N    en procs, muestra solo los N primeros
+  --socket   socket del daemon (por defecto $XDG_RUNTIME_DIR/sistema_defensa.sock);
+             con snapshot y tui, lee del daemon en lugar de muestrear
+  --http     en daemon, sirve la API HTTP en esa dirección (p. ej. 127.0.0.1:9100)
+  --influx   en daemon, envía cada snapshot en el protocolo de líneas de InfluxDB
+  --graphite en daemon, envía cada snapshot en el texto plano de Graphite;
+             DESTINO es udp://host:puerto, tcp://host:puerto o una ruta de archivo
+             (se pueden repetir)
+  --server   en agent, dirección de la instancia central (p. ej. 10.0.0.5:9200)
+  --name     en agent, nombre del equipo en la flota (por defecto, el nombre de host)
+
+  --tls-cert, --tls-key
+             certificado y clave PEM: en daemon, la API HTTP usa TLS;
+             en agent, certificado de cliente para TLS mutuo
+  --tls-client-ca
+             en daemon, exige certificados de cliente firmados por esta CA
+  --tls-ca   en agent, CA que firma el certificado del servidor (activa TLS)
+  --tokens   en daemon, archivo de tokens con alcance lectura o control
+             (sin él, la API no pide token, no permite acciones y solo
+             escucha en loopback)
+  --sin-autenticacion
+             en daemon, permite servir la API sin tokens en una dirección
+             que no es de loopback (cualquiera en la red verá los procesos)
+  --token    en agent, token con alcance agente";
+
+/// Opciones de una invocación de la CLI.
+#[derive(Debug)]
+pub struct Opciones {
+    /// `None` abre la GUI.
+    pub subcomando: Option<String>,
+    pub config: Option<PathBuf>,
+    pub json: bool,
+    pub top: Option<usize>,
+    pub socket: Option<PathBuf>,
+    pub http: Option<String>,
+    pub salidas: Vec<(Formato, Destino)>,
+    pub servidor: Option<String>,
+    pub nombre: Option<String>,
+    pub seguridad: OpcionesSeguridad,
+    pub cliente: OpcionesCliente,
+}
+
+impl Opciones {
+    /// Interpreta los argumentos (sin el nombre del programa).
+    ///
+    /// Sin subcomando solo se acepta `--config` (se abre la GUI).
+    pub fn desde_args(args: &[String]) -> Result<Self, String> {
+        let mut subcomando = None;
+        let mut config = None;
+        let mut json = false;
+        let mut top = None;
+        let mut socket = None;
+        let mut http = None;
+        let mut salidas = Vec::new();
+        let mut servidor = None;
+        let mut nombre = None;
+        let mut seguridad = OpcionesSeguridad::default();
+        let mut cliente = OpcionesCliente::default();
+        let total = args.len();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => config = Some(PathBuf::from(args.next().ok_or("--config necesita una ruta")?)),
+                "--json" => json = true,
+                "--top" => {
+                    let valor = args.next().ok_or("--top necesita un número")?;
+                    top = Some(valor.parse().map_err(|_| format!("--top: '{}' no es un número", valor))?);
+                }
+                "--socket" => socket = Some(PathBuf::from(args.next().ok_or("--socket necesita una ruta")?)),
+                "--http" => http = Some(args.next().ok_or("--http necesita una dirección")?.clone()),
+                "--influx" | "--graphite" => {
+                    let valor = args.next().ok_or_else(|| format!("{} necesita un destino", arg))?;
+                    let formato = if arg == "--influx" { Formato::Influx } else { Formato::Graphite };
+                    salidas.push((formato, Destino::desde_texto(valor).map_err(|e| format!("{}: {}", arg, e))?));
+                }
+                "--server" => servidor = Some(args.next().ok_or("--server necesita una dirección")?.clone()),
+                "--name" => nombre = Some(args.next().ok_or("--name necesita un nombre")?.clone()),
+                "--tls-cert" | "--tls-key" | "--tls-client-ca" | "--tls-ca" | "--tokens" => {
+                    let ruta = PathBuf::from(args.next().ok_or_else(|| format!("{} necesita una ruta", arg))?);
+                    match arg.as_str() {
+                        // el mismo par sirve al servidor y, en agent, como certificado de cliente
+                        "--tls-cert" => (seguridad.certificado, cliente.certificado) = (Some(ruta.clone()), Some(ruta)),
+                        "--tls-key" => (seguridad.clave, cliente.clave) = (Some(ruta.clone()), Some(ruta)),
+                        "--tls-client-ca" => seguridad.ca_clientes = Some(ruta),
+                        "--tls-ca" => cliente.ca = Some(ruta),
+                        _ => seguridad.tokens = Some(ruta),
+                    }
+                }
+                "--token" => cliente.token = Some(args.next().ok_or("--token necesita un token")?.clone()),
+                "--sin-autenticacion" => seguridad.sin_autenticacion = true,
+                "-h" | "--help" | "ayuda" | "help" => return Err(USO.to_string()),
+                otro if SUBCOMANDOS.contains(&otro) && subcomando.is_none() => subcomando = Some(otro.to_string()),
+                otro => return Err(format!("argumento desconocido: {}\n\n{}", otro, USO)),
+            }
+        }
+        if subcomando.is_none() && total > if config.is_some() { 2 } else { 0 } {
+            return Err(format!("falta el subcomando\n\n{}", USO));
+        }
+        Ok(Opciones { subcomando, config, json, top, socket, http, salidas, servidor, nombre, seguridad, cliente })
+    }
+}
+
+/// Lee el archivo de configuración (`--config` o el de la ruta de búsqueda).
+///
+/// # Retorno
+/// La configuración y el archivo que conviene vigilar para recargarla: el que
+/// se leyó o, si no había ninguno, el del usuario (por si se crea después).
+pub fn configuracion(opciones: &Opciones) -> Result<(Configuracion, Option<PathBuf>), String> {
+    let (configuracion, ruta) = Configuracion::buscar(opciones.config.as_deref()).map_err(|e| e.to_string())?;
+    if let Some(ruta) = &ruta {
+        eprintln!("Configuración: {}", ruta.display());
+    }
+    Ok((configuracion, ruta.or_else(config::ruta_usuario)))
+}
+
+/// Ejecuta el subcomando e imprime el resultado en la salida estándar.
+pub fn ejecutar(opciones: &Opciones) -> Result<(), String> {
+    let subcomando = opciones.subcomando.as_deref().unwrap_or_default();
+    match subcomando {
+        "tui" => return tui::ejecutar(opciones.socket.as_deref(), &configuracion(opciones)?.0).map_err(|e| e.to_string()),
+        "daemon" => {
+            let (configuracion, ruta_configuracion) = configuracion(opciones)?;
+            let opciones_daemon = OpcionesDaemon {
+                configuracion,
+                ruta_configuracion,
+                http: opciones.http.clone(),
+                seguridad: opciones.seguridad.clone(),
+                salidas: opciones.salidas.clone(),
+            };
+            return daemon::ejecutar(&ruta_socket(opciones)?, &opciones_daemon).map_err(|e| e.to_string());
+        }
+        "agent" => {
+            let servidor = opciones.servidor.as_deref().ok_or("agent necesita --server DIRECCION")?;
+            flota::ejecutar_agente(servidor, opciones.nombre.as_deref(), &opciones.cliente, &configuracion(opciones)?.0);
+        }
+        "stop" => {
+            let ruta = ruta_socket(opciones)?;
+            return match Cliente::conectar(&ruta).and_then(|mut c| c.pedir(&Peticion::Detener)) {
+                Ok(Respuesta::Detenido) => Ok(()),
+                Ok(otra) => Err(format!("respuesta inesperada: {:?}", otra)),
+                Err(e) => Err(format!("{}: {}", ruta.display(), e)),
+            };
+        }
+        _ => {}
+    }
+    let mut salida = io::stdout().lock();
+    let resultado = if subcomando == "snapshot" {
+        let metric = match &opciones.socket {
+            Some(ruta) => snapshot_daemon(ruta)?,
+            None => Metric::recolectar(configuracion(opciones)?.0.zona()),
+        };
+        if opciones.json { imprimir_json(&mut salida, &metric) } else { imprimir_snapshot(&mut salida, &metric) }
+    } else {
+        let mut muestreador = Muestreador::new();
+        // CPU y procesos necesitan dos lecturas para calcular el uso
+        thread::sleep(INTERVALO_MINIMO_CPU);
+        let json = opciones.json;
+        match subcomando {
+            "cpu" => {
+                let cpu = muestreador.cpu().map_err(|e| e.to_string())?;
+                if json { imprimir_json(&mut salida, &cpu) } else { imprimir_texto(&mut salida, &cpu) }
+            }
+            "mem" => {
+                let memoria = muestreador.memoria().map_err(|e| e.to_string())?;
+                if json { imprimir_json(&mut salida, &memoria) } else { imprimir_texto(&mut salida, &memoria) }
+            }
+            "disk" => {
+                let discos = muestreador.discos().map_err(|e| e.to_string())?;
+                if json { imprimir_json(&mut salida, &discos) } else { imprimir_discos(&mut salida, &discos) }
+            }
+            "net" => {
+                let red = muestreador.red().map_err(|e| e.to_string())?;
+                if json { imprimir_json(&mut salida, &red) } else { imprimir_red(&mut salida, &red) }
+            }
+            _ => {
+                let procesos = muestreador.procesos().map_err(|e| e.to_string())?;
+                if json { imprimir_json(&mut salida, &procesos) } else { imprimir_procesos(&mut salida, &procesos, opciones.top) }
+            }
+        }
+    };
+    match resultado {
+        // `| head` cierra la tubería antes de tiempo; no es un error
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        otro => otro.map_err(|e| e.to_string()),
+    }
+}
+
+fn ruta_socket(opciones: &Opciones) -> Result<PathBuf, String> {
+    opciones
+        .socket
+        .clone()
+        .or_else(daemon::ruta_socket_por_defecto)
+        .ok_or_else(|| "no se pudo determinar la ruta del socket; usar --socket".to_string())
+}
+
+fn snapshot_daemon(ruta: &Path) -> Result<Metric, String> {
+    let respuesta = Cliente::conectar(ruta).and_then(|mut c| c.pedir(&Peticion::Snapshot));
+    match respuesta {
+        Ok(Respuesta::Snapshot(Some(metric))) => Ok(metric),
+        Ok(Respuesta::Snapshot(None)) => Err("el daemon aún no tiene snapshots".to_string()),
+        Ok(otra) => Err(format!("respuesta inesperada: {:?}", otra)),
+        Err(e) => Err(format!("{}: {}", ruta.display(), e)),
+    }
+}
+
+pub fn uso() -> &'static str {
+    USO
+}
+
+fn imprimir_json<T: Serialize>(salida: &mut impl Write, valor: &T) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *salida, valor)?;
+    writeln!(salida)
+}
+
+fn imprimir_texto(salida: &mut impl Write, valor: &impl Display) -> io::Result<()> {
+    write!(salida, "{}", valor)
+}
+
+fn imprimir_snapshot(salida: &mut impl Write, metric: &Metric) -> io::Result<()> {
+    writeln!(salida, "Snapshot {}", metric.id)?;
+    if let Some(inventario) = metric.inventario() {
+        writeln!(salida, "\n== Equipo ==")?;
+        imprimir_texto(salida, inventario)?;
+    }
+    if let Some(cpu) = metric.cpu() {
+        writeln!(salida, "\n== CPU ==")?;
+        imprimir_texto(salida, cpu)?;
+    }
+    if let Some(memoria) = metric.memoria() {
+        writeln!(salida, "\n== Memoria ==")?;
+        imprimir_texto(salida, memoria)?;
+    }
+    if let Some(discos) = metric.disco() {
+        writeln!(salida, "\n== Discos ==")?;
+        imprimir_discos(salida, discos)?;
+    }
+    if let Some(red) = metric.red() {
+        writeln!(salida, "\n== Red ==")?;
+        imprimir_red(salida, red)?;
+    }
+    if let Some(procesos) = metric.procesos() {
+        writeln!(salida, "\n== Procesos (top 10 por CPU) ==")?;
+        imprimir_procesos(salida, procesos, Some(10))?;
+    }
+    for (nombre, error) in &metric.errores {
+        writeln!(salida, "\nError en {}: {}", nombre, error)?;
+    }
+    Ok(())
+}
+
+fn imprimir_discos(salida: &mut impl Write, discos: &DiscosInfo) -> io::Result<()> {
+    let filas = discos
+        .get_discos()
+        .iter()
+        .map(|d| {
+            vec![
+                d.get_nombre().to_string(),
+                d.get_sistema_archivos().to_string(),
+                format!("{:.1}", d.get_espacio_total()),
+                format!("{:.1}", d.get_espacio_usado()),
+                format!("{:.1}", d.get_espacio_libre()),
+                d.get_ruta().to_string(),
+            ]
+        })
+        .collect();
+    imprimir_tabla(salida, &["DISCO", "FS", "TOTAL GB", "USADO GB", "LIBRE GB", "RUTA"], filas)?;
+    writeln!(
+        salida,
+        "Total: {:.1} GB, usado {:.1} GB, libre {:.1} GB",
+        discos.get_espacio_total(), discos.get_espacio_usado(), discos.get_espacio_libre()
+    )
+}
+
+fn imprimir_red(salida: &mut impl Write, red: &InterfacesInfo) -> io::Result<()> {
+    let filas = red
+        .get_interfaces()
+        .iter()
+        .map(|i| {
+            vec![
+                i.get_nombre().to_string(),
+                i.get_bytes_recibidos().to_string(),
+                i.get_bytes_enviados().to_string(),
+                i.get_total_errores().to_string(),
+                i.get_direccion_ip().join(" "),
+            ]
+        })
+        .collect();
+    imprimir_tabla(salida, &["INTERFAZ", "RX BYTES", "TX BYTES", "ERRORES", "IPS"], filas)
+}
+
+fn imprimir_procesos(salida: &mut impl Write, procesos: &ProcesosInfo, top: Option<usize>) -> io::Result<()> {
+    let mut ordenados: Vec<_> = procesos.get_procesos().iter().collect();
+    ordenados.sort_by(|a, b| b.get_uso_cpu().total_cmp(&a.get_uso_cpu()));
+    let filas = ordenados
+        .into_iter()
+        .take(top.unwrap_or(usize::MAX))
+        .map(|p| {
+            vec![
+                p.get_pid().to_string(),
+                p.get_ppid().map(|ppid| ppid.to_string()).unwrap_or_default(),
+                p.get_usuario().to_string(),
+                format!("{:.1}", p.get_uso_cpu()),
+                p.get_uso_memoria().to_string(),
+                p.get_estado().to_string(),
+                p.get_nombre().to_string(),
+            ]
+        })
+        .collect();
+    imprimir_tabla(salida, &["PID", "PPID", "USUARIO", "CPU %", "MEM MB", "ESTADO", "NOMBRE"], filas)?;
+    writeln!(salida, "Procesos: {}", procesos.get_cantidad_procesos())
+}
+
+// Tabla alineada a la izquierda; la última columna no se rellena
+fn imprimir_tabla(salida: &mut impl Write, encabezados: &[&str], filas: Vec<Vec<String>>) -> io::Result<()> {
+    let mut anchos: Vec<usize> = encabezados.iter().map(|e| e.chars().count()).collect();
+    for fila in &filas {
+        for (ancho, celda) in anchos.iter_mut().zip(fila) {
+            *ancho = (*ancho).max(celda.chars().count());
+        }
+    }
+    let linea = |celdas: Vec<&str>| {
+        let ultima = celdas.len().saturating_sub(1);
+        celdas
+            .iter()
+            .enumerate()
+            .map(|(i, c)| if i == ultima { c.to_string() } else { format!("{:<ancho$}", c, ancho = anchos[i]) })
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+    writeln!(salida, "{}", linea(encabezados.to_vec()))?;
+    for fila in &filas {
+        writeln!(salida, "{}", linea(fila.iter().map(String::as_str).collect()))?;
+    }
+    Ok(())
+}
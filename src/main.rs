@@ -1,10 +1,47 @@
+mod cli;
+mod tui;
+
 use gui::SistemaDefensaApp;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::Opciones::desde_args(&args) {
+        Ok(opciones) if opciones.subcomando.is_none() => ejecutar_gui(&opciones),
+        Ok(opciones) => match cli::ejecutar(&opciones) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Err(mensaje) => {
+            eprintln!("{}", mensaje);
+            ExitCode::from(2)
+        }
+    }
+}
 
-fn main() -> eframe::Result<()> {
+fn ejecutar_gui(opciones: &cli::Opciones) -> ExitCode {
+    let (configuracion, ruta) = match cli::configuracion(opciones) {
+        Ok(encontrada) => encontrada,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
     let native_options = eframe::NativeOptions::default();
-    eframe::run_native(
+    let resultado = eframe::run_native(
         "Sistema de Defensa",
         native_options,
-        Box::new(|_cc| Ok(Box::new(SistemaDefensaApp::default()))),
-    )
+        Box::new(|cc| Ok(Box::new(SistemaDefensaApp::new(cc, configuracion, ruta)))),
+    );
+    match resultado {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // sin pantalla: sugerir el modo de línea de comandos
+            eprintln!("No se pudo abrir la ventana: {}\n\n{}", e, cli::uso());
+            ExitCode::FAILURE
+        }
+    }
 }
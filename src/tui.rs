@@ -0,0 +1,376 @@
+//! Interfaz de terminal: el mismo contenido que los paneles de métricas,
+//! gráficas y alertas de la GUI, para equipos a los que solo se entra por SSH.
+//!
+//! Usa el mismo `gui::metrics::Metrics` (hilo de monitoreo, retención y
+//! almacén) y `gui::alertas` para evaluar las alertas. Con un socket, los
+//! snapshots vienen de un daemon en lugar del monitoreo local.
+//!
+//! Teclas: `Tab`/`→` y `Shift+Tab`/`←` cambian de vista, `1`-`4` van a una
+//! vista, `q`/`Esc` salen. En cada vista, la barra inferior indica sus teclas.
+
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use chrono::Utc;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use gui::alertas::{self, Umbrales};
+use gui::config::Configuracion;
+use gui::metrics::{Metric, Metrics};
+use ratatui::DefaultTerminal;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Gauge, List, Paragraph, Row, Sparkline, Table, TableState, Tabs, Wrap};
+use ratatui::Frame;
+
+/// Cada cuánto se redibuja si no hay teclas pendientes.
+const REFRESCO: Duration = Duration::from_millis(250);
+
+/// Ventanas de tiempo de las gráficas, en minutos.
+const VENTANAS: [(&str, i64); 4] = [("5 min", 5), ("1 h", 60), ("24 h", 24 * 60), ("30 días", 30 * 24 * 60)];
+
+/// Series graficadas: título, serie de `Metric::valores`, máximo fijo (porcentajes) y color.
+const GRAFICAS: [(&str, &str, Option<f64>, Color); 4] = [
+    ("CPU %", "cpu.uso", Some(100.0), Color::Green),
+    ("Memoria %", "memoria.uso", Some(100.0), Color::Blue),
+    ("Red RX (MB)", "red.recibidos_mb", None, Color::Red),
+    ("Disco %", "disco.uso", Some(100.0), Color::Cyan),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Vista {
+    Metricas,
+    Graficas,
+    Procesos,
+    Alertas,
+}
+
+impl Vista {
+    const TODAS: [Vista; 4] = [Vista::Metricas, Vista::Graficas, Vista::Procesos, Vista::Alertas];
+
+    fn titulo(&self) -> &'static str {
+        match self {
+            Vista::Metricas => "Métricas",
+            Vista::Graficas => "Gráficas",
+            Vista::Procesos => "Procesos",
+            Vista::Alertas => "Alertas",
+        }
+    }
+
+    fn indice(&self) -> usize {
+        Self::TODAS.iter().position(|v| v == self).unwrap_or(0)
+    }
+
+    fn ayuda(&self) -> &'static str {
+        match self {
+            Vista::Metricas => "",
+            Vista::Graficas => "v: ventana",
+            Vista::Procesos => "↑/↓: mover  o: ordenar por CPU/memoria",
+            Vista::Alertas => "c/C: umbral CPU -/+  m/M: umbral memoria -/+",
+        }
+    }
+}
+
+struct Tui {
+    metrics: Metrics,
+    vista: Vista,
+    umbrales: Umbrales,
+    ventana: usize,
+    procesos: TableState,
+    por_memoria: bool,
+    graficas: Option<Graficas>,
+}
+
+/// Columnas ya calculadas de las gráficas; se recalculan solo cuando llega un
+/// snapshot nuevo o cambian la ventana o el ancho.
+struct Graficas {
+    clave: (usize, u16, i64), // ventana, ancho y marca del último snapshot
+    // por gráfica: valor de cada columna (escalado a 0..=1000) y último valor
+    series: Vec<(Vec<Option<u64>>, Option<f64>)>,
+}
+
+/// Arranca el monitoreo según `configuracion` (o se conecta al daemon en
+/// `socket`) y muestra la interfaz hasta que se pulse `q`.
+pub fn ejecutar(socket: Option<&Path>, configuracion: &Configuracion) -> io::Result<()> {
+    let mut metrics = Metrics::con_configuracion(configuracion);
+    match socket {
+        Some(ruta) => {
+            let historico = metrics.conectar_daemon(ruta).esperar()?;
+            metrics.usar_daemon(historico);
+        }
+        None => metrics.start_monitoring_con(configuracion.registro()),
+    }
+    let mut tui = Tui {
+        metrics,
+        vista: Vista::Metricas,
+        umbrales: configuracion.umbrales(),
+        ventana: 0,
+        procesos: TableState::default().with_selected(0),
+        por_memoria: false,
+        graficas: None,
+    };
+    let mut terminal = ratatui::init();
+    let resultado = tui.bucle(&mut terminal);
+    // la terminal se restaura aunque el bucle falle
+    ratatui::restore();
+    tui.metrics.stop_monitoring();
+    resultado
+}
+
+impl Tui {
+    fn bucle(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            self.metrics.recibir();
+            terminal.draw(|frame| self.dibujar(frame))?;
+            if !event::poll(REFRESCO)? {
+                continue;
+            }
+            let Event::Key(tecla) = event::read()? else { continue };
+            if tecla.kind != KeyEventKind::Press {
+                continue;
+            }
+            let actual = self.vista.indice();
+            match tecla.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Tab | KeyCode::Right => self.vista = Vista::TODAS[(actual + 1) % Vista::TODAS.len()],
+                KeyCode::BackTab | KeyCode::Left => {
+                    self.vista = Vista::TODAS[(actual + Vista::TODAS.len() - 1) % Vista::TODAS.len()]
+                }
+                KeyCode::Char(c @ '1'..='4') => self.vista = Vista::TODAS[c as usize - '1' as usize],
+                codigo => self.tecla_vista(codigo),
+            }
+        }
+    }
+
+    fn tecla_vista(&mut self, codigo: KeyCode) {
+        match (self.vista, codigo) {
+            (Vista::Graficas, KeyCode::Char('v')) => self.ventana = (self.ventana + 1) % VENTANAS.len(),
+            (Vista::Procesos, KeyCode::Down) => self.procesos.select_next(),
+            (Vista::Procesos, KeyCode::Up) => self.procesos.select_previous(),
+            (Vista::Procesos, KeyCode::Char('o')) => self.por_memoria = !self.por_memoria,
+            (Vista::Alertas, KeyCode::Char('c')) => self.umbrales.cpu = (self.umbrales.cpu - 5.0).max(0.0),
+            (Vista::Alertas, KeyCode::Char('C')) => self.umbrales.cpu = (self.umbrales.cpu + 5.0).min(100.0),
+            (Vista::Alertas, KeyCode::Char('m')) => self.umbrales.memoria = (self.umbrales.memoria - 5.0).max(0.0),
+            (Vista::Alertas, KeyCode::Char('M')) => self.umbrales.memoria = (self.umbrales.memoria + 5.0).min(100.0),
+            _ => {}
+        }
+    }
+
+    fn dibujar(&mut self, frame: &mut Frame) {
+        let [arriba, cuerpo, abajo] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+        let cantidad_alertas = self
+            .metrics
+            .latest()
+            .map_or(0, |m| alertas::evaluar(m, &self.umbrales).len());
+        let titulos = Vista::TODAS.iter().enumerate().map(|(i, v)| {
+            if *v == Vista::Alertas && cantidad_alertas > 0 {
+                format!("{} {} ({})", i + 1, v.titulo(), cantidad_alertas).red()
+            } else {
+                format!("{} {}", i + 1, v.titulo()).into()
+            }
+        });
+        let tabs = Tabs::new(titulos)
+            .select(self.vista.indice())
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .block(Block::default().borders(Borders::ALL).title(" Sistema de Defensa "));
+        frame.render_widget(tabs, arriba);
+
+        let ayuda = format!("q: salir  Tab/←/→: vista  {}", self.vista.ayuda());
+        frame.render_widget(Paragraph::new(ayuda).dark_gray(), abajo);
+
+        let Some(metric) = self.metrics.latest().cloned() else {
+            frame.render_widget(Paragraph::new("Esperando el primer snapshot..."), cuerpo);
+            return;
+        };
+        match self.vista {
+            Vista::Metricas => Self::dibujar_metricas(frame, cuerpo, &metric),
+            Vista::Graficas => self.dibujar_graficas(frame, cuerpo, &metric),
+            Vista::Procesos => self.dibujar_procesos(frame, cuerpo, &metric),
+            Vista::Alertas => self.dibujar_alertas(frame, cuerpo, &metric),
+        }
+    }
+
+    fn dibujar_metricas(frame: &mut Frame, area: Rect, metric: &Metric) {
+        let valores = metric.valores();
+        let [barras, texto] = Layout::vertical([Constraint::Length(6), Constraint::Min(0)]).areas(area);
+        let filas = Layout::vertical([Constraint::Length(2); 3]).split(barras);
+        for (i, (titulo, serie)) in [("CPU", "cpu.uso"), ("Memoria", "memoria.uso"), ("Disco", "disco.uso")].iter().enumerate() {
+            let uso = valores.get(*serie).copied().unwrap_or(0.0);
+            let gauge = Gauge::default()
+                .block(Block::default().title(*titulo))
+                .gauge_style(Style::new().fg(if uso > 80.0 { Color::Red } else { Color::Green }))
+                .ratio((uso / 100.0).clamp(0.0, 1.0))
+                .label(format!("{:.1}%", uso));
+            frame.render_widget(gauge, filas[i]);
+        }
+
+        let mut lineas: Vec<Line> = vec![Line::from(format!("ID: {}", metric.id))];
+        for (nombre, error) in &metric.errores {
+            let sufijo = if metric.lecturas.contains_key(nombre) { " (se muestra la última lectura válida)" } else { "" };
+            lineas.push(format!("{}: {}{}", nombre, error, sufijo).yellow().into());
+        }
+        if let Some(inventario) = metric.inventario() {
+            lineas.push(Line::from(format!(
+                "Host: {}  OS: {}  Kernel: {}  CPU: {} ({} físicos / {} lógicos)",
+                inventario.get_nombre_equipo(),
+                inventario.get_sistema_operativo(),
+                inventario.get_version_kernel(),
+                inventario.get_modelo_cpu(),
+                inventario.get_nucleos_fisicos(),
+                inventario.get_nucleos_logicos()
+            )));
+        }
+        if let Some(cpu) = metric.cpu() {
+            let nucleos: Vec<String> = cpu.get_uso_nucleos().iter().map(|u| format!("{:.0}%", u)).collect();
+            lineas.push(Line::from(format!("CPU: {} MHz, núcleos: {}", cpu.get_frecuencia(), nucleos.join(" "))));
+        }
+        if let Some(memoria) = metric.memoria() {
+            lineas.push(Line::from(format!(
+                "Memoria: {} / {} MB usados, swap: {} / {} MB",
+                memoria.get_memoria_usada(), memoria.get_memoria_total(), memoria.get_swap_usada(), memoria.get_swap_total()
+            )));
+        }
+        if let Some(red) = metric.red() {
+            lineas.push(Line::from(format!(
+                "Red: {} interfaces, RX {} bytes, TX {} bytes",
+                red.get_cantidad_interfaces(), red.get_bytes_recibidos(), red.get_bytes_enviados()
+            )));
+        }
+        if let Some(disco) = metric.disco() {
+            lineas.push(Line::from(format!(
+                "Disco: {} discos, {:.1} / {:.1} GB usados",
+                disco.get_cantidad_discos(), disco.get_espacio_usado(), disco.get_espacio_total()
+            )));
+        }
+        if let Some(procesos) = metric.procesos() {
+            lineas.push(Line::from(format!("Procesos: {}", procesos.get_cantidad_procesos())));
+        }
+        let fuentes: Vec<String> = metric
+            .lecturas
+            .keys()
+            .filter_map(|nombre| Some(format!("{} {:.0} s", nombre, metric.antiguedad(nombre)?)))
+            .collect();
+        lineas.push(Line::from(format!("Fuentes (antigüedad): {}", fuentes.join(", "))).dark_gray());
+
+        let parrafo = Paragraph::new(lineas)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::TOP).title(" Detalle "));
+        frame.render_widget(parrafo, texto);
+    }
+
+    fn dibujar_graficas(&mut self, frame: &mut Frame, area: Rect, metric: &Metric) {
+        let nombre_ventana = VENTANAS[self.ventana].0;
+        let areas = Layout::vertical([Constraint::Ratio(1, 4); 4]).split(area);
+        let ancho = area.width.saturating_sub(2);
+        let clave = (self.ventana, ancho, metric.marca);
+        if self.graficas.as_ref().is_none_or(|g| g.clave != clave) {
+            self.graficas = Some(Graficas { clave, series: self.calcular_graficas(metric, ancho as usize) });
+        }
+        let Some(graficas) = &self.graficas else { return };
+        for (i, ((titulo, _, _, color), (datos, ultimo))) in GRAFICAS.iter().zip(&graficas.series).enumerate() {
+            let ultimo = ultimo.map_or("-".to_string(), |v| format!("{:.1}", v));
+            let sparkline = Sparkline::default()
+                .block(Block::default().borders(Borders::ALL).title(format!(" {} — {} (últimos {}) ", titulo, ultimo, nombre_ventana)))
+                .data(datos)
+                .max(1000)
+                .style(Style::new().fg(*color));
+            frame.render_widget(sparkline, areas[i]);
+        }
+    }
+
+    // Promedia cada serie de la ventana en `columnas` tramos iguales
+    fn calcular_graficas(&self, metric: &Metric, columnas: usize) -> Vec<(Vec<Option<u64>>, Option<f64>)> {
+        let ahora = metric.marca.max(Utc::now().timestamp_millis());
+        let desde = ahora - VENTANAS[self.ventana].1 * 60_000;
+        GRAFICAS
+            .iter()
+            .map(|(_, serie, maximo, _)| {
+                let puntos = self.metrics.serie(serie, desde, ahora);
+                let promedios = promediar_columnas(&puntos, desde, ahora, columnas);
+                let tope = maximo.unwrap_or_else(|| promedios.iter().flatten().copied().fold(0.0, f64::max));
+                // Sparkline solo acepta enteros: los valores se escalan a 0..=1000
+                let datos = promedios
+                    .iter()
+                    .map(|v| v.map(|v| if tope > 0.0 { (v / tope * 1000.0).round().max(0.0) as u64 } else { 0 }))
+                    .collect();
+                (datos, puntos.last().map(|p| p.1))
+            })
+            .collect()
+    }
+
+    fn dibujar_procesos(&mut self, frame: &mut Frame, area: Rect, metric: &Metric) {
+        let Some(procesos) = metric.procesos() else {
+            frame.render_widget(Paragraph::new("Sin lectura de procesos."), area);
+            return;
+        };
+        let mut ordenados: Vec<_> = procesos.get_procesos().iter().collect();
+        if self.por_memoria {
+            ordenados.sort_by_key(|p| std::cmp::Reverse(p.get_uso_memoria()));
+        } else {
+            ordenados.sort_by(|a, b| b.get_uso_cpu().total_cmp(&a.get_uso_cpu()));
+        }
+        let filas = ordenados.iter().map(|p| {
+            Row::new(vec![
+                p.get_pid().to_string(),
+                p.get_ppid().map(|ppid| ppid.to_string()).unwrap_or_default(),
+                p.get_usuario().to_string(),
+                format!("{:.1}", p.get_uso_cpu()),
+                p.get_uso_memoria().to_string(),
+                p.get_estado().to_string(),
+                p.get_nombre().to_string(),
+            ])
+        });
+        let anchos = [
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Min(10),
+        ];
+        let orden = if self.por_memoria { "memoria" } else { "CPU" };
+        let tabla = Table::new(filas, anchos)
+            .header(Row::new(["PID", "PPID", "USUARIO", "CPU %", "MEM MB", "ESTADO", "NOMBRE"]).bold())
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} procesos, ordenados por {} ", procesos.get_cantidad_procesos(), orden)),
+            );
+        frame.render_stateful_widget(tabla, area, &mut self.procesos);
+    }
+
+    fn dibujar_alertas(&self, frame: &mut Frame, area: Rect, metric: &Metric) {
+        let [umbrales, lista] = Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+        let texto = format!("Umbral CPU: {:.0}%   Umbral memoria: {:.0}%", self.umbrales.cpu, self.umbrales.memoria);
+        frame.render_widget(Paragraph::new(texto).block(Block::default().borders(Borders::ALL).title(" Umbrales ")), umbrales);
+
+        let activas = alertas::evaluar(metric, &self.umbrales);
+        let bloque = Block::default().borders(Borders::ALL).title(format!(" Alertas activas: {} ", activas.len()));
+        if activas.is_empty() {
+            frame.render_widget(Paragraph::new("Sin alertas.").green().block(bloque), lista);
+        } else {
+            let elementos = activas.iter().map(|a| Line::from(a.to_string()).red());
+            frame.render_widget(List::new(elementos).block(bloque), lista);
+        }
+    }
+}
+
+/// Promedio de los puntos que caen en cada uno de `columnas` tramos iguales de
+/// `[desde, hasta]`; un tramo sin puntos queda en `None`.
+fn promediar_columnas(puntos: &[(i64, f64)], desde: i64, hasta: i64, columnas: usize) -> Vec<Option<f64>> {
+    if columnas == 0 {
+        return Vec::new();
+    }
+    let tramo = (hasta - desde).max(1) as f64 / columnas as f64;
+    let mut sumas = vec![(0.0, 0u32); columnas];
+    for &(marca, valor) in puntos {
+        let i = (((marca - desde) as f64 / tramo) as usize).min(columnas - 1);
+        sumas[i].0 += valor;
+        sumas[i].1 += 1;
+    }
+    sumas.into_iter().map(|(suma, n)| (n > 0).then(|| suma / n as f64)).collect()
+}